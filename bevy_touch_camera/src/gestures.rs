@@ -0,0 +1,356 @@
+use bevy::{
+    input::{touch, InputSystem},
+    prelude::{
+        App, Event, EventWriter, IntoSystemConfigs, Plugin, PreUpdate, Res, ResMut, Resource,
+        Touches, Vec2,
+    },
+    time::Time,
+};
+
+use crate::GesturePhase;
+
+/// A plugin that turns the raw `Touches` into [`GestureEvent`]s. [`TouchCameraPlugin`](crate::TouchCameraPlugin)
+/// adds it if it isn't there yet, add it on its own to only recognize gestures
+#[derive(Default)]
+pub struct GesturePlugin {
+    pub config: GestureConfig,
+}
+
+impl Plugin for GesturePlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(feature = "mouse")]
+        if !app.is_plugin_added::<crate::MouseTouchPlugin>() {
+            app.add_plugins(crate::MouseTouchPlugin::default());
+        }
+        app.insert_resource(Recognizer::default())
+            .insert_resource(self.config.clone())
+            .add_event::<GestureEvent>()
+            .add_systems(PreUpdate, recognize_gestures.after(InputSystem));
+    }
+}
+
+/// The thresholds of the gesture recognizer. Distances are in logical pixels, times in seconds.
+/// A copy of this will be attached as a `Resource` to the `App`, changes take effect on the next frame
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct GestureConfig {
+    /// Longest time a finger can stay down for a tap
+    pub tap_max_duration: f32,
+    /// Farthest a finger can move for a tap or a long press
+    pub tap_max_distance: f32,
+    /// Longest time between two taps of a double tap
+    pub double_tap_interval: f32,
+    /// Farthest two taps of a double tap can be apart
+    pub double_tap_max_distance: f32,
+    /// Time a finger has to stay down for a long press
+    pub long_press_duration: f32,
+    /// Shortest distance a finger has to move for a swipe
+    pub swipe_min_distance: f32,
+    /// Lowest average speed of a swipe in logical pixels per second
+    pub swipe_min_speed: f32,
+    /// Distance a finger has to move before a pan starts. At `0.` every touch of a single
+    /// finger is a pan, also the ones that end up as taps
+    pub pan_min_distance: f32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            tap_max_duration: 0.3,
+            tap_max_distance: 10.,
+            double_tap_interval: 0.3,
+            double_tap_max_distance: 40.,
+            long_press_duration: 0.5,
+            swipe_min_distance: 50.,
+            swipe_min_speed: 500.,
+            pan_min_distance: 0.,
+        }
+    }
+}
+
+/// A gesture recognized from the touches. Positions are in logical window coordinates with y
+/// pointing down. Phases follow [`GesturePhase`], without `Gliding`
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum GestureEvent {
+    /// A single finger was lifted quickly without moving
+    Tap { position: Vec2 },
+    /// A second tap close to the last one. It comes after the [`GestureEvent::Tap`] of that tap
+    DoubleTap { position: Vec2 },
+    /// A single finger stays down without moving. It ends when the finger is lifted
+    LongPress { phase: GesturePhase, position: Vec2 },
+    /// A single finger moved far and fast, sent when it is lifted
+    Swipe {
+        direction: SwipeDirection,
+        start: Vec2,
+        end: Vec2,
+        /// The average speed in logical pixels per second
+        velocity: Vec2,
+    },
+    /// A single finger moving. It ends when the finger is lifted or another one touches down
+    Pan {
+        phase: GesturePhase,
+        /// Where the finger touched down
+        start: Vec2,
+        position: Vec2,
+        /// How far the finger moved since the last event
+        delta: Vec2,
+    },
+    /// Two fingers moving. It ends as soon as one of them is lifted or a third one touches down.
+    /// The fingers don't pan after that until all of them were lifted
+    Pinch {
+        phase: GesturePhase,
+        fingers: [Vec2; 2],
+        /// Where the fingers were at the last event
+        last: [Vec2; 2],
+    },
+}
+
+/// The main direction of a [`GestureEvent::Swipe`] on the screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl SwipeDirection {
+    fn of(distance: Vec2) -> Self {
+        match (
+            distance.x.abs() > distance.y.abs(),
+            distance.x > 0.,
+            distance.y > 0.,
+        ) {
+            (true, true, _) => Self::Right,
+            (true, false, _) => Self::Left,
+            (false, _, true) => Self::Down,
+            (false, _, false) => Self::Up,
+        }
+    }
+}
+
+#[derive(Default)]
+enum Mode {
+    #[default]
+    None,
+    Single(Single),
+    Pinch {
+        ids: [u64; 2],
+        last: [Vec2; 2],
+    },
+    // Too many fingers or one left over from a pinch. Waits until all are lifted
+    Cancelled,
+}
+
+struct Single {
+    id: u64,
+    start: Vec2,
+    started_at: f32,
+    last: Vec2,
+    // Whether the finger ever left the tap distance
+    moved: bool,
+    panning: bool,
+    long_pressing: bool,
+}
+
+#[derive(Resource, Default)]
+struct Recognizer {
+    mode: Mode,
+    // Time and position of the last tap, for double taps
+    last_tap: Option<(f32, Vec2)>,
+}
+
+impl Single {
+    fn update(
+        mut self,
+        position: Vec2,
+        config: &GestureConfig,
+        now: f32,
+        events: &mut EventWriter<GestureEvent>,
+    ) -> Self {
+        let single = &mut self;
+        if position.distance(single.start) > config.tap_max_distance {
+            single.moved = true;
+        }
+        let delta = position - single.last;
+        if !single.panning && position.distance(single.start) >= config.pan_min_distance {
+            single.panning = true;
+            events.send(GestureEvent::Pan {
+                phase: GesturePhase::Started,
+                start: single.start,
+                position,
+                delta: position - single.start,
+            });
+        } else if single.panning && delta != Vec2::ZERO {
+            events.send(GestureEvent::Pan {
+                phase: GesturePhase::Moved,
+                start: single.start,
+                position,
+                delta,
+            });
+        }
+        if !single.long_pressing
+            && !single.moved
+            && now - single.started_at >= config.long_press_duration
+        {
+            single.long_pressing = true;
+            events.send(GestureEvent::LongPress {
+                phase: GesturePhase::Started,
+                position,
+            });
+        } else if single.long_pressing && delta != Vec2::ZERO {
+            events.send(GestureEvent::LongPress {
+                phase: GesturePhase::Moved,
+                position,
+            });
+        }
+        single.last = position;
+        self
+    }
+}
+
+impl Recognizer {
+    /// Ends the gesture of a single finger, because it was `lifted` or other fingers joined
+    fn end_single(
+        &mut self,
+        single: Single,
+        position: Vec2,
+        lifted: bool,
+        config: &GestureConfig,
+        now: f32,
+        events: &mut EventWriter<GestureEvent>,
+    ) {
+        if single.panning {
+            events.send(GestureEvent::Pan {
+                phase: GesturePhase::Ended,
+                start: single.start,
+                position,
+                delta: position - single.last,
+            });
+        }
+        if single.long_pressing {
+            events.send(GestureEvent::LongPress {
+                phase: GesturePhase::Ended,
+                position,
+            });
+        }
+        if !lifted {
+            return;
+        }
+
+        let duration = now - single.started_at;
+        let distance = position - single.start;
+        if !single.moved
+            && !single.long_pressing
+            && distance.length() <= config.tap_max_distance
+            && duration <= config.tap_max_duration
+        {
+            events.send(GestureEvent::Tap { position });
+            match self.last_tap.take() {
+                Some((time, at))
+                    if now - time <= config.double_tap_interval
+                        && at.distance(position) <= config.double_tap_max_distance =>
+                {
+                    events.send(GestureEvent::DoubleTap { position });
+                }
+                _ => self.last_tap = Some((now, position)),
+            }
+        } else if distance.length() >= config.swipe_min_distance
+            && duration > 0.
+            && distance.length() / duration >= config.swipe_min_speed
+        {
+            events.send(GestureEvent::Swipe {
+                direction: SwipeDirection::of(distance),
+                start: single.start,
+                end: position,
+                velocity: distance / duration,
+            });
+        }
+    }
+}
+
+fn recognize_gestures(
+    touches: Res<Touches>,
+    mut recognizer: ResMut<Recognizer>,
+    mut events: EventWriter<GestureEvent>,
+    config: Res<GestureConfig>,
+    time: Res<Time>,
+) {
+    let now = time.elapsed_seconds();
+    let mut pressed: Vec<&touch::Touch> = touches.iter().collect();
+    pressed.sort_by_key(|touch| touch.id());
+    let ids: Vec<u64> = pressed.iter().map(|touch| touch.id()).collect();
+    let positions: Vec<Vec2> = pressed.iter().map(|touch| touch.position()).collect();
+
+    // continue or end the current gesture
+    let mode = match std::mem::take(&mut recognizer.mode) {
+        Mode::Single(single) if ids == [single.id] => {
+            Mode::Single(single.update(positions[0], &config, now, &mut events))
+        }
+        Mode::Single(single) => {
+            let position = touches
+                .get_released(single.id)
+                .or(touches.get_pressed(single.id))
+                .map_or(single.last, |touch| touch.position());
+            let lifted = pressed.is_empty();
+            recognizer.end_single(single, position, lifted, &config, now, &mut events);
+            Mode::None
+        }
+        Mode::Pinch {
+            ids: pinch_ids,
+            last,
+        } if ids == pinch_ids => {
+            let fingers = [positions[0], positions[1]];
+            if fingers != last {
+                events.send(GestureEvent::Pinch {
+                    phase: GesturePhase::Moved,
+                    fingers,
+                    last,
+                });
+            }
+            Mode::Pinch {
+                ids: pinch_ids,
+                last: fingers,
+            }
+        }
+        Mode::Pinch { last, .. } => {
+            events.send(GestureEvent::Pinch {
+                phase: GesturePhase::Ended,
+                fingers: last,
+                last,
+            });
+            Mode::Cancelled
+        }
+        mode => mode,
+    };
+
+    // start a new one
+    recognizer.mode = match (mode, pressed.as_slice()) {
+        (Mode::Cancelled, []) => Mode::None,
+        (Mode::None, [touch]) => {
+            let single = Single {
+                id: touch.id(),
+                start: touch.position(),
+                started_at: now,
+                last: touch.position(),
+                moved: false,
+                panning: false,
+                long_pressing: false,
+            };
+            Mode::Single(single.update(touch.position(), &config, now, &mut events))
+        }
+        (Mode::None, [a, b]) => {
+            let fingers = [a.position(), b.position()];
+            events.send(GestureEvent::Pinch {
+                phase: GesturePhase::Started,
+                fingers,
+                last: fingers,
+            });
+            Mode::Pinch {
+                ids: [a.id(), b.id()],
+                last: fingers,
+            }
+        }
+        (Mode::None, [_, _, _, ..]) => Mode::Cancelled,
+        (mode, _) => mode,
+    };
+}
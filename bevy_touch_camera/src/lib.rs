@@ -1,4 +1,5 @@
-//! A plugin for touch based camera movement. Supports one finger drag to pan and two finger pinch to zoom.
+//! A plugin for touch based camera movement of 2D and 3D cameras. Supports one finger drag to pan, two finger
+//! pinch to zoom and optionally two finger twist to rotate.
 //!
 //! ## How to use
 //! Add [`TouchCameraPlugin`] to your app.
@@ -11,7 +12,8 @@
 //!
 //! You can configure some of the parameters of the plugin by passing a [`TouchCameraConfig`] object.
 //!
-//! Changing configuration at runtime is currently not supported. [^1]
+//! The configuration can be changed at runtime through the `TouchCameraConfig` resource. Changes take effect
+//! on the next frame, also in the middle of a gesture, without the camera jumping.
 //! ```ignore
 //! let config = TouchCameraConfig {
 //!     drag_sensitivity: 2.,
@@ -21,11 +23,102 @@
 //! app.add_plugins((TouchCameraPlugin {config}))
 //! ```
 //!
+//! Set [`TouchCameraConfig::world_anchored`] to keep the world position under a dragging finger and under the
+//! middle of a pinch, like in maps or strategy games.
+//!
+//! Set [`TouchCameraConfig::momentum`] to let the camera glide on after a drag or a pinch and slow down with
+//! the friction of [`Momentum`]. A new touch stops it.
+//! ```ignore
+//! let config = TouchCameraConfig {
+//!     momentum: Some(Momentum { friction: 2., ..Default::default() }),
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! Set [`TouchCameraConfig::twist`] to rotate the camera around the middle of two twisting fingers. The
+//! [`Twist::threshold`] keeps pinches from rotating it, and [`Twist::snap`] turns it to the closest multiple of
+//! 90° when the fingers are lifted. Panning follows the rotation of the camera.
+//!
+//! [`TouchCameraConfig::min_scale`] and [`TouchCameraConfig::max_scale`] limit the zoom, and
+//! [`TouchCameraConfig::bounds`] keeps the view inside a rectangle of the world, either stopping at its edge or
+//! with a rubber band. [`content_bounds`] computes such a rectangle around the entities of a scene.
+//! ```ignore
+//! let config = TouchCameraConfig {
+//!     max_scale: 4.,
+//!     bounds: Some(Bounds {
+//!         rect: Rect::new(-1000., -500., 1000., 500.),
+//!         mode: BoundsMode::RubberBand { stretch: 0.3, stiffness: 8. },
+//!     }),
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! To give a camera its own parameters, attach a `TouchCameraConfig` component to it. It overrides the resource
+//! for that camera only.
+//! ```ignore
+//! cmds.spawn((Camera2dBundle::default(), TouchCameraTag, TouchCameraConfig {
+//!     drag_sensitivity: 0.5,
+//!     ..Default::default()
+//! }));
+//! ```
+//!
 //! The plugin will try to attach itself to a camera. This can be done in either one of the following ways:
 //! 1) Create a single `Camera` component before the `PostUpdate` schedule. The plugin will attach itself automatically to it.
 //! 2) Manually attach a `TouchCameraTag` component to the camera entity you want to be handled by the plugin.Useful if you have multiple active cameras or if method 1) is not possible.
 //!
-//! [^1]: TODO maybe it is, need to test it and update example
+//! All tagged cameras follow the gestures.
+//!
+//! Every frame a gesture moves the cameras, a [`TouchCameraEvent`] with the pan delta, the zoom factor and the
+//! [`GesturePhase`] is sent. Set [`TouchCameraConfig::move_cameras`] to `false` to leave the cameras alone and
+//! only read the events, e.g. to combine them with other inputs in your own camera controller.
+//! ```ignore
+//! fn my_camera(mut events: EventReader<TouchCameraEvent>, mut cameras: Query<&mut MyCamera>) {
+//!     for event in events.read() {
+//!         for mut camera in &mut cameras {
+//!             camera.offset -= event.pan;
+//!             camera.zoom *= event.zoom;
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The gestures come from the [`GesturePlugin`], which the `TouchCameraPlugin` adds for you. It turns the touches
+//! into [`GestureEvent`]s: taps, double taps, long presses, swipes, pans and pinches, with the thresholds of the
+//! [`GestureConfig`] resource. Read them for your own actions, or add the `GesturePlugin` on its own if you don't
+//! need the camera.
+//! ```ignore
+//! fn select(mut gestures: EventReader<GestureEvent>) {
+//!     for gesture in gestures.read() {
+//!         if let GestureEvent::Tap { position } = gesture {
+//!             // ...
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Set [`TouchCameraConfig::double_tap_zoom`] to zoom in on the point of a double tap.
+//!
+//! With the `mouse` feature the `GesturePlugin` also adds the `MouseTouchPlugin`, which turns the mouse into touches
+//! for developing without a touchscreen: dragging pans, scrolling and trackpad pinches zoom around the cursor, and
+//! dragging while holding the left Alt key puts down a second finger that mirrors the cursor, to pinch and twist.
+//! `MouseTouchConfig` changes the button, the key and the distances.
+//!
+//! Cameras with a `PerspectiveProjection`, like a `Camera3dBundle`, pan in their screen plane, as far as the
+//! [`Perspective::focus_distance`] in front of them moves with the fingers. A pinch dollies towards that focus
+//! point or changes the field of view, see [`PerspectiveZoom`], and with [`Perspective::orbit`] one finger orbits
+//! around it while two fingers pan.
+//! ```ignore
+//! let config = TouchCameraConfig {
+//!     perspective: Perspective { orbit: true, focus_distance: 20., ..Default::default() },
+//!     ..Default::default()
+//! };
+//! ```
+mod gestures;
+#[cfg(feature = "mouse")]
+mod mouse;
 mod plugin;
 
+pub use gestures::*;
+#[cfg(feature = "mouse")]
+pub use mouse::*;
 pub use plugin::*;
@@ -0,0 +1,175 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::{
+        keyboard::KeyboardInput,
+        mouse::{MouseButtonInput, MouseScrollUnit, MouseWheel},
+        touch::{TouchInput, TouchPhase},
+        touchpad::TouchpadMagnify,
+        ButtonState, InputSystem,
+    },
+    prelude::{
+        App, EventReader, EventWriter, IntoSystemConfigs, KeyCode, MouseButton, Plugin, PreUpdate,
+        Res, ResMut, Resource, Vec2,
+    },
+    window::CursorMoved,
+};
+
+/// A plugin that turns the mouse into touches, so the [`GesturePlugin`](crate::GesturePlugin) and
+/// the camera behave on a desktop like on a touchscreen. Needs the `mouse` feature, with it the
+/// `GesturePlugin` adds this plugin if it isn't there yet
+#[derive(Default)]
+pub struct MouseTouchPlugin {
+    pub config: MouseTouchConfig,
+}
+
+impl Plugin for MouseTouchPlugin {
+    fn build(&self, app: &mut App) {
+        // the cursor is followed also without a window plugin, e.g. in tests
+        app.add_event::<CursorMoved>()
+            .insert_resource(MouseFingers::default())
+            .insert_resource(self.config.clone())
+            .add_systems(PreUpdate, emulate_touches.before(InputSystem));
+    }
+}
+
+/// How the mouse emulates fingers. Distances are in logical pixels.
+/// A copy of this will be attached as a `Resource` to the `App`, changes take effect on the next frame
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct MouseTouchConfig {
+    /// The button that puts a finger down at the cursor
+    pub button: MouseButton,
+    /// Holding this key while dragging puts down a second finger, which mirrors the movement of
+    /// the cursor around the middle of both, to pinch and twist
+    pub second_finger: KeyCode,
+    /// Where the second finger touches down, relative to the cursor. Scrolling pinches two
+    /// fingers this far apart around the cursor
+    pub second_finger_offset: Vec2,
+    /// How much scrolling by one line spreads or closes the fingers of a pinch, as a factor of
+    /// their distance. Trackpads scrolling by pixels count 20 pixels as a line
+    pub scroll_zoom: f32,
+}
+
+impl Default for MouseTouchConfig {
+    fn default() -> Self {
+        Self {
+            button: MouseButton::Left,
+            second_finger: KeyCode::AltLeft,
+            second_finger_offset: Vec2::new(-200., 0.),
+            scroll_zoom: 0.1,
+        }
+    }
+}
+
+const PIXELS_PER_LINE: f32 = 20.;
+
+// The ids of the emulated touches, far from the ones of real touchscreens
+const DRAG_IDS: [u64; 2] = [u64::MAX - 3, u64::MAX - 2];
+const SCROLL_IDS: [u64; 2] = [u64::MAX - 1, u64::MAX];
+
+#[derive(Resource, Default)]
+struct MouseFingers {
+    cursor: Vec2,
+    pressed: bool,
+    second_pressed: bool,
+    // The point the second finger mirrors the cursor around
+    pivot: Option<Vec2>,
+    drag: [Option<Vec2>; 2],
+    scroll: [Option<Vec2>; 2],
+    // The factor scrolled while the pinch of the scroll fingers was starting
+    pending_zoom: Option<f32>,
+}
+
+/// Sends the touch events for fingers that moved from `from` to `to`
+fn move_fingers(
+    ids: [u64; 2],
+    from: &mut [Option<Vec2>; 2],
+    to: [Option<Vec2>; 2],
+    touches: &mut EventWriter<TouchInput>,
+) {
+    for ((id, from), to) in ids.into_iter().zip(from.iter_mut()).zip(to) {
+        let (phase, position) = match (*from, to) {
+            (None, Some(to)) => (TouchPhase::Started, to),
+            (Some(from), Some(to)) if from != to => (TouchPhase::Moved, to),
+            (Some(from), None) => (TouchPhase::Ended, from),
+            _ => continue,
+        };
+        touches.send(TouchInput {
+            phase,
+            position,
+            force: None,
+            id,
+        });
+        *from = to;
+    }
+}
+
+/// The desktop input the fingers are emulated from
+#[derive(SystemParam)]
+struct MouseEvents<'w, 's> {
+    cursor: EventReader<'w, 's, CursorMoved>,
+    buttons: EventReader<'w, 's, MouseButtonInput>,
+    keys: EventReader<'w, 's, KeyboardInput>,
+    wheel: EventReader<'w, 's, MouseWheel>,
+    magnify: EventReader<'w, 's, TouchpadMagnify>,
+}
+
+fn emulate_touches(
+    mut input: MouseEvents,
+    mut fingers: ResMut<MouseFingers>,
+    mut touches: EventWriter<TouchInput>,
+    config: Res<MouseTouchConfig>,
+) {
+    if let Some(cursor) = input.cursor.read().last() {
+        fingers.cursor = cursor.position;
+    }
+    for event in input.buttons.read() {
+        if event.button == config.button {
+            fingers.pressed = event.state == ButtonState::Pressed;
+        }
+    }
+    for event in input.keys.read() {
+        if event.key_code == Some(config.second_finger) {
+            fingers.second_pressed = event.state == ButtonState::Pressed;
+        }
+    }
+    let lines: f32 = input
+        .wheel
+        .read()
+        .map(|wheel| match wheel.unit {
+            MouseScrollUnit::Line => wheel.y,
+            MouseScrollUnit::Pixel => wheel.y / PIXELS_PER_LINE,
+        })
+        .sum();
+    let magnify: f32 = input.magnify.read().map(|magnify| magnify.0).sum();
+    let zoom = (1. + config.scroll_zoom).powf(lines) * (1. + magnify);
+
+    // dragging
+    let cursor = fingers.cursor;
+    fingers.pivot = match (fingers.pressed && fingers.second_pressed, fingers.pivot) {
+        (true, None) => Some(cursor + config.second_finger_offset / 2.),
+        (true, pivot) => pivot,
+        (false, _) => None,
+    };
+    let drag = [
+        fingers.pressed.then_some(cursor),
+        fingers.pivot.map(|pivot| 2. * pivot - cursor),
+    ];
+    move_fingers(DRAG_IDS, &mut fingers.drag, drag, &mut touches);
+
+    // scrolling and trackpad pinches, the fingers of a pinch start in one frame and move in the next
+    let scroll = match (fingers.scroll, fingers.pending_zoom.take()) {
+        ([Some(a), Some(b)], pending) if zoom != 1. || pending.is_some() => {
+            let (middle, offset) = ((a + b) / 2., (b - a) / 2.);
+            let offset = offset * zoom * pending.unwrap_or(1.);
+            [Some(middle - offset), Some(middle + offset)]
+        }
+        ([Some(_), Some(_)], _) => [None, None],
+        _ if zoom != 1. && !fingers.pressed => {
+            fingers.pending_zoom = Some(zoom);
+            let offset = config.second_finger_offset / 2.;
+            [Some(cursor + offset), Some(cursor - offset)]
+        }
+        _ => [None, None],
+    };
+    move_fingers(SCROLL_IDS, &mut fingers.scroll, scroll, &mut touches);
+}
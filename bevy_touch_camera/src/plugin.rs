@@ -1,13 +1,21 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
 use bevy::{
-    input::touch,
+    log::{error, info},
+    math::Rect,
     prelude::{
-        error, info, App, Camera, Commands, Component, Entity, OrthographicProjection, Plugin,
-        PostStartup, Query, Res, ResMut, Resource, Touches, Transform, Update, Vec2, Vec3, With,
+        AnyOf, App, Camera, Commands, Component, Entity, EulerRot, Event, EventReader, EventWriter,
+        GlobalTransform, Mut, OrthographicProjection, PerspectiveProjection, Plugin, PostStartup,
+        Projection, Quat, Query, Res, ResMut, Resource, Transform, Update, Vec2, Vec3, With,
     },
+    render::primitives::Aabb,
     time::Time,
+    utils::{HashMap, HashSet},
 };
 
-/// A plugin that will update camera movement based on `Touch` gestures that Bevy provides
+use crate::{GestureEvent, GesturePlugin};
+
+/// A plugin that will update camera movement based on the [`GestureEvent`]s of the [`GesturePlugin`]
 #[derive(Default)]
 pub struct TouchCameraPlugin {
     pub config: TouchCameraConfig,
@@ -15,16 +23,23 @@ pub struct TouchCameraPlugin {
 
 impl Plugin for TouchCameraPlugin {
     fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<GesturePlugin>() {
+            app.add_plugins(GesturePlugin::default());
+        }
         app.insert_resource(TouchTracker::default())
             .insert_resource(self.config.clone())
+            .add_event::<TouchCameraEvent>()
             .add_systems(PostStartup, setup)
             .add_systems(Update, touch_pan_zoom);
     }
 }
 
 /// Contains the configuration parameters for the plugin.
-/// A copy of this will be attached as a `Resource` to the `App`.
-#[derive(Resource, Clone)]
+/// A copy of this will be attached as a `Resource` to the `App`. Changes to the resource take effect
+/// on the next frame, also in the middle of a gesture.
+///
+/// Attach it as a `Component` to a tagged camera to override the resource for that camera only.
+#[derive(Resource, Component, Clone, Debug, PartialEq)]
 pub struct TouchCameraConfig {
     /// How far the camera will move relative to the touch drag distance. Higher is faster
     pub drag_sensitivity: f32,
@@ -37,6 +52,35 @@ pub struct TouchCameraConfig {
     /// Higher values make it more tolerant.
     /// Very low values not recommended as it would be overly sensitive
     pub opposites_tolerance: f32,
+    /// Whether the tagged cameras are moved. Turn it off to only read the [`TouchCameraEvent`]s
+    /// and combine them with other inputs in your own camera controller
+    pub move_cameras: bool,
+    /// Keeps the world position under the fingers: a dragging finger stays on the same point and
+    /// the point under the middle of a pinch stays fixed while zooming. The sensitivities and the
+    /// opposites tolerance don't apply then.
+    /// Until a camera knows the size of its viewport, usually before the first frame, it moves
+    /// based on the viewport like without this mode
+    pub world_anchored: bool,
+    /// Keeps the camera gliding after the fingers are lifted, until it slows down or a new touch
+    /// stops it. `None` stops the camera right away
+    pub momentum: Option<Momentum>,
+    /// The smallest `OrthographicProjection::scale` a pinch zooms in to. Keep it above `0.`.
+    /// A perspective camera that dollies stops at this part of the [`Perspective::focus_distance`]
+    pub min_scale: f32,
+    /// The largest `OrthographicProjection::scale` a pinch zooms out to, or the largest part of
+    /// the [`Perspective::focus_distance`] a perspective camera dollies back to
+    pub max_scale: f32,
+    /// Keeps the visible area of orthographic cameras inside a rectangle of the world. `None`
+    /// lets them move anywhere
+    pub bounds: Option<Bounds>,
+    /// Rotates the cameras around the middle of two fingers twisting. `None` only pans and zooms
+    pub twist: Option<Twist>,
+    /// The factor a double tap scales the projection by, keeping the tapped point in place. It
+    /// is sent as a [`GesturePhase::Started`] and a [`GesturePhase::Ended`] [`TouchCameraEvent`].
+    /// `None` or a factor that isn't above `0.` ignores double taps
+    pub double_tap_zoom: Option<f32>,
+    /// How cameras with a `PerspectiveProjection` move
+    pub perspective: Perspective,
 }
 
 impl Default for TouchCameraConfig {
@@ -46,10 +90,245 @@ impl Default for TouchCameraConfig {
             zoom_sensitivity: 0.005,
             touch_time_min: 0.01,
             opposites_tolerance: 0.,
+            move_cameras: true,
+            world_anchored: false,
+            momentum: None,
+            min_scale: 0.001,
+            max_scale: f32::INFINITY,
+            bounds: None,
+            twist: None,
+            double_tap_zoom: None,
+            perspective: Perspective::default(),
+        }
+    }
+}
+
+/// How a camera glides after a gesture, see [`TouchCameraConfig::momentum`].
+/// It starts as fast as the fingers moved in the last moments before they were lifted
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Momentum {
+    /// How quickly the glide slows down. Every second the speed drops by the factor `e^-friction`
+    pub friction: f32,
+    /// Panning stops below this speed, in logical pixels per second
+    pub min_speed: f32,
+    /// Zooming stops below this speed, in the natural logarithm of the scale per second
+    pub min_zoom_speed: f32,
+}
+
+impl Default for Momentum {
+    fn default() -> Self {
+        Self {
+            friction: 4.,
+            min_speed: 10.,
+            min_zoom_speed: 0.05,
+        }
+    }
+}
+
+/// How a camera with a `PerspectiveProjection` moves, see [`TouchCameraConfig::perspective`].
+/// It looks at a focus point in front of it: panning moves the plane through that point with the
+/// fingers, and dollying moves the camera towards it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Perspective {
+    pub zoom: PerspectiveZoom,
+    /// How far in front of the camera the focus point is. A camera that dollied keeps the same
+    /// part of it, so changes take effect right away
+    pub focus_distance: f32,
+    /// Orbits the camera around the focus point with one finger instead of panning. Two fingers
+    /// pan then
+    pub orbit: bool,
+}
+
+impl Default for Perspective {
+    fn default() -> Self {
+        Self {
+            zoom: PerspectiveZoom::Dolly,
+            focus_distance: 10.,
+            orbit: false,
+        }
+    }
+}
+
+/// What a pinch changes on a perspective camera
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PerspectiveZoom {
+    /// Moves the camera along its view direction towards the focus point
+    Dolly,
+    /// Changes the field of view and keeps the camera in place
+    Fov,
+}
+
+// Limits of the field of view a pinch zooms to, in radians
+const MIN_FOV: f32 = 0.01;
+const MAX_FOV: f32 = 3.;
+// Orbiting stops this close to looking straight up or down, as the sine of the pitch
+const MAX_PITCH_SIN: f32 = 0.99;
+
+/// How two fingers rotate a camera, see [`TouchCameraConfig::twist`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Twist {
+    /// How far in radians the fingers have to twist before the camera starts rotating, so a
+    /// pinch doesn't rotate it by accident
+    pub threshold: f32,
+    /// Turns the camera to the closest multiple of 90° when the fingers are lifted
+    pub snap: bool,
+}
+
+impl Default for Twist {
+    fn default() -> Self {
+        Self {
+            threshold: 0.25,
+            snap: false,
+        }
+    }
+}
+
+/// A rectangle of the world the cameras can't leave, see [`TouchCameraConfig::bounds`].
+/// Use [`content_bounds`] to fit it to the content of a scene
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    /// Where the cameras can look, in world coordinates. If a camera sees more than the
+    /// rectangle in one direction, it is centered on it in that direction
+    pub rect: Rect,
+    pub mode: BoundsMode,
+}
+
+/// What happens when a gesture moves a camera past its [`Bounds`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BoundsMode {
+    /// The camera stops at the edge
+    Clamp,
+    /// The camera moves past the edge reluctantly and springs back when the fingers are lifted
+    RubberBand {
+        /// The part of the movement past the edge the camera follows (0. .. 1.)
+        stretch: f32,
+        /// How fast the camera springs back. Every second the distance past the edge shrinks by
+        /// the factor `e^-stiffness`
+        stiffness: f32,
+    },
+}
+
+impl Bounds {
+    /// How far `view` has to move to be inside the bounds
+    fn correction(&self, view: Rect) -> Vec2 {
+        let axis = |view_min: f32, view_max: f32, min: f32, max: f32| {
+            if view_max - view_min > max - min {
+                (min + max - view_min - view_max) / 2.
+            } else if view_min < min {
+                min - view_min
+            } else if view_max > max {
+                max - view_max
+            } else {
+                0.
+            }
+        };
+        Vec2::new(
+            axis(view.min.x, view.max.x, self.rect.min.x, self.rect.max.x),
+            axis(view.min.y, view.max.y, self.rect.min.y, self.rect.max.y),
+        )
+    }
+}
+
+/// The smallest rectangle around the content in world coordinates, e.g. all sprites in a
+/// scene, to use as [`Bounds::rect`]. Entities without an `Aabb` count as a single point.
+/// `None` without any content
+/// ```ignore
+/// fn fit(content: Query<(&GlobalTransform, Option<&Aabb>), With<Sprite>>, mut config: ResMut<TouchCameraConfig>) {
+///     config.bounds = content_bounds(&content).map(|rect| Bounds { rect, mode: BoundsMode::Clamp });
+/// }
+/// ```
+pub fn content_bounds<'a>(
+    content: impl IntoIterator<Item = (&'a GlobalTransform, Option<&'a Aabb>)>,
+) -> Option<Rect> {
+    content
+        .into_iter()
+        .flat_map(|(transform, aabb)| {
+            let (center, half) = aabb.map_or((Vec3::ZERO, Vec3::ZERO), |aabb| {
+                (aabb.center.into(), aabb.half_extents.into())
+            });
+            [-1., 1.].into_iter().flat_map(move |x| {
+                [-1., 1.].into_iter().flat_map(move |y| {
+                    [-1., 1.].into_iter().map(move |z| {
+                        transform
+                            .transform_point(center + half * Vec3::new(x, y, z))
+                            .truncate()
+                    })
+                })
+            })
+        })
+        .fold(None, |rect: Option<Rect>, point| {
+            Some(rect.map_or(Rect::from_corners(point, point), |rect| {
+                rect.union_point(point)
+            }))
+        })
+}
+
+// A pinch zooms in by at most this factor a frame, so fast fingers can't flip the projection
+const MIN_ZOOM_FACTOR: f32 = 0.5;
+
+impl TouchCameraConfig {
+    /// How much a pinch scales the projection
+    fn zoom_factor(&self, pinch: &Pinch) -> f32 {
+        if self.world_anchored {
+            return pinch.ratio;
+        }
+        if pinch.dot_delta > self.opposites_tolerance {
+            return 1.;
+        }
+        (1. + pinch.direction.signum() * pinch.delta_total * self.zoom_sensitivity)
+            .max(MIN_ZOOM_FACTOR)
+    }
+
+    /// The distance the middle of a pinch moved that the camera follows
+    fn pinch_pan(&self, pinch: &Pinch) -> Vec2 {
+        if self.world_anchored {
+            pinch.middle - pinch.last_middle
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    /// The pan of a [`TouchCameraEvent`] for fingers that moved by `distance`
+    fn event_pan(&self, distance: Vec2) -> Vec2 {
+        if self.world_anchored {
+            distance
+        } else {
+            self.drag_sensitivity * distance
         }
     }
 }
 
+/// Sent every frame a gesture moves the cameras, whether [`TouchCameraConfig::move_cameras`] is
+/// set or not. The values follow the `TouchCameraConfig` resource.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct TouchCameraEvent {
+    pub phase: GesturePhase,
+    /// How far the finger moved since the last event in logical pixels, with y pointing down,
+    /// scaled by the drag sensitivity. A camera following the finger moves the opposite way.
+    /// With [`TouchCameraConfig::world_anchored`] it isn't scaled, and a pinch moves it too
+    pub pan: Vec2,
+    /// The factor the pinch scales the projection by, `1.` for no zoom. Below `1.` zooms in.
+    /// Always above `0.`, so it can be multiplied into a zoom of your own
+    pub zoom: f32,
+    /// How far in radians the fingers twisted counterclockwise on the screen, once they passed
+    /// the [`Twist::threshold`]. A camera following the fingers rotates the opposite way.
+    /// Always `0.` without [`TouchCameraConfig::twist`]
+    pub rotation: f32,
+}
+
+/// Where a gesture is in its lifetime
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GesturePhase {
+    /// The first event of a gesture. It can already carry movement
+    Started,
+    Moved,
+    /// The fingers are lifted and the camera keeps moving, see [`TouchCameraConfig::momentum`]
+    Gliding,
+    /// All fingers are lifted and the glide is over or was stopped by a new touch. Carries no
+    /// movement
+    Ended,
+}
+
 /// This is the tag that the plugin will scan for and update its `Camera` component.
 /// You can either attach it manually to your camera, or the plugin will try to attach it
 /// to the default camera in the `PostStartup` schedule
@@ -62,26 +341,466 @@ enum GestureType {
     None,
     Pan,
     Pinch,
-    PinchCancelled,
 }
 
 #[derive(Resource, Default)]
 struct TouchTracker {
-    pub camera_start_pos: Vec3,
     pub time_start_touch: f32,
     pub gesture_type: GestureType,
 
-    // Keeps track of position on last frame.
-    // This is different from Touch.last_position as that only updates when there has been a movement
-    pub last_touch_a: Option<Vec2>,
-    pub last_touch_b: Option<Vec2>,
+    // Where the panning finger touched down and where it is now
+    pub pan: Option<(Vec2, Vec2)>,
+
+    // Touch position each camera has already followed while panning, `None` for the events. The
+    // camera moves by the distance from here, so changing the sensitivity never makes it jump
+    pub pan_origins: HashMap<Option<Entity>, Vec2>,
+    // Whether a `GesturePhase::Started` event was sent and the gesture didn't end yet
+    pub gesture_started: bool,
+    // How fast each camera moves, `None` for the events. Measured during a gesture, used up by
+    // the glide afterwards
+    pub velocities: HashMap<Option<Entity>, Velocity>,
+    // How far the fingers twisted since the second one touched down, and who passed the
+    // threshold and follows the twist
+    pub twist_total: f32,
+    pub twisting: HashSet<Option<Entity>>,
+    // How far each perspective camera dollied towards its focus point, as a factor of the
+    // `Perspective::focus_distance` of its config. Cameras that were never pinched aren't in here
+    pub dolly: HashMap<Entity, f32>,
+}
+
+// Speeds are averaged over about this many seconds, so a release is as fast as the last few frames
+const VELOCITY_SMOOTHING: f32 = 0.05;
+
+#[derive(Default, Clone, Copy)]
+struct Velocity {
+    // Logical pixels per second
+    pan: Vec2,
+    // Natural logarithm of the scale per second
+    zoom: f32,
+    // Where the fingers were last
+    position: Vec2,
+    // Whether a perspective camera orbits instead of panning
+    orbit: bool,
+    // Whether it was tracked this frame
+    tracked: bool,
+}
+
+impl Velocity {
+    fn track(&mut self, distance: Vec2, zoom: f32, position: Vec2, dt: f32) {
+        if dt <= 0. {
+            return;
+        }
+        let weight = (dt / VELOCITY_SMOOTHING).min(1.);
+        self.pan += (distance / dt - self.pan) * weight;
+        self.zoom += (zoom.ln() / dt - self.zoom) * weight;
+        self.position = position;
+        self.tracked = true;
+    }
+
+    /// Applies the friction of `momentum`. Returns whether there is still movement left
+    fn slow_down(&mut self, momentum: &Momentum, dt: f32) -> bool {
+        let factor = (-momentum.friction * dt).exp();
+        self.pan *= factor;
+        self.zoom *= factor;
+        if self.pan.length() < momentum.min_speed {
+            self.pan = Vec2::ZERO;
+        }
+        if self.zoom.abs() < momentum.min_zoom_speed {
+            self.zoom = 0.;
+        }
+        self.pan != Vec2::ZERO || self.zoom != 0.
+    }
 }
 
+impl TouchTracker {
+    /// The distance the finger moved since `key` followed it last, since it touched down if
+    /// `key` didn't follow it yet
+    fn follow(&mut self, key: Option<Entity>, (start, position): (Vec2, Vec2)) -> Vec2 {
+        let origin = self.pan_origins.insert(key, position).unwrap_or(start);
+        position - origin
+    }
+
+    fn track(&mut self, key: Option<Entity>, distance: Vec2, zoom: f32, position: Vec2, dt: f32) {
+        self.velocities
+            .entry(key)
+            .or_default()
+            .track(distance, zoom, position, dt);
+    }
+
+    /// Marks whether the velocity of `camera` orbits a perspective camera
+    fn orbit(&mut self, camera: Entity, orbit: bool) {
+        if let Some(velocity) = self.velocities.get_mut(&Some(camera)) {
+            velocity.orbit = orbit;
+        }
+    }
+
+    /// How far `camera` dollied towards its focus point
+    fn dolly(&mut self, camera: Entity) -> &mut f32 {
+        self.dolly.entry(camera).or_insert(1.)
+    }
+
+    /// How far `key` rotates for a twist of `angle` this frame
+    fn twist(&mut self, key: Option<Entity>, config: &TouchCameraConfig, angle: f32) -> f32 {
+        let Some(twist) = config.twist else {
+            return 0.;
+        };
+        // the twist up to the threshold is lost, so the camera doesn't jump
+        if self.twisting.contains(&key) {
+            angle
+        } else {
+            if self.twist_total.abs() >= twist.threshold {
+                self.twisting.insert(key);
+            }
+            0.
+        }
+    }
+
+    fn event(&mut self, pan: Vec2, zoom: f32, rotation: f32) -> TouchCameraEvent {
+        let phase = if self.gesture_started {
+            GesturePhase::Moved
+        } else {
+            GesturePhase::Started
+        };
+        self.gesture_started = true;
+        TouchCameraEvent {
+            phase,
+            pan,
+            zoom,
+            rotation,
+        }
+    }
+}
+
+struct Pinch {
+    delta_total: f32,
+    dot_delta: f32,
+    direction: f32,
+    // Distance of the fingers in the last frame divided by the current one
+    ratio: f32,
+    // Middle between the fingers in the last frame and now
+    last_middle: Vec2,
+    middle: Vec2,
+    // How far the fingers twisted counterclockwise on the screen since the last frame
+    angle: f32,
+}
+
+/// The world position below `position` in window coordinates
+fn world_position(camera: &Camera, transform: &GlobalTransform, position: Vec2) -> Option<Vec2> {
+    let viewport = camera.logical_viewport_rect()?;
+    camera.viewport_to_world_2d(transform, position - viewport.min)
+}
+
+/// The projection of a tagged camera, whichever way it is stored
+enum CameraProjection<'a> {
+    Orthographic(&'a mut OrthographicProjection),
+    Perspective(&'a mut PerspectiveProjection),
+}
+
+type Projections<'a> = (
+    Option<Mut<'a, OrthographicProjection>>,
+    Option<Mut<'a, PerspectiveProjection>>,
+    Option<Mut<'a, Projection>>,
+);
+
+fn camera_projection<'a>(projections: &'a mut Projections) -> CameraProjection<'a> {
+    match projections {
+        (Some(orthographic), _, _) => CameraProjection::Orthographic(orthographic),
+        (_, Some(perspective), _) => CameraProjection::Perspective(perspective),
+        (_, _, Some(projection)) => match projection.as_mut() {
+            Projection::Orthographic(orthographic) => CameraProjection::Orthographic(orthographic),
+            Projection::Perspective(perspective) => CameraProjection::Perspective(perspective),
+        },
+        (None, None, None) => unreachable!("the query needs one of the projections"),
+    }
+}
+
+/// How the fingers move a camera in one frame
+#[derive(Clone, Copy)]
+struct Motion {
+    /// Where the fingers are now, in window coordinates
+    position: Vec2,
+    /// How far they moved since the last frame
+    distance: Vec2,
+    /// The factor they scale the projection by
+    zoom: f32,
+    /// How far they twisted counterclockwise on the screen, in radians
+    twist: f32,
+    /// Whether the world stays under the fingers
+    anchored: bool,
+    /// Whether a perspective camera orbits around its focus point instead of panning
+    orbit: bool,
+}
+
+impl Motion {
+    /// One finger that moved by `distance` to `position`
+    fn pan(position: Vec2, distance: Vec2, anchored: bool, orbit: bool) -> Self {
+        Self {
+            position,
+            distance,
+            zoom: 1.,
+            twist: 0.,
+            anchored,
+            orbit,
+        }
+    }
+}
+
+/// Moves the camera with the fingers. `dolly` is how far a perspective camera dollied towards its
+/// focus point
+fn move_camera(
+    config: &TouchCameraConfig,
+    camera: &Camera,
+    global: &GlobalTransform,
+    transform: &mut Transform,
+    projection: CameraProjection,
+    dolly: &mut f32,
+    motion: Motion,
+) {
+    match projection {
+        CameraProjection::Orthographic(projection) => {
+            move_orthographic(config, camera, global, transform, projection, motion)
+        }
+        CameraProjection::Perspective(projection) => {
+            move_perspective(config, camera, transform, projection, dolly, motion)
+        }
+    }
+}
+
+fn move_perspective(
+    config: &TouchCameraConfig,
+    camera: &Camera,
+    transform: &mut Transform,
+    projection: &mut PerspectiveProjection,
+    dolly: &mut f32,
+    Motion {
+        distance,
+        zoom,
+        twist,
+        anchored,
+        orbit,
+        ..
+    }: Motion,
+) {
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+    let focus = config.perspective.focus_distance * *dolly;
+    let sensitivity = if anchored {
+        1.
+    } else {
+        config.drag_sensitivity
+    };
+    if orbit {
+        let center = transform.translation + transform.forward() * focus;
+        let yaw = -TAU * distance.x / viewport.x * sensitivity;
+        let pitch = -PI * distance.y / viewport.y * sensitivity;
+        transform.rotate_around(center, Quat::from_rotation_y(yaw));
+        let mut pitched = *transform;
+        pitched.rotate_around(center, Quat::from_axis_angle(pitched.right(), pitch));
+        if pitched.forward().y.abs() < MAX_PITCH_SIN {
+            *transform = pitched;
+        }
+    } else {
+        // the plane through the focus point follows the fingers
+        let per_pixel = 2. * focus * (projection.fov / 2.).tan() / viewport.y;
+        transform.translation -=
+            transform.rotation * Vec3::new(distance.x, -distance.y, 0.) * per_pixel * sensitivity;
+    }
+    transform.rotate_local_z(-twist);
+    match config.perspective.zoom {
+        PerspectiveZoom::Dolly => {
+            let dollied = (*dolly * zoom).clamp(config.min_scale, config.max_scale);
+            transform.translation +=
+                transform.forward() * config.perspective.focus_distance * (*dolly - dollied);
+            *dolly = dollied;
+        }
+        PerspectiveZoom::Fov => {
+            projection.fov = (projection.fov * zoom).clamp(MIN_FOV, MAX_FOV);
+        }
+    }
+}
+
+fn move_orthographic(
+    config: &TouchCameraConfig,
+    camera: &Camera,
+    global: &GlobalTransform,
+    transform: &mut Transform,
+    projection: &mut OrthographicProjection,
+    Motion {
+        position,
+        distance,
+        zoom,
+        twist,
+        anchored,
+        ..
+    }: Motion,
+) {
+    let scale = projection.scale;
+    let zoom = (scale * zoom).clamp(config.min_scale, config.max_scale) / scale;
+    let start = *transform;
+    let (pivot, pivot_zoom) = match anchors(anchored, camera, global, position - distance, position)
+    {
+        Some((from, to)) => {
+            // move the point under the last position below the new one, then scale around it
+            transform.translation += (from - to).extend(0.);
+            (Some(from), zoom)
+        }
+        None => {
+            transform.translation -= transform.rotation
+                * (config.drag_sensitivity
+                    * Vec3::new(distance.x, -distance.y, 0.)
+                    * projection.scale);
+            (world_position(camera, global, position), 1.)
+        }
+    };
+    if let Some(pivot) = pivot.filter(|_| twist != 0. || pivot_zoom != 1.) {
+        let center = transform.translation.truncate();
+        let center = pivot + Vec2::from_angle(-twist).rotate(center - pivot) * pivot_zoom;
+        transform.translation = center.extend(transform.translation.z);
+    }
+    transform.rotate_z(-twist);
+    projection.scale = scale * zoom;
+
+    let Some(bounds) = &config.bounds else {
+        return;
+    };
+    let before = bounds.correction(view(&start, projection, 1.));
+    let after = bounds.correction(view(transform, projection, zoom));
+    let correction = match bounds.mode {
+        BoundsMode::Clamp => after,
+        // only the part of the movement that went further past the edge is damped
+        BoundsMode::RubberBand { stretch, .. } => {
+            let axis = |before: f32, after: f32| {
+                if after.abs() > before.abs() {
+                    (after - before) * (1. - stretch)
+                } else {
+                    0.
+                }
+            };
+            Vec2::new(axis(before.x, after.x), axis(before.y, after.y))
+        }
+    };
+    transform.translation += correction.extend(0.);
+}
+
+/// The rectangle around what a camera sees after the scale changed by `zoom`. The area of the
+/// projection is computed from the scale after each frame
+fn view(transform: &Transform, projection: &OrthographicProjection, zoom: f32) -> Rect {
+    let area = projection.area;
+    let corner = |x: f32, y: f32| {
+        transform
+            .transform_point(Vec3::new(x, y, 0.) * zoom)
+            .truncate()
+    };
+    let first = corner(area.min.x, area.min.y);
+    [
+        corner(area.max.x, area.max.y),
+        corner(area.min.x, area.max.y),
+        corner(area.max.x, area.min.y),
+    ]
+    .into_iter()
+    .fold(Rect::from_corners(first, first), |rect, corner| {
+        rect.union_point(corner)
+    })
+}
+
+/// Turns the orthographic cameras that followed a twist to the closest multiple of 90° if they
+/// snap
+fn snap(camera_q: &mut TouchCameras, tracker: &mut TouchTracker, config: &TouchCameraConfig) {
+    for (entity, _, _, mut transform, mut projections, camera_config) in camera_q.iter_mut() {
+        let config = camera_config.unwrap_or(config);
+        if !tracker.twisting.contains(&Some(entity))
+            || !config.twist.is_some_and(|twist| twist.snap)
+        {
+            continue;
+        }
+        let CameraProjection::Orthographic(_) = camera_projection(&mut projections) else {
+            continue;
+        };
+        let (_, _, angle) = transform.rotation.to_euler(EulerRot::XYZ);
+        let snapped = (angle / FRAC_PI_2).round() * FRAC_PI_2;
+        transform.rotate_z(snapped - angle);
+    }
+    tracker.twisting.clear();
+}
+
+/// Moves the cameras that were left past the edge of their rubber band back into the bounds
+fn spring_back(camera_q: &mut TouchCameras, config: &TouchCameraConfig, dt: f32) {
+    for (_, _, _, mut transform, mut projections, camera_config) in camera_q.iter_mut() {
+        let config = camera_config.unwrap_or(config);
+        let Some(
+            bounds @ Bounds {
+                mode: BoundsMode::RubberBand { stiffness, .. },
+                ..
+            },
+        ) = config.bounds.filter(|_| config.move_cameras)
+        else {
+            continue;
+        };
+        let CameraProjection::Orthographic(projection) = camera_projection(&mut projections) else {
+            continue;
+        };
+        let correction = bounds.correction(view(&transform, projection, 1.));
+        if correction == Vec2::ZERO {
+            continue;
+        }
+        let step = if correction.length() < SPRING_SNAP {
+            correction
+        } else {
+            correction * (1. - (-stiffness * dt).exp())
+        };
+        transform.translation += step.extend(0.);
+    }
+}
+
+// Distance in world units below which a spring lands right on the edge
+const SPRING_SNAP: f32 = 0.01;
+
+/// The world positions below `from` and `to` if the camera keeps them under the fingers
+fn anchors(
+    anchored: bool,
+    camera: &Camera,
+    transform: &GlobalTransform,
+    from: Vec2,
+    to: Vec2,
+) -> Option<(Vec2, Vec2)> {
+    if !anchored {
+        return None;
+    }
+    Some((
+        world_position(camera, transform, from)?,
+        world_position(camera, transform, to)?,
+    ))
+}
+
+type TouchCameras<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Camera,
+        &'static GlobalTransform,
+        &'static mut Transform,
+        AnyOf<(
+            &'static mut OrthographicProjection,
+            &'static mut PerspectiveProjection,
+            &'static mut Projection,
+        )>,
+        Option<&'static TouchCameraConfig>,
+    ),
+    With<TouchCameraTag>,
+>;
+
 fn setup(
     tag_query: Query<&TouchCameraTag, With<Camera>>,
     camera_query: Query<Entity, With<Camera>>,
+    config: Res<TouchCameraConfig>,
     mut commands: Commands,
 ) {
+    // the tag is attached even without `move_cameras`, the flag can be switched on later
+    if !config.move_cameras {
+        info!("TouchCameraPlugin initialized: only sending events");
+    }
     if !tag_query.is_empty() {
         info!("TouchCameraPlugin initialized: found a tag attached to a camera");
         return;
@@ -100,77 +819,346 @@ fn setup(
 }
 
 fn touch_pan_zoom(
-    touches_res: Res<Touches>,
-    mut camera_q: Query<(&mut Transform, &mut OrthographicProjection), With<TouchCameraTag>>,
+    mut gestures: EventReader<GestureEvent>,
+    mut camera_q: TouchCameras,
     mut tracker: ResMut<TouchTracker>,
+    mut events: EventWriter<TouchCameraEvent>,
     config: Res<TouchCameraConfig>,
     time: Res<Time>,
 ) {
-    let Ok((mut transform, mut projection)) = camera_q.get_single_mut() else {
-        return;
-    };
-
-    let touches: Vec<&touch::Touch> = touches_res.iter().collect();
+    let dt = time.delta_seconds();
+    // forget the cameras that were despawned or lost their tag
+    tracker.dolly.retain(|camera, _| camera_q.contains(*camera));
+    // a pinch right after a pan continues the gesture, anything later is a new one
+    let active = tracker.gesture_type != GestureType::None;
+    for gesture in gestures.read() {
+        match *gesture {
+            GestureEvent::Pan {
+                phase: GesturePhase::Started,
+                start,
+                position,
+                ..
+            } => {
+                if !active {
+                    interrupt(&mut tracker, &mut events);
+                }
+                tracker.gesture_type = GestureType::Pan;
+                tracker.time_start_touch = time.elapsed_seconds();
+                tracker.pan = Some((start, position));
+            }
+            GestureEvent::Pan {
+                phase: GesturePhase::Moved,
+                position,
+                ..
+            } => {
+                if let Some((_, last)) = &mut tracker.pan {
+                    *last = position;
+                }
+            }
+            GestureEvent::Pinch {
+                phase: GesturePhase::Started,
+                fingers,
+                last,
+            } => {
+                if !active {
+                    interrupt(&mut tracker, &mut events);
+                }
+                tracker.gesture_type = GestureType::Pinch;
+                tracker.twist_total = 0.;
+                tracker.twisting.clear();
+                pinch(
+                    &mut camera_q,
+                    &mut tracker,
+                    &mut events,
+                    &config,
+                    fingers,
+                    last,
+                    dt,
+                );
+            }
+            GestureEvent::Pinch {
+                phase: GesturePhase::Moved,
+                fingers,
+                last,
+            } if tracker.gesture_type == GestureType::Pinch => {
+                pinch(
+                    &mut camera_q,
+                    &mut tracker,
+                    &mut events,
+                    &config,
+                    fingers,
+                    last,
+                    dt,
+                );
+            }
+            GestureEvent::Pan {
+                phase: GesturePhase::Ended,
+                ..
+            }
+            | GestureEvent::Pinch {
+                phase: GesturePhase::Ended,
+                ..
+            } => {
+                tracker.gesture_type = GestureType::None;
+                tracker.pan = None;
+                tracker.pan_origins.clear();
+            }
+            GestureEvent::DoubleTap { position } => {
+                double_tap(&mut camera_q, &mut tracker, &mut events, &config, position)
+            }
+            _ => {}
+        }
+    }
 
-    if touches.is_empty() {
-        tracker.gesture_type = GestureType::None;
-        tracker.last_touch_a = None;
-        tracker.last_touch_b = None;
+    if tracker.gesture_type == GestureType::None {
+        snap(&mut camera_q, &mut tracker, &config);
+        glide(&mut camera_q, &mut tracker, &mut events, &config, dt);
+        spring_back(&mut camera_q, &config, dt);
         return;
     }
 
-    if touches_res.any_just_released() {
-        tracker.gesture_type = GestureType::PinchCancelled;
-        tracker.last_touch_a = None;
-        tracker.last_touch_b = None;
+    if let (GestureType::Pan, Some(pan)) = (&tracker.gesture_type, tracker.pan) {
+        let time_since_start = time.elapsed_seconds() - tracker.time_start_touch;
+        let position = pan.1;
+        // whoever starts following late catches up with the whole drag
+        if time_since_start >= config.touch_time_min {
+            let distance = tracker.follow(None, pan);
+            tracker.track(None, distance, 1., position, dt);
+            let event = tracker.event(config.event_pan(distance), 1., 0.);
+            events.send(event);
+        }
+        for (entity, camera, global, mut transform, mut projections, camera_config) in
+            camera_q.iter_mut()
+        {
+            let config = camera_config.unwrap_or(&config);
+            if !config.move_cameras || time_since_start < config.touch_time_min {
+                continue;
+            }
+            let distance = tracker.follow(Some(entity), pan);
+            move_camera(
+                config,
+                camera,
+                global,
+                &mut transform,
+                camera_projection(&mut projections),
+                tracker.dolly(entity),
+                Motion::pan(
+                    position,
+                    distance,
+                    config.world_anchored,
+                    config.perspective.orbit,
+                ),
+            );
+            tracker.track(Some(entity), distance, 1., position, dt);
+            tracker.orbit(entity, config.perspective.orbit);
+        }
+    }
+
+    // whatever didn't move this frame slows down, so resting fingers are released without a glide
+    for velocity in tracker.velocities.values_mut() {
+        if !velocity.tracked {
+            velocity.track(Vec2::ZERO, 1., velocity.position, dt);
+        }
+        velocity.tracked = false;
     }
+}
 
-    if touches.len() == 2 {
-        tracker.gesture_type = GestureType::Pinch;
-        // complicated way to reset previous position to prevent some bugs. Should simplify
-        let last_a = if tracker.last_touch_b.is_none() {
-            touches[0].position()
+/// Pans, zooms and twists the cameras with two fingers that moved from `last` to `fingers`
+fn pinch(
+    camera_q: &mut TouchCameras,
+    tracker: &mut TouchTracker,
+    events: &mut EventWriter<TouchCameraEvent>,
+    config: &TouchCameraConfig,
+    [a, b]: [Vec2; 2],
+    [last_a, last_b]: [Vec2; 2],
+    dt: f32,
+) {
+    let delta_a = a - last_a;
+    let delta_b = b - last_b;
+    let distance_current = a.distance(b);
+    let distance_last = last_a.distance(last_b);
+    // the window's y points down
+    let angle = -(last_b - last_a).angle_between(b - a);
+    let pinch = Pinch {
+        delta_total: (delta_a + delta_b).length(),
+        dot_delta: delta_a.dot(delta_b),
+        direction: distance_last - distance_current,
+        ratio: if distance_last > 0. && distance_current > 0. {
+            distance_last / distance_current
         } else {
-            tracker.last_touch_a.unwrap_or(touches[0].position())
+            1.
+        },
+        last_middle: (last_a + last_b) / 2.,
+        middle: (a + b) / 2.,
+        angle: if angle.is_finite() { angle } else { 0. },
+    };
+    tracker.twist_total += pinch.angle;
+
+    let (distance, zoom) = (config.pinch_pan(&pinch), config.zoom_factor(&pinch));
+    tracker.track(None, distance, zoom, pinch.middle, dt);
+    let rotation = tracker.twist(None, config, pinch.angle);
+    let event = tracker.event(config.event_pan(distance), zoom, rotation);
+    events.send(event);
+    for (entity, camera, global, mut transform, mut projections, camera_config) in
+        camera_q.iter_mut()
+    {
+        let config = camera_config.unwrap_or(config);
+        if !config.move_cameras {
+            continue;
+        }
+        let projection = camera_projection(&mut projections);
+        // with one finger orbiting, two of them pan
+        let distance = match projection {
+            CameraProjection::Perspective(_) if config.perspective.orbit => {
+                pinch.middle - pinch.last_middle
+            }
+            _ => config.pinch_pan(&pinch),
         };
-        let last_b = if tracker.last_touch_b.is_none() {
-            touches[1].position()
-        } else {
-            tracker.last_touch_b.unwrap_or(touches[1].position())
+        let zoom = config.zoom_factor(&pinch);
+        let twist = tracker.twist(Some(entity), config, pinch.angle);
+        move_camera(
+            config,
+            camera,
+            global,
+            &mut transform,
+            projection,
+            tracker.dolly(entity),
+            Motion {
+                position: pinch.middle,
+                distance,
+                zoom,
+                twist,
+                anchored: config.world_anchored,
+                orbit: false,
+            },
+        );
+        tracker.track(Some(entity), distance, zoom, pinch.middle, dt);
+        tracker.orbit(entity, false);
+    }
+}
+
+/// Zooms the cameras in on the point of a double tap
+fn double_tap(
+    camera_q: &mut TouchCameras,
+    tracker: &mut TouchTracker,
+    events: &mut EventWriter<TouchCameraEvent>,
+    config: &TouchCameraConfig,
+    position: Vec2,
+) {
+    if let Some(zoom) = config.double_tap_zoom.filter(|zoom| *zoom > 0.) {
+        events.send_batch([
+            TouchCameraEvent {
+                phase: GesturePhase::Started,
+                pan: Vec2::ZERO,
+                zoom,
+                rotation: 0.,
+            },
+            TouchCameraEvent {
+                phase: GesturePhase::Ended,
+                pan: Vec2::ZERO,
+                zoom: 1.,
+                rotation: 0.,
+            },
+        ]);
+    }
+    for (entity, camera, global, mut transform, mut projections, camera_config) in
+        camera_q.iter_mut()
+    {
+        let config = camera_config.unwrap_or(config);
+        let Some(zoom) = config
+            .double_tap_zoom
+            .filter(|zoom| config.move_cameras && *zoom > 0.)
+        else {
+            continue;
         };
+        move_camera(
+            config,
+            camera,
+            global,
+            &mut transform,
+            camera_projection(&mut projections),
+            tracker.dolly(entity),
+            Motion {
+                zoom,
+                ..Motion::pan(position, Vec2::ZERO, true, false)
+            },
+        );
+    }
+}
 
-        let delta_a = touches[0].position() - last_a;
-        let delta_b = touches[1].position() - last_b;
-        let delta_total = (delta_a + delta_b).length();
-        let dot_delta = delta_a.dot(delta_b);
-        if dot_delta > config.opposites_tolerance {
-            return;
+/// Keeps the cameras and the events moving after the fingers are lifted
+fn glide(
+    camera_q: &mut TouchCameras,
+    tracker: &mut TouchTracker,
+    events: &mut EventWriter<TouchCameraEvent>,
+    config: &TouchCameraConfig,
+    dt: f32,
+) {
+    for (entity, camera, global, mut transform, mut projections, camera_config) in
+        camera_q.iter_mut()
+    {
+        let config = camera_config.unwrap_or(config);
+        let Some(mut velocity) = tracker.velocities.remove(&Some(entity)) else {
+            continue;
+        };
+        let Some(momentum) = config.momentum.filter(|_| config.move_cameras) else {
+            continue;
+        };
+        move_camera(
+            config,
+            camera,
+            global,
+            &mut transform,
+            camera_projection(&mut projections),
+            tracker.dolly(entity),
+            Motion {
+                zoom: (velocity.zoom * dt).exp(),
+                ..Motion::pan(
+                    velocity.position,
+                    velocity.pan * dt,
+                    config.world_anchored,
+                    velocity.orbit,
+                )
+            },
+        );
+        if velocity.slow_down(&momentum, dt) {
+            tracker.velocities.insert(Some(entity), velocity);
         }
+    }
 
-        let distance_current = touches[0].position() - touches[1].position();
-        let distance_prev = touches[0].previous_position() - touches[1].previous_position();
-        let pinch_direction = distance_prev.length() - distance_current.length();
-        projection.scale +=
-            pinch_direction.signum() * delta_total * config.zoom_sensitivity * projection.scale;
-
-        tracker.last_touch_a = Some(touches[0].position());
-        tracker.last_touch_b = Some(touches[1].position());
-    } else if touches.len() == 1
-        && matches!(tracker.gesture_type, GestureType::None | GestureType::Pan)
+    if let (Some(mut velocity), Some(momentum)) =
+        (tracker.velocities.remove(&None), config.momentum)
     {
-        if tracker.gesture_type == GestureType::None {
-            tracker.camera_start_pos = transform.translation;
-            tracker.time_start_touch = time.elapsed_seconds();
+        if tracker.gesture_started {
+            events.send(TouchCameraEvent {
+                phase: GesturePhase::Gliding,
+                pan: config.event_pan(velocity.pan * dt),
+                zoom: (velocity.zoom * dt).exp(),
+                rotation: 0.,
+            });
         }
-        tracker.gesture_type = GestureType::Pan;
-        let time_since_start = time.elapsed_seconds() - tracker.time_start_touch;
-        if time_since_start < config.touch_time_min {
+        if velocity.slow_down(&momentum, dt) {
+            tracker.velocities.insert(None, velocity);
             return;
         }
-        let distance = Vec3::new(touches[0].distance().x, -touches[0].distance().y, 0.);
-        transform.translation =
-            tracker.camera_start_pos - config.drag_sensitivity * distance * projection.scale;
-        tracker.last_touch_a = Some(touches[0].position());
-        tracker.last_touch_b = None;
+    }
+    end_gesture(tracker, events);
+}
+
+/// Stops the glide of the last gesture for a new one
+fn interrupt(tracker: &mut TouchTracker, events: &mut EventWriter<TouchCameraEvent>) {
+    tracker.velocities.clear();
+    end_gesture(tracker, events);
+}
+
+fn end_gesture(tracker: &mut TouchTracker, events: &mut EventWriter<TouchCameraEvent>) {
+    if tracker.gesture_started {
+        tracker.gesture_started = false;
+        events.send(TouchCameraEvent {
+            phase: GesturePhase::Ended,
+            pan: Vec2::ZERO,
+            zoom: 1.,
+            rotation: 0.,
+        });
     }
 }
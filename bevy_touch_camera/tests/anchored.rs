@@ -0,0 +1,93 @@
+mod common;
+
+use bevy::{
+    prelude::*,
+    render::camera::{ScalingMode, Viewport},
+};
+use bevy_touch_camera::{TouchCameraConfig, TouchCameraPlugin};
+use common::{down, send, to, up, windowed};
+
+fn app() -> App {
+    windowed(TouchCameraPlugin {
+        config: TouchCameraConfig {
+            world_anchored: true,
+            ..default()
+        },
+    })
+}
+
+fn camera(app: &mut App, viewport: Option<Viewport>, scaling_mode: ScalingMode) -> Entity {
+    common::camera(
+        app,
+        (
+            Camera {
+                viewport,
+                ..default()
+            },
+            OrthographicProjection {
+                scaling_mode,
+                ..default()
+            },
+            TransformBundle::from_transform(Transform::from_xyz(30., -20., 0.)),
+        ),
+    )
+}
+
+/// The world position below `position` in window coordinates
+fn world(app: &mut App, camera: Entity, position: Vec2) -> Vec2 {
+    let mut query = app.world.query::<(&Camera, &GlobalTransform)>();
+    let (camera, transform) = query.get(&app.world, camera).unwrap();
+    let viewport = camera.logical_viewport_rect().unwrap();
+    camera
+        .viewport_to_world_2d(transform, position - viewport.min)
+        .unwrap()
+}
+
+fn assert_near(a: Vec2, b: Vec2) {
+    assert!(a.distance(b) < 1e-3, "{a} != {b}");
+}
+
+fn fingers_stay_on_the_world(viewport: Option<Viewport>, scaling_mode: ScalingMode) {
+    let mut app = app();
+    let camera = camera(&mut app, viewport, scaling_mode);
+
+    // dragging
+    let start = Vec2::new(500., 300.);
+    let below = world(&mut app, camera, start);
+    send(&mut app, &[down(0, start)]);
+    for end in [Vec2::new(520., 340.), Vec2::new(700., 100.)] {
+        send(&mut app, &[to(0, end)]);
+        assert_near(world(&mut app, camera, end), below);
+    }
+    send(&mut app, &[up(0, Vec2::new(700., 100.))]);
+
+    // pinching, with the fingers moving apart and together unevenly along the line between them
+    let (a, b) = (Vec2::new(450., 300.), Vec2::new(650., 250.));
+    let (below_a, below_b) = (world(&mut app, camera, a), world(&mut app, camera, b));
+    send(&mut app, &[down(1, a)]);
+    send(&mut app, &[down(2, b)]);
+    for (a, b) in [
+        (Vec2::new(410., 310.), Vec2::new(730., 230.)),
+        (Vec2::new(490., 290.), Vec2::new(690., 240.)),
+    ] {
+        send(&mut app, &[to(1, a)]);
+        send(&mut app, &[to(2, b)]);
+        assert_near(world(&mut app, camera, a), below_a);
+        assert_near(world(&mut app, camera, b), below_b);
+    }
+}
+
+#[test]
+fn anchored_in_the_window() {
+    fingers_stay_on_the_world(None, ScalingMode::WindowSize(1.));
+}
+
+#[test]
+fn anchored_in_a_viewport_with_a_fixed_height() {
+    let viewport = Viewport {
+        physical_position: UVec2::new(400, 0),
+        physical_size: UVec2::new(400, 600),
+        ..default()
+    };
+    fingers_stay_on_the_world(Some(viewport), ScalingMode::FixedVertical(10.));
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_touch_camera::{content_bounds, Bounds, BoundsMode, TouchCameraConfig, TouchCameraPlugin};
+use common::{camera, down, orthographic, send, to, up, view, windowed};
+
+/// A camera that sees `-400..400 × -300..300` of the window and can't leave
+/// `-500..500 × -400..400`
+fn app(mode: BoundsMode) -> (App, Entity) {
+    let mut app = windowed(TouchCameraPlugin {
+        config: TouchCameraConfig {
+            min_scale: 0.5,
+            max_scale: 2.,
+            bounds: Some(Bounds {
+                rect: Rect::new(-500., -400., 500., 400.),
+                mode,
+            }),
+            ..default()
+        },
+    });
+    let camera = camera(&mut app, orthographic());
+    (app, camera)
+}
+
+#[test]
+fn zoom_stops_at_the_limits() {
+    let (mut app, camera) = app(BoundsMode::Clamp);
+    send(&mut app, &[down(0, (390., 300.))]);
+    send(&mut app, &[down(1, (410., 300.))]);
+    send(&mut app, &[to(0, (0., 300.))]);
+    send(&mut app, &[to(1, (800., 300.))]);
+    assert_eq!(view(&app, camera).1, 0.5);
+    send(&mut app, &[to(0, (399., 300.))]);
+    send(&mut app, &[to(1, (401., 300.))]);
+    assert_eq!(view(&app, camera).1, 2.);
+
+    // a finger moving further than the sensitivity is made for zooms in by half at most
+    app.world.resource_mut::<TouchCameraConfig>().min_scale =
+        TouchCameraConfig::default().min_scale;
+    send(&mut app, &[to(0, (-5000., 300.))]);
+    assert_eq!(view(&app, camera).1, 1.);
+}
+
+#[test]
+fn clamped_at_the_edge() {
+    let (mut app, camera) = app(BoundsMode::Clamp);
+    send(&mut app, &[down(0, (400., 300.))]);
+    send(&mut app, &[to(0, (700., 400.))]);
+    assert_eq!(view(&app, camera).0, Vec2::new(-100., 100.));
+    send(&mut app, &[to(0, (650., 300.))]);
+    assert_eq!(view(&app, camera).0, Vec2::new(-50., 0.));
+}
+
+#[test]
+fn rubber_band_springs_back() {
+    let (mut app, camera) = app(BoundsMode::RubberBand {
+        stretch: 0.5,
+        stiffness: 10.,
+    });
+    send(&mut app, &[down(0, (400., 300.))]);
+    send(&mut app, &[to(0, (700., 300.))]);
+    // 100 up to the edge, then half of the other 200
+    assert_eq!(view(&app, camera).0.x, -200.);
+
+    send(&mut app, &[up(0, (700., 300.))]);
+    let mut last = view(&app, camera).0.x;
+    assert!(last > -200.);
+    for _ in 0..20 {
+        app.update();
+        let x = view(&app, camera).0.x;
+        assert!(x >= last && x <= -100.);
+        last = x;
+    }
+    assert_eq!(last, -100.);
+}
+
+#[test]
+fn bounds_around_the_content() {
+    let mut world = World::new();
+    world.spawn(GlobalTransform::from_xyz(-30., 10., 0.));
+    world.spawn((
+        GlobalTransform::from(Transform::from_xyz(50., 0., 0.).with_scale(Vec3::splat(2.))),
+        Aabb::from_min_max(Vec3::new(-5., -10., 0.), Vec3::new(5., 10., 0.)),
+    ));
+    let content = world
+        .query::<(&GlobalTransform, Option<&Aabb>)>()
+        .iter(&world)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        content_bounds(content),
+        Some(Rect::new(-30., -20., 60., 20.))
+    );
+    assert_eq!(content_bounds([]), None);
+}
@@ -0,0 +1,131 @@
+//! Headless apps fed with scripted touches
+// every test file uses only some of the fixtures
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::{
+    app::Plugins,
+    input::{
+        touch::{TouchInput, TouchPhase},
+        InputPlugin,
+    },
+    prelude::*,
+    render::camera::{camera_system, ManualTextureViews},
+    time::TimeUpdateStrategy,
+    transform::TransformPlugin,
+};
+use bevy_touch_camera::{TouchCameraConfig, TouchCameraPlugin, TouchCameraTag};
+
+/// An app with `MinimalPlugins` in which every frame takes 100 ms
+fn base() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, InputPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+    app
+}
+
+/// An app without a window, cameras have no viewport there and move like with
+/// `world_anchored: false`
+pub fn headless<M>(plugins: impl Plugins<M>) -> App {
+    let mut app = base();
+    app.add_plugins(plugins);
+    app
+}
+
+/// An app with a `800 × 600` window. Bevy's `camera_system` computes the viewport and the
+/// projection of the cameras like it does when rendering
+pub fn windowed<M>(plugins: impl Plugins<M>) -> App {
+    let mut app = base();
+    app.add_plugins((
+        AssetPlugin::default(),
+        TransformPlugin,
+        WindowPlugin {
+            primary_window: Some(Window {
+                resolution: (800., 600.).into(),
+                ..default()
+            }),
+            ..default()
+        },
+    ))
+    .add_plugins(plugins)
+    .init_asset::<Image>()
+    .init_resource::<ManualTextureViews>()
+    .add_systems(
+        PostUpdate,
+        (
+            camera_system::<OrthographicProjection>,
+            camera_system::<Projection>,
+        ),
+    );
+    app
+}
+
+/// Spawns a camera with a `TouchCameraTag` and runs a frame
+pub fn camera(app: &mut App, camera: impl Bundle) -> Entity {
+    let camera = app.world.spawn((camera, TouchCameraTag)).id();
+    app.update();
+    camera
+}
+
+/// An orthographic camera at the origin
+pub fn orthographic() -> impl Bundle {
+    (
+        Camera::default(),
+        OrthographicProjection::default(),
+        TransformBundle::default(),
+    )
+}
+
+/// A headless app with the `TouchCameraPlugin` and one orthographic camera
+pub fn app(config: TouchCameraConfig) -> (App, Entity) {
+    let mut app = headless(TouchCameraPlugin { config });
+    let camera = camera(&mut app, orthographic());
+    (app, camera)
+}
+
+pub fn down(id: u64, position: impl Into<Vec2>) -> TouchInput {
+    touch(id, TouchPhase::Started, position)
+}
+
+pub fn to(id: u64, position: impl Into<Vec2>) -> TouchInput {
+    touch(id, TouchPhase::Moved, position)
+}
+
+/// Lifts a finger. It has to be moved to `position` before, `Touches` keep the last position
+pub fn up(id: u64, position: impl Into<Vec2>) -> TouchInput {
+    touch(id, TouchPhase::Ended, position)
+}
+
+pub fn touch(id: u64, phase: TouchPhase, position: impl Into<Vec2>) -> TouchInput {
+    TouchInput {
+        phase,
+        position: position.into(),
+        force: None,
+        id,
+    }
+}
+
+/// Runs one frame with `touches`
+pub fn send(app: &mut App, touches: &[TouchInput]) {
+    for touch in touches {
+        app.world.send_event(*touch);
+    }
+    app.update();
+}
+
+/// Runs one frame for each entry of `frames`, with its touches
+pub fn play(app: &mut App, frames: &[&[TouchInput]]) {
+    for touches in frames {
+        send(app, touches);
+    }
+}
+
+/// The translation and scale of an orthographic `camera`
+pub fn view(app: &App, camera: Entity) -> (Vec2, f32) {
+    let transform = app.world.get::<Transform>(camera).unwrap();
+    let projection = app.world.get::<OrthographicProjection>(camera).unwrap();
+    (transform.translation.truncate(), projection.scale)
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_touch_camera::{
+    GesturePhase, TouchCameraConfig, TouchCameraEvent, TouchCameraPlugin, TouchCameraTag,
+};
+use common::{app, camera, down, headless, orthographic, send, to, up, view};
+
+fn x(app: &App, camera: Entity) -> f32 {
+    view(app, camera).0.x
+}
+
+fn scale(app: &App, camera: Entity) -> f32 {
+    view(app, camera).1
+}
+
+#[test]
+fn changing_the_resource_mid_drag() {
+    let (mut app, camera) = app(TouchCameraConfig::default());
+    send(&mut app, &[down(0, (0., 0.))]);
+    send(&mut app, &[to(0, (10., 0.))]);
+    assert_eq!(x(&app, camera), -10.);
+
+    // the camera doesn't jump, only the following movement is faster
+    app.world
+        .resource_mut::<TouchCameraConfig>()
+        .drag_sensitivity = 2.;
+    app.update();
+    assert_eq!(x(&app, camera), -10.);
+    send(&mut app, &[to(0, (15., 0.))]);
+    assert_eq!(x(&app, camera), -20.);
+
+    // waiting longer before panning stops the camera until the finger was down long enough
+    app.world.resource_mut::<TouchCameraConfig>().touch_time_min = 10.;
+    send(&mut app, &[to(0, (20., 0.))]);
+    assert_eq!(x(&app, camera), -20.);
+    send(&mut app, &[up(0, (20., 0.))]);
+
+    // a new drag waits, then catches up with the whole drag
+    app.world.resource_mut::<TouchCameraConfig>().touch_time_min = 0.25;
+    send(&mut app, &[down(1, (0., 0.))]);
+    send(&mut app, &[to(1, (5., 0.))]);
+    assert_eq!(x(&app, camera), -20.);
+    app.update();
+    app.update();
+    assert_eq!(x(&app, camera), -30.);
+}
+
+#[test]
+fn component_overrides_the_resource() {
+    let (mut app, plain) = app(TouchCameraConfig::default());
+    let fast = camera(&mut app, orthographic());
+    app.world.entity_mut(fast).insert(TouchCameraConfig {
+        drag_sensitivity: 3.,
+        zoom_sensitivity: 0.,
+        ..default()
+    });
+
+    send(&mut app, &[down(0, (0., 0.))]);
+    send(&mut app, &[to(0, (10., 0.))]);
+    assert_eq!((x(&app, plain), x(&app, fast)), (-10., -30.));
+
+    // the component can change mid drag too
+    app.world
+        .get_mut::<TouchCameraConfig>(fast)
+        .unwrap()
+        .drag_sensitivity = 1.;
+    send(&mut app, &[to(0, (20., 0.))]);
+    assert_eq!((x(&app, plain), x(&app, fast)), (-20., -40.));
+    send(&mut app, &[up(0, (20., 0.))]);
+
+    // spreading the fingers zooms in, except for the camera without zoom
+    send(&mut app, &[down(1, (-10., 0.))]);
+    send(&mut app, &[down(2, (10., 0.))]);
+    send(&mut app, &[to(1, (-20., 0.))]);
+    assert!(scale(&app, plain) < 1.);
+    assert_eq!(scale(&app, fast), 1.);
+}
+
+fn events(app: &mut App) -> Vec<TouchCameraEvent> {
+    app.world
+        .resource_mut::<Events<TouchCameraEvent>>()
+        .drain()
+        .collect()
+}
+
+#[test]
+fn events_without_moving_the_cameras() {
+    let (mut app, camera) = app(TouchCameraConfig::default());
+    app.world.resource_mut::<TouchCameraConfig>().move_cameras = false;
+
+    send(&mut app, &[down(0, (0., 0.))]);
+    send(&mut app, &[to(0, (10., 5.))]);
+    send(&mut app, &[to(0, (12., 5.))]);
+    assert_eq!(
+        events(&mut app),
+        [
+            TouchCameraEvent {
+                phase: GesturePhase::Started,
+                pan: Vec2::new(10., 5.),
+                zoom: 1.,
+                rotation: 0.,
+            },
+            TouchCameraEvent {
+                phase: GesturePhase::Moved,
+                pan: Vec2::new(2., 0.),
+                zoom: 1.,
+                rotation: 0.,
+            },
+        ]
+    );
+    assert_eq!(x(&app, camera), 0.);
+
+    send(&mut app, &[down(1, (20., 5.))]);
+    send(&mut app, &[to(1, (30., 5.))]);
+    let pinch = events(&mut app);
+    assert!(pinch.iter().all(|event| event.phase == GesturePhase::Moved));
+    assert!(pinch.last().unwrap().zoom < 1.);
+    assert_eq!(scale(&app, camera), 1.);
+
+    send(&mut app, &[up(0, (12., 5.))]);
+    send(&mut app, &[up(1, (30., 5.))]);
+    assert_eq!(
+        events(&mut app).last(),
+        Some(&TouchCameraEvent {
+            phase: GesturePhase::Ended,
+            pan: Vec2::ZERO,
+            zoom: 1.,
+            rotation: 0.,
+        })
+    );
+
+    // a camera can still opt in with its own config
+    app.world
+        .entity_mut(camera)
+        .insert(TouchCameraConfig::default());
+    send(&mut app, &[down(2, (0., 0.))]);
+    send(&mut app, &[to(2, (10., 0.))]);
+    assert_eq!(x(&app, camera), -10.);
+    assert_eq!(events(&mut app)[0].phase, GesturePhase::Started);
+}
+
+#[test]
+fn switching_move_cameras_on_at_runtime() {
+    let mut app = headless(TouchCameraPlugin {
+        config: TouchCameraConfig {
+            move_cameras: false,
+            ..default()
+        },
+    });
+    // no tag, the plugin picks the only camera at startup
+    let camera = app.world.spawn(orthographic()).id();
+    app.update();
+    assert!(app.world.get::<TouchCameraTag>(camera).is_some());
+
+    send(&mut app, &[down(0, (0., 0.))]);
+    send(&mut app, &[to(0, (10., 0.))]);
+    send(&mut app, &[up(0, (10., 0.))]);
+    assert_eq!(x(&app, camera), 0.);
+
+    app.world.resource_mut::<TouchCameraConfig>().move_cameras = true;
+    send(&mut app, &[down(1, (0., 0.))]);
+    send(&mut app, &[to(1, (10., 0.))]);
+    assert_eq!(x(&app, camera), -10.);
+}
+
+#[test]
+fn event_zoom_stays_above_zero() {
+    let (mut app, _) = app(TouchCameraConfig::default());
+    app.world.resource_mut::<TouchCameraConfig>().move_cameras = false;
+
+    // a finger moving 300 pixels in a frame, far past what the sensitivity is made for
+    send(&mut app, &[down(0, (0., 0.))]);
+    send(&mut app, &[down(1, (100., 0.))]);
+    events(&mut app);
+    send(&mut app, &[to(1, (400., 0.))]);
+    send(&mut app, &[to(1, (800., 0.))]);
+    let mut zoom = 1.;
+    for event in events(&mut app) {
+        assert!(event.zoom > 0., "{event:?}");
+        zoom *= event.zoom;
+    }
+    assert!(zoom > 0. && zoom < 1.);
+}
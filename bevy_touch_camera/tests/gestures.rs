@@ -0,0 +1,227 @@
+mod common;
+
+use bevy::{input::touch::TouchInput, prelude::*};
+use bevy_touch_camera::{
+    GestureConfig, GestureEvent, GesturePhase, GesturePlugin, SwipeDirection, TouchCameraConfig,
+    TouchCameraEvent,
+};
+use common::{down, headless, send, to, up, view};
+
+fn app() -> App {
+    let mut app = headless(GesturePlugin::default());
+    app.update();
+    app
+}
+
+/// Sends the touches of one frame and returns the gestures recognized in it
+fn frame(app: &mut App, touches: &[TouchInput]) -> Vec<GestureEvent> {
+    send(app, touches);
+    app.world
+        .resource_mut::<Events<GestureEvent>>()
+        .drain()
+        .collect()
+}
+
+fn tap(app: &mut App, position: Vec2) -> Vec<GestureEvent> {
+    let mut gestures = frame(app, &[down(0, position)]);
+    gestures.extend(frame(app, &[up(0, position)]));
+    gestures
+}
+
+fn pan(phase: GesturePhase, position: Vec2, delta: Vec2) -> GestureEvent {
+    GestureEvent::Pan {
+        phase,
+        start: Vec2::ZERO,
+        position,
+        delta,
+    }
+}
+
+#[test]
+fn taps_and_double_taps() {
+    let mut app = app();
+    let position = Vec2::new(100., 100.);
+    assert!(tap(&mut app, position).contains(&GestureEvent::Tap { position }));
+
+    // the second tap close by is a double tap
+    let second = Vec2::new(110., 95.);
+    let gestures = tap(&mut app, second);
+    assert!(gestures.ends_with(&[
+        GestureEvent::Tap { position: second },
+        GestureEvent::DoubleTap { position: second },
+    ]));
+
+    // a third one starts over
+    assert!(!tap(&mut app, second).contains(&GestureEvent::DoubleTap { position: second }));
+
+    // too late for a double tap
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(!tap(&mut app, second).contains(&GestureEvent::DoubleTap { position: second }));
+}
+
+#[test]
+fn long_press() {
+    let mut app = app();
+    let position = Vec2::new(100., 100.);
+    frame(&mut app, &[down(0, position)]);
+    let mut gestures = vec![];
+    for _ in 0..5 {
+        gestures.extend(frame(&mut app, &[]));
+    }
+    assert_eq!(
+        gestures,
+        [GestureEvent::LongPress {
+            phase: GesturePhase::Started,
+            position
+        }]
+    );
+    let gestures = frame(&mut app, &[up(0, position)]);
+    assert!(gestures.contains(&GestureEvent::LongPress {
+        phase: GesturePhase::Ended,
+        position
+    }));
+    assert!(!gestures.contains(&GestureEvent::Tap { position }));
+}
+
+#[test]
+fn pan_and_swipe() {
+    let mut app = app();
+    assert_eq!(
+        frame(&mut app, &[down(0, Vec2::ZERO)]),
+        [pan(GesturePhase::Started, Vec2::ZERO, Vec2::ZERO)]
+    );
+    let position = Vec2::new(-40., 10.);
+    assert_eq!(
+        frame(&mut app, &[to(0, position)]),
+        [pan(GesturePhase::Moved, position, position)]
+    );
+    // resting fingers don't send anything
+    assert_eq!(frame(&mut app, &[]), []);
+    let end = Vec2::new(-300., 20.);
+    frame(&mut app, &[to(0, end)]);
+    let gestures = frame(&mut app, &[up(0, end)]);
+    assert_eq!(gestures[0], pan(GesturePhase::Ended, end, Vec2::ZERO));
+    let GestureEvent::Swipe {
+        direction,
+        start,
+        end: swiped,
+        velocity,
+    } = gestures[1]
+    else {
+        panic!("no swipe in {gestures:?}");
+    };
+    assert_eq!(
+        (direction, start, swiped),
+        (SwipeDirection::Left, Vec2::ZERO, end)
+    );
+    assert!(velocity.distance(end / 0.4) < 0.1);
+
+    // slow movements are no swipes
+    app.world.resource_mut::<GestureConfig>().swipe_min_speed = 1000.;
+    frame(&mut app, &[down(0, Vec2::ZERO)]);
+    frame(&mut app, &[to(0, Vec2::new(0., 60.))]);
+    let gestures = frame(&mut app, &[up(0, Vec2::new(0., 60.))]);
+    assert!(!gestures
+        .iter()
+        .any(|gesture| matches!(gesture, GestureEvent::Swipe { .. })));
+}
+
+#[test]
+fn pinch_until_a_finger_is_lifted() {
+    let mut app = app();
+    let (a, b) = (Vec2::new(0., 0.), Vec2::new(100., 0.));
+    frame(&mut app, &[down(0, a)]);
+    // the second finger ends the pan and starts a pinch
+    assert_eq!(
+        frame(&mut app, &[down(1, b)]),
+        [
+            pan(GesturePhase::Ended, a, Vec2::ZERO),
+            GestureEvent::Pinch {
+                phase: GesturePhase::Started,
+                fingers: [a, b],
+                last: [a, b],
+            }
+        ]
+    );
+    let moved = Vec2::new(150., 0.);
+    assert_eq!(
+        frame(&mut app, &[to(1, moved)]),
+        [GestureEvent::Pinch {
+            phase: GesturePhase::Moved,
+            fingers: [a, moved],
+            last: [a, b],
+        }]
+    );
+    assert_eq!(
+        frame(&mut app, &[up(1, moved)]),
+        [GestureEvent::Pinch {
+            phase: GesturePhase::Ended,
+            fingers: [a, moved],
+            last: [a, moved],
+        }]
+    );
+
+    // the finger left over doesn't pan until all are lifted
+    assert_eq!(frame(&mut app, &[to(0, Vec2::new(50., 50.))]), []);
+    assert_eq!(frame(&mut app, &[up(0, a)]), []);
+    assert_eq!(frame(&mut app, &[down(2, Vec2::ZERO)]).len(), 1);
+}
+
+#[test]
+fn three_fingers_cancel() {
+    let mut app = app();
+    frame(&mut app, &[down(0, Vec2::ZERO), down(1, Vec2::X)]);
+    let gestures = frame(&mut app, &[down(2, Vec2::Y)]);
+    assert!(matches!(
+        gestures[..],
+        [GestureEvent::Pinch {
+            phase: GesturePhase::Ended,
+            ..
+        }]
+    ));
+    assert_eq!(frame(&mut app, &[up(2, Vec2::Y)]), []);
+}
+
+#[test]
+fn double_tap_zooms_the_camera() {
+    let (mut app, camera) = common::app(TouchCameraConfig {
+        double_tap_zoom: Some(0.5),
+        ..default()
+    });
+    let scale = |app: &App| view(app, camera).1;
+    tap(&mut app, Vec2::ZERO);
+    assert_eq!(scale(&app), 1.);
+    tap(&mut app, Vec2::ZERO);
+    assert_eq!(scale(&app), 0.5);
+
+    // without moving the cameras the zoom is sent as an event
+    app.world.resource_mut::<TouchCameraConfig>().move_cameras = false;
+    tap(&mut app, Vec2::ZERO);
+    app.world.resource_mut::<Events<TouchCameraEvent>>().clear();
+    tap(&mut app, Vec2::ZERO);
+    let events: Vec<TouchCameraEvent> = app
+        .world
+        .resource_mut::<Events<TouchCameraEvent>>()
+        .drain()
+        .collect();
+    assert_eq!(
+        events,
+        [
+            TouchCameraEvent {
+                phase: GesturePhase::Started,
+                pan: Vec2::ZERO,
+                zoom: 0.5,
+                rotation: 0.,
+            },
+            TouchCameraEvent {
+                phase: GesturePhase::Ended,
+                pan: Vec2::ZERO,
+                zoom: 1.,
+                rotation: 0.,
+            },
+        ]
+    );
+    assert_eq!(scale(&app), 0.5);
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_touch_camera::{GesturePhase, Momentum, TouchCameraConfig, TouchCameraEvent};
+use common::{down, send, to, up, view};
+
+fn app(momentum: Option<Momentum>) -> (App, Entity) {
+    common::app(TouchCameraConfig {
+        momentum,
+        ..default()
+    })
+}
+
+/// Drags the camera to -30 by 10 pixels every 100 ms and lifts the finger
+fn drag(app: &mut App) {
+    send(app, &[down(0, (0., 0.))]);
+    for x in [10., 20., 30.] {
+        send(app, &[to(0, (x, 0.))]);
+    }
+    send(app, &[up(0, (30., 0.))]);
+}
+
+#[test]
+fn without_momentum_the_camera_stops() {
+    let (mut app, camera) = app(None);
+    drag(&mut app);
+    app.update();
+    assert_eq!(view(&app, camera).0.x, -30.);
+}
+
+#[test]
+fn gliding_slows_down_and_stops() {
+    let (mut app, camera) = app(Some(Momentum::default()));
+    drag(&mut app);
+    // the release frame already glides with the speed of the drag
+    assert_eq!(view(&app, camera).0.x, -40.);
+
+    let mut steps = vec![];
+    for _ in 0..20 {
+        let before = view(&app, camera).0.x;
+        app.update();
+        steps.push(before - view(&app, camera).0.x);
+    }
+    assert!(steps[0] > 0. && steps[0] < 10.);
+    assert!(steps.windows(2).all(|pair| pair[1] <= pair[0]));
+    assert_eq!(steps.last(), Some(&0.));
+}
+
+#[test]
+fn a_new_touch_stops_the_glide() {
+    let (mut app, camera) = app(Some(Momentum {
+        friction: 0.1,
+        ..default()
+    }));
+    drag(&mut app);
+    app.update();
+    let stopped = view(&app, camera).0.x;
+    send(&mut app, &[down(1, (100., 0.))]);
+    app.update();
+    assert_eq!(view(&app, camera).0.x, stopped);
+
+    // the resting finger is lifted without a glide
+    send(&mut app, &[up(1, (100., 0.))]);
+    app.update();
+    assert_eq!(view(&app, camera).0.x, stopped);
+}
+
+#[test]
+fn pinch_zoom_glides() {
+    let (mut app, camera) = app(Some(Momentum::default()));
+    send(&mut app, &[down(0, (-10., 0.)), down(1, (10., 0.))]);
+    send(&mut app, &[to(0, (-20., 0.))]);
+    send(&mut app, &[to(0, (-30., 0.))]);
+    let released = view(&app, camera).1;
+    assert!(released < 1.);
+
+    send(&mut app, &[up(0, (-30., 0.)), up(1, (10., 0.))]);
+    app.update();
+    let glided = view(&app, camera).1;
+    assert!(glided < released);
+    for _ in 0..20 {
+        app.update();
+    }
+    let stopped = view(&app, camera).1;
+    assert!(stopped < glided);
+    app.update();
+    assert_eq!(view(&app, camera).1, stopped);
+}
+
+#[test]
+fn a_fast_spread_glides_and_stops() {
+    let (mut app, camera) = app(Some(Momentum::default()));
+    send(&mut app, &[down(0, (0., 0.)), down(1, (100., 0.))]);
+    // 300 pixels in one frame would zoom by a factor below 0
+    send(&mut app, &[to(1, (400., 0.))]);
+    assert_eq!(view(&app, camera).1, 0.5);
+    send(&mut app, &[up(0, (0., 0.)), up(1, (400., 0.))]);
+    for _ in 0..50 {
+        app.update();
+    }
+    let stopped = view(&app, camera).1;
+    assert!(stopped.is_finite() && stopped > 0.);
+    app.update();
+    assert_eq!(view(&app, camera).1, stopped);
+}
+
+#[test]
+fn events_glide_then_end() {
+    let (mut app, camera) = app(Some(Momentum::default()));
+    app.world.resource_mut::<TouchCameraConfig>().move_cameras = false;
+    let mut events: Vec<TouchCameraEvent> = vec![];
+    let mut read = |app: &mut App| {
+        events.extend(app.world.resource_mut::<Events<TouchCameraEvent>>().drain());
+    };
+    drag(&mut app);
+    read(&mut app);
+    for _ in 0..20 {
+        app.update();
+        read(&mut app);
+    }
+    let phases: Vec<GesturePhase> = events.iter().map(|event| event.phase).collect();
+    let gliding = phases
+        .iter()
+        .position(|phase| *phase == GesturePhase::Gliding);
+    assert_eq!(phases[gliding.unwrap() - 1], GesturePhase::Moved);
+    assert_eq!(events[gliding.unwrap()].pan, Vec2::new(10., 0.));
+    assert!(phases[gliding.unwrap()..phases.len() - 1]
+        .iter()
+        .all(|phase| *phase == GesturePhase::Gliding));
+    assert_eq!(phases.last(), Some(&GesturePhase::Ended));
+    assert_eq!(view(&app, camera).0.x, 0.);
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use bevy::{
+    input::{
+        keyboard::KeyboardInput,
+        mouse::{MouseButtonInput, MouseScrollUnit, MouseWheel},
+        ButtonState,
+    },
+    prelude::*,
+    window::CursorMoved,
+};
+use bevy_touch_camera::{GestureEvent, GesturePhase, GesturePlugin};
+use common::headless;
+
+fn app() -> App {
+    let mut app = headless(GesturePlugin::default());
+    app.update();
+    app
+}
+
+/// Runs a frame and returns the gestures recognized in it
+fn frame(app: &mut App) -> Vec<GestureEvent> {
+    app.update();
+    app.world
+        .resource_mut::<Events<GestureEvent>>()
+        .drain()
+        .collect()
+}
+
+fn cursor(app: &mut App, x: f32, y: f32) {
+    app.world.send_event(CursorMoved {
+        window: Entity::PLACEHOLDER,
+        position: Vec2::new(x, y),
+    });
+}
+
+fn button(app: &mut App, state: ButtonState) {
+    app.world.send_event(MouseButtonInput {
+        button: MouseButton::Left,
+        state,
+        window: Entity::PLACEHOLDER,
+    });
+}
+
+fn alt(app: &mut App, state: ButtonState) {
+    app.world.send_event(KeyboardInput {
+        scan_code: 0,
+        key_code: Some(KeyCode::AltLeft),
+        state,
+        window: Entity::PLACEHOLDER,
+    });
+}
+
+fn pinch(phase: GesturePhase, fingers: [Vec2; 2], last: [Vec2; 2]) -> GestureEvent {
+    GestureEvent::Pinch {
+        phase,
+        fingers,
+        last,
+    }
+}
+
+#[test]
+fn dragging_pans() {
+    let mut app = app();
+    let start = Vec2::new(100., 100.);
+    cursor(&mut app, start.x, start.y);
+    button(&mut app, ButtonState::Pressed);
+    assert_eq!(
+        frame(&mut app),
+        [GestureEvent::Pan {
+            phase: GesturePhase::Started,
+            start,
+            position: start,
+            delta: Vec2::ZERO,
+        }]
+    );
+    let moved = Vec2::new(150., 100.);
+    cursor(&mut app, moved.x, moved.y);
+    assert_eq!(
+        frame(&mut app),
+        [GestureEvent::Pan {
+            phase: GesturePhase::Moved,
+            start,
+            position: moved,
+            delta: Vec2::X * 50.,
+        }]
+    );
+    button(&mut app, ButtonState::Released);
+    assert!(frame(&mut app).contains(&GestureEvent::Pan {
+        phase: GesturePhase::Ended,
+        start,
+        position: moved,
+        delta: Vec2::ZERO,
+    }));
+}
+
+#[test]
+fn dragging_with_the_modifier_pinches() {
+    let mut app = app();
+    cursor(&mut app, 300., 300.);
+    alt(&mut app, ButtonState::Pressed);
+    button(&mut app, ButtonState::Pressed);
+    // the second finger touches down 200 pixels to the left and mirrors the cursor
+    let start = [Vec2::new(300., 300.), Vec2::new(100., 300.)];
+    assert_eq!(
+        frame(&mut app),
+        [pinch(GesturePhase::Started, start, start)]
+    );
+    cursor(&mut app, 320., 310.);
+    let moved = [Vec2::new(320., 310.), Vec2::new(80., 290.)];
+    assert_eq!(frame(&mut app), [pinch(GesturePhase::Moved, moved, start)]);
+    alt(&mut app, ButtonState::Released);
+    assert!(matches!(
+        frame(&mut app)[..],
+        [GestureEvent::Pinch {
+            phase: GesturePhase::Ended,
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn scrolling_pinches_around_the_cursor() {
+    let mut app = app();
+    cursor(&mut app, 400., 300.);
+    app.world.send_event(MouseWheel {
+        unit: MouseScrollUnit::Line,
+        x: 0.,
+        y: 1.,
+        window: Entity::PLACEHOLDER,
+    });
+    let start = [Vec2::new(300., 300.), Vec2::new(500., 300.)];
+    assert_eq!(
+        frame(&mut app),
+        [pinch(GesturePhase::Started, start, start)]
+    );
+    // the fingers spread by the scroll in the next frame and are lifted after it
+    let spread = [Vec2::new(290., 300.), Vec2::new(510., 300.)];
+    let gestures = frame(&mut app);
+    assert_eq!(gestures.len(), 1);
+    let GestureEvent::Pinch { fingers, .. } = gestures[0] else {
+        panic!("no pinch in {gestures:?}");
+    };
+    assert!(fingers[0].distance(spread[0]) < 1e-3 && fingers[1].distance(spread[1]) < 1e-3);
+    assert!(matches!(
+        frame(&mut app)[..],
+        [GestureEvent::Pinch {
+            phase: GesturePhase::Ended,
+            ..
+        }]
+    ));
+}
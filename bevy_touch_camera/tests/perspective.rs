@@ -0,0 +1,155 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::{input::touch::TouchPhase, prelude::*};
+use bevy_touch_camera::{Perspective, PerspectiveZoom, TouchCameraConfig, TouchCameraPlugin};
+use common::{send, touch, windowed};
+
+/// A perspective camera 10 units in front of the origin, looking at it
+fn app(config: TouchCameraConfig) -> (App, Entity) {
+    let mut app = windowed(TouchCameraPlugin { config });
+    let camera = common::camera(
+        &mut app,
+        (
+            Camera::default(),
+            Projection::Perspective(PerspectiveProjection::default()),
+            TransformBundle::from_transform(Transform::from_xyz(0., 0., 10.)),
+        ),
+    );
+    (app, camera)
+}
+
+/// Moves both fingers in the same frame
+fn fingers(app: &mut App, phase: TouchPhase, positions: &[Vec2]) {
+    let touches: Vec<_> = (0..)
+        .zip(positions)
+        .map(|(id, &position)| touch(id, phase, position))
+        .collect();
+    send(app, &touches);
+}
+
+/// Spreads two fingers around the middle of the window from 100 to 200 pixels apart
+fn spread(app: &mut App) {
+    let middle = Vec2::new(400., 300.);
+    let apart = |distance: f32| {
+        [
+            middle - Vec2::X * distance / 2.,
+            middle + Vec2::X * distance / 2.,
+        ]
+    };
+    fingers(app, TouchPhase::Started, &apart(100.));
+    fingers(app, TouchPhase::Moved, &apart(200.));
+    fingers(app, TouchPhase::Ended, &apart(200.));
+}
+
+fn transform(app: &App, camera: Entity) -> Transform {
+    *app.world.get::<Transform>(camera).unwrap()
+}
+
+fn fov(app: &App, camera: Entity) -> f32 {
+    match app.world.get::<Projection>(camera).unwrap() {
+        Projection::Perspective(perspective) => perspective.fov,
+        Projection::Orthographic(_) => unreachable!(),
+    }
+}
+
+fn assert_near(a: Vec3, b: Vec3) {
+    assert!(a.distance(b) < 1e-3, "{a} != {b}");
+}
+
+#[test]
+fn panning_moves_the_focus_plane_with_the_finger() {
+    let (mut app, camera) = app(TouchCameraConfig::default());
+    let start = Vec2::new(400., 300.);
+    let end = start + Vec2::new(60., 30.);
+    fingers(&mut app, TouchPhase::Started, &[start]);
+    fingers(&mut app, TouchPhase::Moved, &[end]);
+    fingers(&mut app, TouchPhase::Ended, &[end]);
+
+    // the focus plane 10 units away shows this many units per pixel
+    let per_pixel = 2. * 10. * (FRAC_PI_4 / 2.).tan() / 600.;
+    assert_near(
+        transform(&app, camera).translation,
+        Vec3::new(-60. * per_pixel, 30. * per_pixel, 10.),
+    );
+}
+
+#[test]
+fn pinching_dollies_towards_the_focus() {
+    let (mut app, camera) = app(TouchCameraConfig {
+        world_anchored: true,
+        ..default()
+    });
+    spread(&mut app);
+    assert_near(transform(&app, camera).translation, Vec3::new(0., 0., 5.));
+    assert_eq!(fov(&app, camera), FRAC_PI_4);
+
+    // panning is finer this close
+    let start = Vec2::new(400., 300.);
+    fingers(&mut app, TouchPhase::Started, &[start]);
+    fingers(&mut app, TouchPhase::Moved, &[start + Vec2::X * 60.]);
+    let per_pixel = 2. * 5. * (FRAC_PI_4 / 2.).tan() / 600.;
+    assert_near(
+        transform(&app, camera).translation,
+        Vec3::new(-60. * per_pixel, 0., 5.),
+    );
+    fingers(&mut app, TouchPhase::Ended, &[start + Vec2::X * 60.]);
+
+    // the camera keeps half of a new focus distance
+    app.world
+        .resource_mut::<TouchCameraConfig>()
+        .perspective
+        .focus_distance = 20.;
+    fingers(&mut app, TouchPhase::Started, &[start]);
+    fingers(&mut app, TouchPhase::Moved, &[start + Vec2::Y * 60.]);
+    let per_pixel_far = 2. * 10. * (FRAC_PI_4 / 2.).tan() / 600.;
+    assert_near(
+        transform(&app, camera).translation,
+        Vec3::new(-60. * per_pixel, 60. * per_pixel_far, 5.),
+    );
+}
+
+#[test]
+fn pinching_can_change_the_fov() {
+    let (mut app, camera) = app(TouchCameraConfig {
+        world_anchored: true,
+        perspective: Perspective {
+            zoom: PerspectiveZoom::Fov,
+            ..default()
+        },
+        ..default()
+    });
+    spread(&mut app);
+    assert_near(transform(&app, camera).translation, Vec3::new(0., 0., 10.));
+    assert!((fov(&app, camera) - FRAC_PI_4 / 2.).abs() < 1e-5);
+}
+
+#[test]
+fn orbiting_keeps_looking_at_the_focus() {
+    let (mut app, camera) = app(TouchCameraConfig {
+        perspective: Perspective {
+            orbit: true,
+            ..default()
+        },
+        ..default()
+    });
+    let start = Vec2::new(400., 300.);
+    fingers(&mut app, TouchPhase::Started, &[start]);
+    // a quarter of the width turns a quarter around
+    fingers(&mut app, TouchPhase::Moved, &[start + Vec2::new(200., 0.)]);
+    let orbited = transform(&app, camera);
+    assert_near(orbited.translation, Vec3::new(-10., 0., 0.));
+    assert_near(orbited.forward(), Vec3::X);
+
+    // looking down from above
+    fingers(
+        &mut app,
+        TouchPhase::Moved,
+        &[start + Vec2::new(200., 120.)],
+    );
+    let orbited = transform(&app, camera);
+    assert!((orbited.translation.length() - 10.).abs() < 1e-3);
+    assert!(orbited.translation.y > 0.);
+    assert_near(orbited.forward(), -orbited.translation.normalize());
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_touch_camera::TouchCameraConfig;
+use common::{app, down, play, to, up, view};
+
+#[test]
+fn panning() {
+    let (mut app, camera) = app(TouchCameraConfig::default());
+    play(
+        &mut app,
+        &[
+            &[down(0, (100., 100.))],
+            &[to(0, (130., 80.))],
+            &[to(0, (160., 60.))],
+            &[up(0, (160., 60.))],
+        ],
+    );
+    // the window's y points down
+    assert_eq!(view(&app, camera), (Vec2::new(-60., -40.), 1.));
+}
+
+#[test]
+fn pinching() {
+    let (mut app, camera) = app(TouchCameraConfig::default());
+    play(
+        &mut app,
+        &[
+            &[down(0, (300., 300.)), down(1, (500., 300.))],
+            &[to(1, (550., 300.))],
+        ],
+    );
+    // 50 pixels apart with a `zoom_sensitivity` of `0.005`
+    assert_eq!(view(&app, camera), (Vec2::ZERO, 0.75));
+    play(&mut app, &[&[to(1, (600., 300.))]]);
+    assert_eq!(view(&app, camera), (Vec2::ZERO, 0.5625));
+
+    // closing the fingers zooms back out
+    play(
+        &mut app,
+        &[
+            &[to(1, (550., 300.))],
+            &[up(0, (300., 300.)), up(1, (550., 300.))],
+        ],
+    );
+    assert_eq!(view(&app, camera), (Vec2::ZERO, 0.5625 * 1.25));
+}
+
+#[test]
+fn pinching_then_releasing_one_finger() {
+    let (mut app, camera) = app(TouchCameraConfig::default());
+    play(
+        &mut app,
+        &[
+            &[down(0, (300., 300.)), down(1, (500., 300.))],
+            &[to(1, (550., 300.))],
+            &[up(1, (550., 300.))],
+            // the finger left over doesn't pan
+            &[to(0, (250., 300.))],
+            &[to(0, (200., 250.))],
+        ],
+    );
+    assert_eq!(view(&app, camera), (Vec2::ZERO, 0.75));
+
+    // until it was lifted too
+    play(
+        &mut app,
+        &[
+            &[up(0, (200., 250.))],
+            &[down(0, (100., 100.))],
+            &[to(0, (110., 100.))],
+        ],
+    );
+    assert_eq!(view(&app, camera), (Vec2::new(-7.5, 0.), 0.75));
+}
+
+#[test]
+fn three_fingers() {
+    let (mut app, camera) = app(TouchCameraConfig::default());
+    play(
+        &mut app,
+        &[
+            &[down(0, (300., 300.)), down(1, (500., 300.))],
+            &[down(2, (400., 400.))],
+            &[
+                to(0, (250., 300.)),
+                to(1, (600., 300.)),
+                to(2, (400., 450.)),
+            ],
+            &[up(2, (400., 450.))],
+            &[to(0, (200., 300.))],
+            &[up(0, (200., 300.)), up(1, (600., 300.))],
+        ],
+    );
+    assert_eq!(view(&app, camera), (Vec2::ZERO, 1.));
+
+    // also when they touch down together
+    play(
+        &mut app,
+        &[
+            &[
+                down(0, (300., 300.)),
+                down(1, (500., 300.)),
+                down(2, (400., 400.)),
+            ],
+            &[
+                to(0, (250., 300.)),
+                to(1, (600., 300.)),
+                to(2, (400., 450.)),
+            ],
+            &[
+                up(0, (250., 300.)),
+                up(1, (600., 300.)),
+                up(2, (400., 450.)),
+            ],
+        ],
+    );
+    assert_eq!(view(&app, camera), (Vec2::ZERO, 1.));
+}
+
+#[test]
+fn quick_taps_under_touch_time_min() {
+    let (mut app, camera) = app(TouchCameraConfig {
+        touch_time_min: 0.45,
+        ..default()
+    });
+    for _ in 0..3 {
+        play(
+            &mut app,
+            &[
+                &[down(0, (100., 100.))],
+                &[to(0, (120., 100.))],
+                &[up(0, (120., 100.))],
+            ],
+        );
+    }
+    assert_eq!(view(&app, camera), (Vec2::ZERO, 1.));
+
+    // a finger resting long enough catches up with the whole drag
+    play(
+        &mut app,
+        &[
+            &[down(0, (100., 100.))],
+            &[to(0, (120., 100.))],
+            &[],
+            &[],
+            &[],
+        ],
+    );
+    assert_eq!(view(&app, camera), (Vec2::ZERO, 1.));
+    play(&mut app, &[&[]]);
+    assert_eq!(view(&app, camera), (Vec2::new(-20., 0.), 1.));
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{input::touch::TouchPhase, prelude::*};
+use bevy_touch_camera::{TouchCameraConfig, TouchCameraPlugin, Twist};
+use common::{down, send, to, touch, view, windowed};
+
+/// A camera in the middle of the window
+fn app(config: TouchCameraConfig) -> (App, Entity) {
+    let mut app = windowed(TouchCameraPlugin { config });
+    let camera = common::camera(
+        &mut app,
+        (
+            Camera2dBundle::default().camera,
+            OrthographicProjection::default(),
+            TransformBundle::default(),
+        ),
+    );
+    (app, camera)
+}
+
+/// Moves both fingers in the same frame
+fn fingers(app: &mut App, phase: TouchPhase, (a, b): (Vec2, Vec2)) {
+    send(app, &[touch(0, phase, a), touch(1, phase, b)]);
+}
+
+/// Twists two fingers 100 pixels around the middle of the window counterclockwise, 10° a frame
+fn twist(app: &mut App, degrees: u32) {
+    let middle = Vec2::new(400., 300.);
+    let finger = |degrees: u32| {
+        // the window's y points down
+        let offset = Vec2::from_angle((degrees as f32).to_radians()) * Vec2::new(100., -100.);
+        (middle - offset, middle + offset)
+    };
+    fingers(app, TouchPhase::Started, finger(0));
+    for step in (10..=degrees).step_by(10) {
+        fingers(app, TouchPhase::Moved, finger(step));
+    }
+    fingers(app, TouchPhase::Ended, finger(degrees));
+}
+
+fn angle(app: &App, camera: Entity) -> f32 {
+    let transform = app.world.get::<Transform>(camera).unwrap();
+    transform.rotation.to_euler(EulerRot::XYZ).2
+}
+
+fn assert_near(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-3, "{a} != {b}");
+}
+
+#[test]
+fn twisting_rotates_around_the_fingers() {
+    let (mut app, camera) = app(TouchCameraConfig {
+        world_anchored: true,
+        twist: Some(Twist::default()),
+        ..default()
+    });
+    twist(&mut app, 90);
+    // the first 20° are needed to pass the threshold
+    assert_near(angle(&app, camera), -70f32.to_radians());
+    assert!(view(&app, camera).0.length() < 1e-3);
+}
+
+#[test]
+fn pinching_doesnt_rotate() {
+    let (mut app, camera) = app(TouchCameraConfig {
+        world_anchored: true,
+        twist: Some(Twist::default()),
+        ..default()
+    });
+    let (a, b) = (Vec2::new(200., 310.), Vec2::new(600., 290.));
+    fingers(
+        &mut app,
+        TouchPhase::Started,
+        (Vec2::new(300., 300.), Vec2::new(500., 300.)),
+    );
+    fingers(&mut app, TouchPhase::Moved, (a, b));
+    assert!(view(&app, camera).1 < 1.);
+    assert_eq!(angle(&app, camera), 0.);
+
+    // nor twisting without the option
+    app.world.resource_mut::<TouchCameraConfig>().twist = None;
+    fingers(&mut app, TouchPhase::Ended, (a, b));
+    twist(&mut app, 90);
+    assert_eq!(angle(&app, camera), 0.);
+}
+
+#[test]
+fn snapping_to_right_angles() {
+    let (mut app, camera) = app(TouchCameraConfig {
+        world_anchored: true,
+        twist: Some(Twist {
+            snap: true,
+            ..default()
+        }),
+        ..default()
+    });
+    twist(&mut app, 90);
+    assert_near(angle(&app, camera), -FRAC_PI_2);
+}
+
+#[test]
+fn panning_a_rotated_camera() {
+    let (mut app, camera) = app(TouchCameraConfig::default());
+    app.world
+        .get_mut::<Transform>(camera)
+        .unwrap()
+        .rotate_z(FRAC_PI_2);
+    send(&mut app, &[down(0, (400., 300.))]);
+    send(&mut app, &[to(0, (410., 300.))]);
+    // the world follows the finger to the right of the screen, which is the world's -y
+    let moved = view(&app, camera).0;
+    assert_near(moved.x, 0.);
+    assert_near(moved.y, -10.);
+}
@@ -0,0 +1,353 @@
+//! Colour maps for the cubes and the gradient legend explaining them.
+//!
+//! A [`ColorMode`] decides which value of a cube is shown, a [`Palette`] turns the value,
+//! scaled to `0..=1` by the [`ColorScale`], into a colour.
+
+use bevy::prelude::*;
+
+use crate::Float;
+
+/// The value of a cube that decides its colour.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorMode {
+    /// Refinement level of the cube.
+    #[default]
+    Level,
+    /// Height of the top of the cube.
+    Height,
+    /// Riemann sum of the cell minus the integral over it.
+    Error,
+    /// Whether the integrand is positive or negative at the sample point.
+    Sign,
+}
+
+impl ColorMode {
+    pub const ALL: [Self; 4] = [Self::Level, Self::Height, Self::Error, Self::Sign];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Level => "Stufe",
+            Self::Height => "Höhe",
+            Self::Error => "Fehler",
+            Self::Sign => "Vorzeichen",
+        }
+    }
+}
+
+/// Colour gradients from `0` to `1`.
+///
+/// Viridis, Magma and Cividis are perceptually uniform, Cividis is also designed to look
+/// the same with red-green colour blindness.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+    /// The original purple to green colours of the levels 1 to 6.
+    #[default]
+    Classic,
+    Viridis,
+    Magma,
+    Cividis,
+}
+
+const VIRIDIS: [u32; 9] = [
+    0x440154, 0x472d7b, 0x3b528b, 0x2c728e, 0x21918c, 0x28ae80, 0x5ec962, 0xaddc30, 0xfde725,
+];
+const MAGMA: [u32; 9] = [
+    0x000004, 0x1c1040, 0x4f127b, 0x812581, 0xb5367a, 0xe55964, 0xfb8761, 0xfec287, 0xfcfdbf,
+];
+const CIVIDIS: [u32; 9] = [
+    0x00224e, 0x123570, 0x3b496c, 0x575d6d, 0x707173, 0x8a8779, 0xa69d75, 0xc4b56c, 0xfee838,
+];
+
+fn rgb(hex: u32) -> Vec3 {
+    Vec3::new(
+        (hex >> 16 & 0xff) as f32,
+        (hex >> 8 & 0xff) as f32,
+        (hex & 0xff) as f32,
+    ) / 255.
+}
+
+impl Palette {
+    pub const ALL: [Self; 4] = [Self::Classic, Self::Viridis, Self::Magma, Self::Cividis];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "Klassisch",
+            Self::Viridis => "Viridis",
+            Self::Magma => "Magma",
+            Self::Cividis => "Cividis (farbenblind)",
+        }
+    }
+
+    /// The colour at `t`, which is clamped to `0..=1`.
+    pub fn sample(self, t: Float) -> Color {
+        let t = if t.is_nan() { 0. } else { t.clamp(0., 1.) };
+        let stops = match self {
+            Self::Classic => {
+                // used to be `Color::rgb_u8(124, 40 * n, 255 / n)` for the levels `n = 1..=6`
+                let n = 1. + 5. * t;
+                return Color::rgb(124. / 255., 40. * n / 255., 1. / n);
+            }
+            Self::Viridis => &VIRIDIS,
+            Self::Magma => &MAGMA,
+            Self::Cividis => &CIVIDIS,
+        };
+        let x = t * (stops.len() - 1) as Float;
+        let idx = (x.floor() as usize).min(stops.len() - 2);
+        let c = rgb(stops[idx]).lerp(rgb(stops[idx + 1]), x - idx as Float);
+        Color::rgb(c.x, c.y, c.z)
+    }
+}
+
+/// The current mapping of cube values to colours, shared by both sides of the split screen
+/// so their colours can be compared.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ColorScale {
+    pub mode: ColorMode,
+    pub palette: Palette,
+    pub min: Float,
+    pub max: Float,
+}
+
+impl Default for ColorScale {
+    fn default() -> Self {
+        Self {
+            mode: ColorMode::default(),
+            palette: Palette::default(),
+            min: 0.,
+            max: 1.,
+        }
+    }
+}
+
+impl ColorScale {
+    /// Where `value` lies between `min` and `max`.
+    pub fn position(&self, value: Float) -> Float {
+        if self.max > self.min {
+            (value - self.min) / (self.max - self.min)
+        } else {
+            0.5
+        }
+    }
+
+    pub fn color(&self, value: Float) -> Color {
+        self.palette.sample(self.position(value))
+    }
+
+    fn label(&self, value: Float) -> String {
+        match self.mode {
+            ColorMode::Level => format!("{value:.0}"),
+            ColorMode::Sign if value < 0. => "-".to_string(),
+            ColorMode::Sign => "+".to_string(),
+            ColorMode::Height | ColorMode::Error => format!("{value:.3}"),
+        }
+    }
+}
+
+const SWATCHES: usize = 32;
+
+#[derive(Component)]
+pub(crate) enum Legend {
+    Title,
+    Swatch(usize),
+    Min,
+    Max,
+}
+
+pub(crate) fn setup_legend(mut commands: Commands) {
+    let text = |part: Legend| {
+        (
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.,
+                    ..default()
+                },
+            ),
+            part,
+        )
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(25.0),
+                right: Val::Px(25.0),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(text(Legend::Title));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        height: Val::Px(16.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for idx in 0..SWATCHES {
+                        parent.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(6.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            Legend::Swatch(idx),
+                        ));
+                    }
+                });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        justify_content: JustifyContent::SpaceBetween,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(text(Legend::Min));
+                    parent.spawn(text(Legend::Max));
+                });
+        });
+}
+
+pub(crate) fn legend(
+    scale: Res<ColorScale>,
+    mut parts: Query<(&Legend, Option<&mut Text>, Option<&mut BackgroundColor>)>,
+) {
+    if !scale.is_changed() {
+        return;
+    }
+    for (part, text, background) in &mut parts {
+        match (part, text, background) {
+            (Legend::Title, Some(mut text), _) => {
+                text.sections[0].value =
+                    format!("{} ({})", scale.mode.name(), scale.palette.name());
+            }
+            (Legend::Min, Some(mut text), _) => text.sections[0].value = scale.label(scale.min),
+            (Legend::Max, Some(mut text), _) => text.sections[0].value = scale.label(scale.max),
+            (Legend::Swatch(idx), _, Some(mut background)) => {
+                let t = (*idx as Float + 0.5) / SWATCHES as Float;
+                background.0 = scale.palette.sample(t);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controls::ControlInput;
+    use crate::core::Rule;
+    use crate::testing::*;
+    use crate::Cube;
+
+    fn cube_colors(app: &mut App, level: u8) -> Vec<Color> {
+        let mut query = app
+            .world
+            .query::<(&Cube, &Visibility, &Handle<StandardMaterial>)>();
+        let handles: Vec<_> = query
+            .iter(&app.world)
+            .filter(|(cube, vis, _)| cube.size_n == level && **vis != Visibility::Hidden)
+            .map(|(_, _, handle)| handle.clone())
+            .collect();
+        let materials = app.world.resource::<Assets<StandardMaterial>>();
+        handles
+            .iter()
+            .map(|handle| materials.get(handle).unwrap().base_color)
+            .collect()
+    }
+
+    fn legend_texts(app: &mut App) -> Vec<String> {
+        let mut query = app.world.query::<(&Legend, &Text)>();
+        let mut texts: Vec<_> = query
+            .iter(&app.world)
+            .map(|(part, text)| {
+                let order = match part {
+                    Legend::Title => 0,
+                    Legend::Min => 1,
+                    _ => 2,
+                };
+                (order, text.sections[0].value.clone())
+            })
+            .collect();
+        texts.sort();
+        texts.into_iter().map(|(_, text)| text).collect()
+    }
+
+    #[test]
+    fn cubes_are_colored_by_level() {
+        let mut app = test_app();
+        config(&mut app).n = 2;
+        app.update();
+        for (level, t) in [(1, 0.), (2, 0.2)] {
+            let colors = cube_colors(&mut app, level);
+            assert!(!colors.is_empty());
+            assert!(colors.iter().all(|c| *c == Palette::Classic.sample(t)));
+        }
+        assert_eq!(legend_texts(&mut app), ["Stufe (Klassisch)", "1", "6"]);
+    }
+
+    #[test]
+    fn error_colors_and_legend() {
+        let mut app = test_app();
+        // level -> height -> error
+        press(&mut app, "color_mode", ControlInput::Press);
+        press(&mut app, "color_mode", ControlInput::Press);
+        press(&mut app, "palette", ControlInput::Press);
+        let scale = app.world.resource::<ColorScale>().clone();
+        assert_eq!(scale.mode, ColorMode::Error);
+        assert_eq!(scale.palette, Palette::Viridis);
+        // the lower left corner is the minimum of f on every cell, so each cell is
+        // 1/4 * (1/4 + 1/4) too low
+        assert!((scale.min + 0.125).abs() < 1e-5, "{scale:?}");
+        assert_eq!(scale.min, -scale.max);
+        assert_eq!(
+            legend_texts(&mut app),
+            ["Fehler (Viridis)", "-0.125", "0.125"]
+        );
+        for color in cube_colors(&mut app, 1) {
+            assert_eq!(color, Palette::Viridis.sample(0.));
+        }
+    }
+
+    #[test]
+    fn sign_colors_follow_the_sampled_height() {
+        let mut app = test_app();
+        {
+            let mut config = config(&mut app);
+            config.color_mode = ColorMode::Sign;
+            config.rule = Rule::UpperRight;
+            config.n = 2;
+        }
+        app.update();
+        // refining the upper right sum lowers it, but f stays positive
+        for level in 1..=2 {
+            for color in cube_colors(&mut app, level) {
+                assert_eq!(color, Palette::Classic.sample(1.));
+            }
+        }
+
+        let mut query = app.world.query::<&mut Cube>();
+        for mut cube in query.iter_mut(&mut app.world) {
+            if cube.size_n == 2 {
+                cube.cell.height = -cube.cell.height;
+            }
+        }
+        config(&mut app).set_changed();
+        app.update();
+        for color in cube_colors(&mut app, 1) {
+            assert_eq!(color, Palette::Classic.sample(1.));
+        }
+        for color in cube_colors(&mut app, 2) {
+            assert_eq!(color, Palette::Classic.sample(0.));
+        }
+    }
+}
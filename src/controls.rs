@@ -0,0 +1,948 @@
+//! The control panel on the left side, generated from a [`ControlRegistry`].
+//!
+//! Every [`Control`] is bound to a value in some resource through a [`Binding`], so an
+//! app can add its own controls with [`RegisterControl::register_control`] before `Startup`.
+
+use bevy::{
+    ecs::event::ManualEventReader,
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    ui::RelativeCursorPosition,
+    window::PrimaryWindow,
+};
+
+pub(crate) const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+pub(crate) const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+const SLIDER_FILL: Color = Color::rgb(0.35, 0.35, 0.35);
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+/// Distance of the panel from the left edge of the window, in logical pixels.
+const PANEL_LEFT: f32 = 25.0;
+/// Width of the panel in percent of the window.
+const PANEL_WIDTH: f32 = 20.0;
+/// Fraction of the range a slider moves by with [`ControlInput::Increase`] and
+/// [`ControlInput::Decrease`].
+const SLIDER_STEP: f32 = 0.1;
+/// How far one line of the mouse wheel scrolls the panel.
+const SCROLL_LINE: f32 = 40.0;
+
+type Getter<T> = Box<dyn Fn(&World) -> T + Send + Sync>;
+type Setter<T> = Box<dyn Fn(&mut World, T) + Send + Sync>;
+
+/// Reads and writes a value of type `T` somewhere in the `World`.
+pub struct Binding<T> {
+    get: Getter<T>,
+    set: Setter<T>,
+}
+
+impl<T: 'static> Binding<T> {
+    pub fn new(
+        get: impl Fn(&World) -> T + Send + Sync + 'static,
+        set: impl Fn(&mut World, T) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            get: Box::new(get),
+            set: Box::new(set),
+        }
+    }
+
+    /// Binds to a field of the resource `R`.
+    pub fn resource<R: Resource>(get: fn(&R) -> T, set: fn(&mut R, T)) -> Self {
+        Self {
+            get: Box::new(move |world| get(world.resource::<R>())),
+            set: Box::new(move |world, value| set(&mut world.resource_mut::<R>(), value)),
+        }
+    }
+
+    /// Binds to a field of the component `C` of `entity`. Once the entity is gone it reads
+    /// the default of `T` and ignores writes.
+    pub fn component<C: Component>(entity: Entity, get: fn(&C) -> T, set: fn(&mut C, T)) -> Self
+    where
+        T: Default,
+    {
+        Self {
+            get: Box::new(move |world| world.get::<C>(entity).map_or_else(T::default, get)),
+            set: Box::new(move |world, value| {
+                if let Some(mut component) = world.get_mut::<C>(entity) {
+                    set(&mut component, value);
+                }
+            }),
+        }
+    }
+
+    pub fn get(&self, world: &World) -> T {
+        (self.get)(world)
+    }
+
+    pub fn set(&self, world: &mut World, value: T) {
+        (self.set)(world, value)
+    }
+}
+
+pub enum ControlKind {
+    /// A button that switches a flag on and off.
+    Toggle(Binding<bool>),
+    /// A value display with a button to increase and one to decrease the value.
+    Stepper {
+        binding: Binding<i32>,
+        min: i32,
+        max: i32,
+        more: &'static str,
+        less: &'static str,
+    },
+    /// A bar that sets the value relative to where it is clicked.
+    Slider {
+        binding: Binding<f32>,
+        min: f32,
+        max: f32,
+    },
+    /// A button that cycles through the options.
+    Choice {
+        binding: Binding<usize>,
+        options: Vec<&'static str>,
+    },
+    /// A button that runs a function when pressed.
+    Trigger(Box<dyn Fn(&mut World) + Send + Sync>),
+}
+
+/// A value read from a scene file, see [`Control::load`].
+enum Saved {
+    Flag(bool),
+    Step(i32),
+    Amount(f32),
+    Option(usize),
+}
+
+pub struct Control {
+    /// Identifies the control in [`ControlEvent`]s.
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: ControlKind,
+}
+
+impl Control {
+    pub fn new(key: &'static str, label: &'static str, kind: ControlKind) -> Self {
+        Self { key, label, kind }
+    }
+
+    /// The text shown on the control for the current value.
+    fn text(&self, world: &World) -> String {
+        match &self.kind {
+            ControlKind::Toggle(binding) => {
+                if binding.get(world) {
+                    format!("{} :)", self.label)
+                } else {
+                    self.label.to_string()
+                }
+            }
+            ControlKind::Stepper { binding, .. } => {
+                format!("{}: {}", self.label, binding.get(world))
+            }
+            ControlKind::Slider { binding, .. } => {
+                format!("{}: {:.1}", self.label, binding.get(world))
+            }
+            ControlKind::Choice { binding, options } => format!(
+                "{}: {}",
+                self.label,
+                options.get(binding.get(world)).unwrap_or(&"?")
+            ),
+            ControlKind::Trigger(_) => self.label.to_string(),
+        }
+    }
+
+    /// The value in the form written to a scene file, `None` for a trigger.
+    pub fn save(&self, world: &World) -> Option<String> {
+        match &self.kind {
+            ControlKind::Toggle(binding) => Some(binding.get(world).to_string()),
+            ControlKind::Stepper { binding, .. } => Some(binding.get(world).to_string()),
+            ControlKind::Slider { binding, .. } => Some(binding.get(world).to_string()),
+            ControlKind::Choice { binding, options } => options
+                .get(binding.get(world))
+                .map(|option| option.to_string()),
+            ControlKind::Trigger(_) => None,
+        }
+    }
+
+    /// Sets the value from the form [`Control::save`] writes. Numbers are clamped to the range
+    /// of the control.
+    pub fn load(&self, world: &mut World, value: &str) -> Result<(), String> {
+        match (&self.kind, self.parse(value)?) {
+            (ControlKind::Toggle(binding), Saved::Flag(value)) => binding.set(world, value),
+            (ControlKind::Stepper { binding, .. }, Saved::Step(value)) => binding.set(world, value),
+            (ControlKind::Slider { binding, .. }, Saved::Amount(value)) => {
+                binding.set(world, value)
+            }
+            (ControlKind::Choice { binding, .. }, Saved::Option(value)) => {
+                binding.set(world, value)
+            }
+            _ => unreachable!("`parse` matches the kind"),
+        }
+        Ok(())
+    }
+
+    /// Whether [`Control::load`] would accept `value`.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        self.parse(value).map(|_| ())
+    }
+
+    fn parse(&self, value: &str) -> Result<Saved, String> {
+        let invalid = || format!("invalid value `{value}` for `{}`", self.key);
+        Ok(match &self.kind {
+            ControlKind::Toggle(_) => Saved::Flag(value.parse().map_err(|_| invalid())?),
+            ControlKind::Stepper { min, max, .. } => {
+                let value: i32 = value.parse().map_err(|_| invalid())?;
+                Saved::Step(value.clamp(*min, *max))
+            }
+            ControlKind::Slider { min, max, .. } => {
+                let value = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(invalid)?;
+                Saved::Amount(value.clamp(*min, *max))
+            }
+            ControlKind::Choice { options, .. } => Saved::Option(
+                options
+                    .iter()
+                    .position(|option| *option == value)
+                    .ok_or_else(invalid)?,
+            ),
+            ControlKind::Trigger(_) => return Err(format!("`{}` has no value", self.key)),
+        })
+    }
+
+    /// Position of a slider between `0` and `1`.
+    fn fraction(&self, world: &World) -> Option<f32> {
+        match &self.kind {
+            ControlKind::Slider { binding, min, max } => {
+                Some(((binding.get(world) - min) / (max - min)).clamp(0., 1.))
+            }
+            _ => None,
+        }
+    }
+
+    fn apply(&self, world: &mut World, input: ControlInput) {
+        match (&self.kind, input) {
+            (ControlKind::Toggle(binding), ControlInput::Press) => {
+                let value = binding.get(world);
+                binding.set(world, !value);
+            }
+            (
+                ControlKind::Stepper {
+                    binding, min, max, ..
+                },
+                ControlInput::Increase | ControlInput::Decrease,
+            ) => {
+                let value = binding.get(world);
+                let step = if input == ControlInput::Increase {
+                    1
+                } else {
+                    -1
+                };
+                let new = (value + step).clamp(*min, *max);
+                if new != value {
+                    binding.set(world, new);
+                }
+            }
+            (
+                ControlKind::Slider { binding, min, max },
+                ControlInput::Increase | ControlInput::Decrease,
+            ) => {
+                let step = (max - min) * SLIDER_STEP;
+                let value = binding.get(world);
+                let new = if input == ControlInput::Increase {
+                    value + step
+                } else {
+                    value - step
+                }
+                .clamp(*min, *max);
+                if new != value {
+                    binding.set(world, new);
+                }
+            }
+            (ControlKind::Slider { binding, min, max }, ControlInput::Set(fraction)) => {
+                let value = min + fraction.clamp(0., 1.) * (max - min);
+                if value != binding.get(world) {
+                    binding.set(world, value);
+                }
+            }
+            (ControlKind::Choice { binding, options }, ControlInput::Press) => {
+                let value = binding.get(world);
+                binding.set(world, (value + 1) % options.len().max(1));
+            }
+            (ControlKind::Trigger(run), ControlInput::Press) => run(world),
+            (_, input) => warn!("control `{}` can't handle {:?}", self.key, input),
+        }
+    }
+}
+
+/// All controls of the panel, in the order they are shown.
+#[derive(Resource, Default)]
+pub struct ControlRegistry(Vec<Control>);
+
+impl ControlRegistry {
+    pub fn register(&mut self, control: Control) -> &mut Self {
+        if self.get(control.key).is_some() {
+            warn!("control `{}` is registered twice", control.key);
+        }
+        self.0.push(control);
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Control> {
+        self.0.iter().find(|control| control.key == key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Control> {
+        self.0.iter()
+    }
+}
+
+pub trait RegisterControl {
+    /// Adds a control to the panel. Has to be called before the panel is spawned in `Startup`.
+    fn register_control(&mut self, control: Control) -> &mut Self;
+}
+
+impl RegisterControl for App {
+    fn register_control(&mut self, control: Control) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ControlRegistry::default)
+            .register(control);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlInput {
+    /// Toggles a [`ControlKind::Toggle`], cycles a [`ControlKind::Choice`] and runs a [`ControlKind::Trigger`].
+    Press,
+    /// Steps a [`ControlKind::Stepper`] by one, a [`ControlKind::Slider`] by a tenth of its range.
+    Increase,
+    Decrease,
+    /// Moves a [`ControlKind::Slider`] to the given fraction of its range.
+    Set(f32),
+}
+
+/// Operates the control with the given key, like clicking on it does.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ControlEvent {
+    pub key: &'static str,
+    pub input: ControlInput,
+}
+
+/// A button of a control. `index` is the position of the control in the registry.
+#[derive(Component)]
+pub(crate) struct ControlButton {
+    pub index: usize,
+    pub key: &'static str,
+    pub input: ControlInput,
+}
+
+/// The text that shows the value of a control.
+#[derive(Component)]
+pub(crate) struct ControlLabel {
+    pub index: usize,
+}
+
+/// The value display of a [`ControlKind::Stepper`].
+#[derive(Component)]
+pub(crate) struct ControlValue;
+
+#[derive(Component)]
+pub(crate) struct SliderFill {
+    index: usize,
+}
+
+fn control_style() -> Style {
+    Style {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        border: UiRect::all(Val::Px(5.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        padding: UiRect::all(Val::Px(5.0)),
+        margin: UiRect::vertical(Val::Px(5.0)),
+        ..default()
+    }
+}
+
+fn button_bundle() -> ButtonBundle {
+    ButtonBundle {
+        style: control_style(),
+        border_color: BorderColor(Color::BLACK),
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    }
+}
+
+fn text(s: &str, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        s,
+        TextStyle {
+            font_size,
+            color: TEXT_COLOR,
+            ..default()
+        },
+    )
+}
+
+/// The column of the panel.
+#[derive(Component)]
+pub(crate) struct ControlPanel;
+
+impl ControlPanel {
+    /// Whether `position`, in logical pixels of the window, lies in the column of the panel
+    /// laid out as `node` at `transform`, or in the margin left of it.
+    pub(crate) fn contains(
+        node: &Node,
+        transform: &GlobalTransform,
+        ui_scale: &UiScale,
+        position: Vec2,
+    ) -> bool {
+        // the layout is scaled by `UiScale`, the window positions are not
+        let position = position / ui_scale.0 as f32;
+        let rect = node.logical_rect(transform);
+        Rect::new(0., rect.min.y, rect.max.x, rect.max.y).contains(position)
+    }
+}
+
+/// The controls inside the [`ControlPanel`], scrolled by `scroll_panel` when they don't fit
+/// into the window.
+#[derive(Component, Default)]
+pub(crate) struct ControlList {
+    /// How far the list is scrolled down.
+    offset: f32,
+}
+
+pub(crate) fn setup_panel(mut commands: Commands, registry: Res<ControlRegistry>) {
+    let panel = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    left: Val::Px(PANEL_LEFT),
+                    width: Val::Percent(PANEL_WIDTH),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    overflow: Overflow::clip_y(),
+                    ..default()
+                },
+                ..default()
+            },
+            ControlPanel,
+        ))
+        .id();
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    // centered while the controls fit, from the top when they don't
+                    margin: UiRect::vertical(Val::Auto),
+                    ..default()
+                },
+                ..default()
+            },
+            ControlList::default(),
+        ))
+        .set_parent(panel)
+        .with_children(|parent| {
+            for (index, control) in registry.iter().enumerate() {
+                let button = |input| ControlButton {
+                    index,
+                    key: control.key,
+                    input,
+                };
+                let label = ControlLabel { index };
+                match &control.kind {
+                    ControlKind::Stepper { more, less, .. } => {
+                        parent
+                            .spawn(NodeBundle {
+                                style: control_style(),
+                                border_color: BorderColor(Color::BLACK),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn((text(control.label, 22.0), label, ControlValue));
+                            });
+                        parent
+                            .spawn((button_bundle(), button(ControlInput::Increase)))
+                            .with_children(|parent| {
+                                parent.spawn(text(more, 20.0));
+                            });
+                        parent
+                            .spawn((button_bundle(), button(ControlInput::Decrease)))
+                            .with_children(|parent| {
+                                parent.spawn(text(less, 20.0));
+                            });
+                    }
+                    ControlKind::Slider { .. } => {
+                        parent
+                            .spawn((
+                                button_bundle(),
+                                RelativeCursorPosition::default(),
+                                button(ControlInput::Set(0.)),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn((text(control.label, 20.0), label));
+                                parent.spawn((
+                                    NodeBundle {
+                                        style: Style {
+                                            position_type: PositionType::Absolute,
+                                            left: Val::Px(0.0),
+                                            top: Val::Px(0.0),
+                                            height: Val::Percent(100.0),
+                                            width: Val::Percent(0.0),
+                                            ..default()
+                                        },
+                                        background_color: SLIDER_FILL.into(),
+                                        z_index: ZIndex::Local(-1),
+                                        ..default()
+                                    },
+                                    SliderFill { index },
+                                ));
+                            });
+                    }
+                    ControlKind::Toggle(_)
+                    | ControlKind::Choice { .. }
+                    | ControlKind::Trigger(_) => {
+                        parent
+                            .spawn((button_bundle(), button(ControlInput::Press)))
+                            .with_children(|parent| {
+                                parent.spawn((text(control.label, 20.0), label));
+                            });
+                    }
+                }
+            }
+        });
+}
+
+/// Scrolls the [`ControlList`] with the mouse wheel while the cursor is over the panel.
+pub(crate) fn scroll_panel(
+    mut wheel: EventReader<MouseWheel>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    ui_scale: Res<UiScale>,
+    panel: Query<(&Node, &GlobalTransform), With<ControlPanel>>,
+    mut lists: Query<(&mut ControlList, &mut Style, &Node)>,
+) {
+    let delta: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE,
+            MouseScrollUnit::Pixel => event.y / ui_scale.0 as f32,
+        })
+        .sum();
+    let Ok((panel, transform)) = panel.get_single() else {
+        return;
+    };
+    let cursor = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let over = cursor.is_some_and(|p| ControlPanel::contains(panel, transform, &ui_scale, p));
+    for (mut list, mut style, node) in &mut lists {
+        // also clamped without scrolling, the window may have grown
+        let max = (node.size().y - panel.size().y).max(0.);
+        let offset = if over {
+            list.offset - delta
+        } else {
+            list.offset
+        }
+        .clamp(0., max);
+        list.offset = offset;
+        let top = Val::Px(-offset);
+        if style.top != top {
+            style.top = top;
+        }
+    }
+}
+
+pub(crate) fn button_system(
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            &ControlButton,
+            Option<&RelativeCursorPosition>,
+        ),
+        Changed<Interaction>,
+    >,
+    mut events: EventWriter<ControlEvent>,
+) {
+    for (interaction, mut color, mut border_color, button, cursor) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = HOVERED_BUTTON.into();
+                border_color.0 = Color::RED;
+                // sliders are handled in `slider_system` while they are held down
+                if cursor.is_none() {
+                    events.send(ControlEvent {
+                        key: button.key,
+                        input: button.input,
+                    });
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+                border_color.0 = Color::WHITE;
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+                border_color.0 = Color::BLACK;
+            }
+        }
+    }
+}
+
+pub(crate) fn slider_system(
+    sliders: Query<(&Interaction, &RelativeCursorPosition, &ControlButton)>,
+    mut events: EventWriter<ControlEvent>,
+) {
+    for (interaction, cursor, button) in &sliders {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let Some(position) = cursor.normalized {
+            events.send(ControlEvent {
+                key: button.key,
+                input: ControlInput::Set(position.x),
+            });
+        }
+    }
+}
+
+/// Applies the [`ControlEvent`]s of the frame. They stay in the event queue, so other
+/// systems can read them too.
+pub(crate) fn apply_controls(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<ControlEvent>>,
+) {
+    let events: Vec<ControlEvent> = reader
+        .read(world.resource::<Events<ControlEvent>>())
+        .cloned()
+        .collect();
+    if events.is_empty() {
+        return;
+    }
+    world.resource_scope(|world, registry: Mut<ControlRegistry>| {
+        for event in events {
+            match registry.get(event.key) {
+                Some(control) => control.apply(world, event.input),
+                None => warn!("unknown control `{}`", event.key),
+            }
+        }
+    });
+}
+
+/// Writes the current values into the labels. Only texts that differ are touched.
+pub(crate) fn update_labels(world: &mut World) {
+    world.resource_scope(|world, registry: Mut<ControlRegistry>| {
+        let values: Vec<(String, Option<f32>)> = registry
+            .iter()
+            .map(|control| (control.text(world), control.fraction(world)))
+            .collect();
+
+        let mut labels = world.query::<(&mut Text, &ControlLabel)>();
+        for (mut text, label) in labels.iter_mut(world) {
+            let value = &values[label.index].0;
+            if text.sections[0].value != *value {
+                text.sections[0].value = value.clone();
+            }
+        }
+        let mut fills = world.query::<(&mut Style, &SliderFill)>();
+        for (mut style, fill) in fills.iter_mut(world) {
+            let width = Val::Percent(100. * values[fill.index].1.unwrap_or_default());
+            if style.width != width {
+                style.width = width;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        input::mouse::{MouseScrollUnit, MouseWheel},
+        utils::HashSet,
+    };
+    use bevy_panorbit_camera::PanOrbitCamera;
+
+    use super::*;
+    use crate::testing::*;
+    use crate::{AUTOPLAY, SHOW_COORD, SHOW_FUN, SHOW_INC};
+
+    #[test]
+    fn more_and_less_buttons_change_n() {
+        let mut app = test_app();
+        press(&mut app, "n", ControlInput::Increase);
+        assert_eq!(config(&mut app).n, 2);
+        assert_eq!(n_label(&mut app), "n: 2");
+        assert_eq!(cubes(&mut app, 2), (12, 0));
+
+        for _ in 0..10 {
+            press(&mut app, "n", ControlInput::Increase);
+        }
+        assert_eq!(config(&mut app).n, 6);
+        assert_eq!(n_label(&mut app), "n: 6");
+
+        for _ in 0..10 {
+            press(&mut app, "n", ControlInput::Decrease);
+        }
+        assert_eq!(config(&mut app).n, 0);
+        assert_eq!(n_label(&mut app), "n: 0");
+        assert_eq!(cubes(&mut app, 1), (0, 3));
+    }
+
+    #[test]
+    fn toggle_buttons_update_config_and_labels() {
+        let mut app = test_app();
+
+        let button = press(&mut app, "function", ControlInput::Press);
+        assert!(!config(&mut app).show_function);
+        assert_eq!(label(&mut app, button), SHOW_FUN);
+        assert_eq!(plane_visibility(&mut app), [Visibility::Hidden]);
+        press(&mut app, "function", ControlInput::Press);
+        assert_eq!(label(&mut app, button), format!("{SHOW_FUN} :)"));
+
+        let button = press(&mut app, "incremental", ControlInput::Press);
+        assert!(!config(&mut app).show_incremental_cubes);
+        assert_eq!(label(&mut app, button), SHOW_INC);
+
+        let button = press(&mut app, "grid", ControlInput::Press);
+        assert!(!config(&mut app).show_full_grid);
+        assert_eq!(label(&mut app, button), SHOW_COORD);
+    }
+
+    #[test]
+    fn button_colors_follow_interaction() {
+        let mut app = test_app();
+        let button = button(&mut app, "n", ControlInput::Increase);
+
+        *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Hovered;
+        app.update();
+        assert_eq!(
+            app.world.get::<BackgroundColor>(button).unwrap().0,
+            HOVERED_BUTTON
+        );
+        assert_eq!(
+            app.world.get::<BorderColor>(button).unwrap().0,
+            Color::WHITE
+        );
+
+        *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+        app.update();
+        assert_eq!(app.world.get::<BorderColor>(button).unwrap().0, Color::RED);
+
+        *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::None;
+        app.update();
+        assert_eq!(
+            app.world.get::<BackgroundColor>(button).unwrap().0,
+            NORMAL_BUTTON
+        );
+        assert_eq!(
+            app.world.get::<BorderColor>(button).unwrap().0,
+            Color::BLACK
+        );
+    }
+
+    #[derive(Resource, Default)]
+    struct Downstream {
+        choice: usize,
+        speed: f32,
+        triggered: u32,
+    }
+
+    fn downstream_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Downstream>()
+            .add_event::<ControlEvent>()
+            .register_control(Control::new(
+                "choice",
+                "Farbe",
+                ControlKind::Choice {
+                    binding: Binding::resource::<Downstream>(|d| d.choice, |d, v| d.choice = v),
+                    options: vec!["rot", "gelb", "blau"],
+                },
+            ))
+            .register_control(Control::new(
+                "speed",
+                "Tempo",
+                ControlKind::Slider {
+                    binding: Binding::resource::<Downstream>(|d| d.speed, |d, v| d.speed = v),
+                    min: 1.,
+                    max: 3.,
+                },
+            ))
+            .register_control(Control::new(
+                "trigger",
+                "Los",
+                ControlKind::Trigger(Box::new(|world| {
+                    world.resource_mut::<Downstream>().triggered += 1
+                })),
+            ))
+            .add_systems(Startup, setup_panel)
+            .add_systems(Update, button_system)
+            .add_systems(Update, apply_controls.after(button_system))
+            .add_systems(Update, update_labels.after(apply_controls));
+        app.update();
+        app
+    }
+
+    #[test]
+    fn panel_is_generated_from_the_registry() {
+        let mut app = test_app();
+        let keys: Vec<_> = app
+            .world
+            .resource::<ControlRegistry>()
+            .iter()
+            .map(|control| control.key)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "n",
+                "function",
+                "incremental",
+                "grid",
+                "party",
+                "autoplay",
+                "autoplay_seconds",
+                "rule",
+                "compare",
+                "sync_cameras",
+                "compare_n",
+                "compare_rule",
+                "color_mode",
+                "palette",
+                "slice",
+                "slice_axis",
+                "slice_position",
+                "fubini",
+                "fubini_order",
+                "view",
+                "bookmark",
+                "next_bookmark",
+                "save_scene",
+                "export"
+            ]
+        );
+        // steppers have two buttons
+        let mut query = app.world.query::<&ControlButton>();
+        assert_eq!(query.iter(&app.world).count(), keys.len() + 2);
+        assert_eq!(control_label(&mut app, 5), AUTOPLAY);
+        assert_eq!(control_label(&mut app, 6), "Sekunden: 1.5");
+    }
+
+    #[test]
+    fn downstream_controls() {
+        let mut app = downstream_app();
+        assert_eq!(control_label(&mut app, 0), "Farbe: rot");
+        assert_eq!(control_label(&mut app, 1), "Tempo: 0.0");
+
+        press(&mut app, "choice", ControlInput::Press);
+        assert_eq!(app.world.resource::<Downstream>().choice, 1);
+        assert_eq!(control_label(&mut app, 0), "Farbe: gelb");
+        press(&mut app, "choice", ControlInput::Press);
+        press(&mut app, "choice", ControlInput::Press);
+        assert_eq!(control_label(&mut app, 0), "Farbe: rot");
+
+        press(&mut app, "trigger", ControlInput::Press);
+        assert_eq!(app.world.resource::<Downstream>().triggered, 1);
+        // the event is applied once and is still there for other readers
+        let events = app.world.resource::<Events<ControlEvent>>();
+        let mut reader = events.get_reader();
+        assert_eq!(
+            reader
+                .read(events)
+                .filter(|event| event.key == "trigger")
+                .count(),
+            1
+        );
+        app.update();
+        assert_eq!(app.world.resource::<Downstream>().triggered, 1);
+
+        app.world.send_event(ControlEvent {
+            key: "speed",
+            input: ControlInput::Set(0.75),
+        });
+        app.update();
+        assert_eq!(app.world.resource::<Downstream>().speed, 2.5);
+        assert_eq!(control_label(&mut app, 1), "Tempo: 2.5");
+        let mut query = app.world.query::<&Style>();
+        let fill = app
+            .world
+            .query_filtered::<Entity, With<SliderFill>>()
+            .single(&app.world);
+        assert_eq!(
+            query.get(&app.world, fill).unwrap().width,
+            Val::Percent(75.)
+        );
+    }
+
+    /// The control buttons that lie completely inside the `720` pixels high window.
+    fn buttons_in_window(app: &mut App) -> HashSet<Entity> {
+        let mut query = app
+            .world
+            .query_filtered::<(Entity, &Node, &GlobalTransform), With<ControlButton>>();
+        query
+            .iter(&app.world)
+            .filter(|(_, node, transform)| {
+                let rect = node.logical_rect(transform);
+                rect.min.y >= 0. && rect.max.y <= 720.
+            })
+            .map(|(entity, ..)| entity)
+            .collect()
+    }
+
+    fn scroll(app: &mut App, lines: f32) {
+        app.world.send_event(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: 0.,
+            y: lines,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+    }
+
+    #[test]
+    fn every_control_can_be_scrolled_into_the_window() {
+        let (mut app, camera) = touch_app();
+        app.update();
+        let buttons = app
+            .world
+            .query_filtered::<(), With<ControlButton>>()
+            .iter(&app.world)
+            .count();
+        let mut seen = buttons_in_window(&mut app);
+        assert!(seen.len() < buttons, "all {buttons} buttons fit");
+
+        // the wheel doesn't scroll the panel from the scene
+        let first = seen.clone();
+        app.world
+            .query::<&mut Window>()
+            .single_mut(&mut app.world)
+            .set_cursor_position(Some(Vec2::new(800., 360.)));
+        scroll(&mut app, -1.);
+        assert_eq!(buttons_in_window(&mut app), first);
+        assert!(app.world.get::<PanOrbitCamera>(camera).unwrap().enabled);
+
+        app.world
+            .query::<&mut Window>()
+            .single_mut(&mut app.world)
+            .set_cursor_position(Some(Vec2::new(100., 360.)));
+        for _ in 0..100 {
+            scroll(&mut app, -1.);
+            seen.extend(buttons_in_window(&mut app));
+        }
+        assert_eq!(seen.len(), buttons);
+        // the cameras don't zoom meanwhile
+        assert!(!app.world.get::<PanOrbitCamera>(camera).unwrap().enabled);
+
+        // it stops at the top
+        scroll(&mut app, 100.);
+        app.update();
+        assert_eq!(buttons_in_window(&mut app), first);
+    }
+}
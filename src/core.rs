@@ -0,0 +1,485 @@
+//! The numerical part of the visualization, independent of Bevy.
+//!
+//! An [`Integrand`] is evaluated over a rectangular [`Domain`] that is split by a
+//! [`Partition`] into cells. Every cell is sampled at the point chosen by a [`Rule`],
+//! which gives its height and its contribution to the Riemann sum.
+
+pub type Float = f32;
+
+/// A function `f(x, y)` that can be integrated.
+pub trait Integrand {
+    fn eval(&self, x: Float, y: Float) -> Float;
+}
+
+impl<F: Fn(Float, Float) -> Float> Integrand for F {
+    fn eval(&self, x: Float, y: Float) -> Float {
+        self(x, y)
+    }
+}
+
+/// The rectangle `[min_x, max_x] x [min_y, max_y]` that is integrated over.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Domain {
+    pub min_x: Float,
+    pub max_x: Float,
+    pub min_y: Float,
+    pub max_y: Float,
+}
+
+impl Domain {
+    /// The unit square `[0, 1] x [0, 1]`.
+    pub const UNIT: Self = Self {
+        min_x: 0.,
+        max_x: 1.,
+        min_y: 0.,
+        max_y: 1.,
+    };
+
+    pub fn width(&self) -> Float {
+        self.max_x - self.min_x
+    }
+
+    pub fn depth(&self) -> Float {
+        self.max_y - self.min_y
+    }
+
+    pub fn area(&self) -> Float {
+        self.width() * self.depth()
+    }
+}
+
+impl Default for Domain {
+    fn default() -> Self {
+        Self::UNIT
+    }
+}
+
+/// A uniform grid of `nx * ny` cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Partition {
+    pub nx: u32,
+    pub ny: u32,
+}
+
+impl Partition {
+    pub fn uniform(n: u32) -> Self {
+        Self { nx: n, ny: n }
+    }
+
+    /// The partition of refinement level `level` with `2^level` cells per axis.
+    pub fn dyadic(level: u8) -> Self {
+        Self::uniform(2u32.pow(level.into()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.nx as usize * self.ny as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bounds `(min_x, max_x, min_y, max_y)` of cell `(i, j)`.
+    pub fn bounds(&self, domain: &Domain, i: u32, j: u32) -> (Float, Float, Float, Float) {
+        let dx = domain.width() / self.nx as Float;
+        let dy = domain.depth() / self.ny as Float;
+        let min_x = domain.min_x + dx * i as Float;
+        let min_y = domain.min_y + dy * j as Float;
+        (min_x, min_x + dx, min_y, min_y + dy)
+    }
+
+    /// Whether every cell of `self` lies inside exactly one cell of `coarse`.
+    pub fn refines(&self, coarse: &Self) -> bool {
+        self.nx.is_multiple_of(coarse.nx) && self.ny.is_multiple_of(coarse.ny)
+    }
+}
+
+/// Where in a cell the integrand is sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rule {
+    #[default]
+    LowerLeft,
+    LowerRight,
+    UpperLeft,
+    UpperRight,
+    Midpoint,
+}
+
+impl Rule {
+    pub const ALL: [Self; 5] = [
+        Self::LowerLeft,
+        Self::LowerRight,
+        Self::UpperLeft,
+        Self::UpperRight,
+        Self::Midpoint,
+    ];
+
+    pub fn sample_point(
+        &self,
+        (min_x, max_x, min_y, max_y): (Float, Float, Float, Float),
+    ) -> (Float, Float) {
+        match self {
+            Self::LowerLeft => (min_x, min_y),
+            Self::LowerRight => (max_x, min_y),
+            Self::UpperLeft => (min_x, max_y),
+            Self::UpperRight => (max_x, max_y),
+            Self::Midpoint => ((min_x + max_x) / 2., (min_y + max_y) / 2.),
+        }
+    }
+}
+
+/// One cell of a partition together with its sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub i: u32,
+    pub j: u32,
+    pub min_x: Float,
+    pub max_x: Float,
+    pub min_y: Float,
+    pub max_y: Float,
+    pub sample_x: Float,
+    pub sample_y: Float,
+    /// Height the cell starts at. This is `0` for a plain Riemann sum and the height
+    /// of the enclosing coarser cell for [`refine`].
+    pub base: Float,
+    /// The integrand at the sample point.
+    pub height: Float,
+    /// Contribution of this cell to the sum, `area * (height - base)`.
+    pub volume: Float,
+}
+
+impl Cell {
+    pub fn area(&self) -> Float {
+        (self.max_x - self.min_x) * (self.max_y - self.min_y)
+    }
+
+    pub fn domain(&self) -> Domain {
+        Domain {
+            min_x: self.min_x,
+            max_x: self.max_x,
+            min_y: self.min_y,
+            max_y: self.max_y,
+        }
+    }
+
+    /// How much the Riemann sum of this cell, `area * height`, misses the integral over it.
+    pub fn error(&self, integrand: &impl Integrand) -> Float {
+        self.area() * self.height - integrate_with(integrand, &self.domain(), 2)
+    }
+}
+
+/// The cells of a partition and the resulting Riemann sum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Approximation {
+    pub cells: Vec<Cell>,
+    pub sum: Float,
+}
+
+fn sample(
+    integrand: &impl Integrand,
+    domain: &Domain,
+    partition: &Partition,
+    rule: Rule,
+    i: u32,
+    j: u32,
+    base: Float,
+) -> Cell {
+    let bounds = partition.bounds(domain, i, j);
+    let (sample_x, sample_y) = rule.sample_point(bounds);
+    let height = integrand.eval(sample_x, sample_y);
+    let (min_x, max_x, min_y, max_y) = bounds;
+    let area = (max_x - min_x) * (max_y - min_y);
+    Cell {
+        i,
+        j,
+        min_x,
+        max_x,
+        min_y,
+        max_y,
+        sample_x,
+        sample_y,
+        base,
+        height,
+        volume: area * (height - base),
+    }
+}
+
+/// Samples every cell of `partition`, ordered by `i` and then `j`.
+pub fn cells(
+    integrand: &impl Integrand,
+    domain: &Domain,
+    partition: &Partition,
+    rule: Rule,
+) -> Vec<Cell> {
+    let mut cells = Vec::with_capacity(partition.len());
+    for i in 0..partition.nx {
+        for j in 0..partition.ny {
+            cells.push(sample(integrand, domain, partition, rule, i, j, 0.));
+        }
+    }
+    cells
+}
+
+/// Computes the Riemann sum of `integrand` over `domain`.
+pub fn riemann(
+    integrand: &impl Integrand,
+    domain: &Domain,
+    partition: &Partition,
+    rule: Rule,
+) -> Approximation {
+    let cells = cells(integrand, domain, partition, rule);
+    let sum = cells.iter().map(|cell| cell.volume).sum();
+    Approximation { cells, sum }
+}
+
+/// Samples `fine` and stacks every cell on top of the enclosing cell of `coarse`.
+///
+/// The volumes are the difference between the two Riemann sums, so the sum over
+/// `riemann(coarse)` and all refinements up to `fine` equals `riemann(fine)`.
+///
+/// # Panics
+/// If `fine` does not [refine](Partition::refines) `coarse`.
+pub fn refine(
+    integrand: &impl Integrand,
+    domain: &Domain,
+    coarse: &Partition,
+    fine: &Partition,
+    rule: Rule,
+) -> Vec<Cell> {
+    assert!(
+        fine.refines(coarse),
+        "{fine:?} is not a refinement of {coarse:?}"
+    );
+    let per_x = fine.nx / coarse.nx;
+    let per_y = fine.ny / coarse.ny;
+    let mut cells = Vec::with_capacity(fine.len());
+    for i in 0..fine.nx {
+        for j in 0..fine.ny {
+            let bounds = coarse.bounds(domain, i / per_x, j / per_y);
+            let (x, y) = rule.sample_point(bounds);
+            let base = integrand.eval(x, y);
+            cells.push(sample(integrand, domain, fine, rule, i, j, base));
+        }
+    }
+    cells
+}
+
+/// The cells drawn for refinement level `n`, stacked on `prev_n` (`0` means the ground).
+pub fn level_cells(
+    integrand: &impl Integrand,
+    domain: &Domain,
+    n: u8,
+    prev_n: u8,
+    rule: Rule,
+) -> Vec<Cell> {
+    if prev_n == 0 {
+        cells(integrand, domain, &Partition::dyadic(n), rule)
+    } else {
+        refine(
+            integrand,
+            domain,
+            &Partition::dyadic(prev_n),
+            &Partition::dyadic(n),
+            rule,
+        )
+    }
+}
+
+/// Nodes and weights of the 3-point Gauss-Legendre rule on `[-1, 1]`.
+const NODES: [(Float, Float); 3] = [
+    (-0.774_596_7, 5. / 9.),
+    (0., 8. / 9.),
+    (0.774_596_7, 5. / 9.),
+];
+
+/// The exact integral of `integrand` over `domain`, up to quadrature error.
+///
+/// Uses the 3-point Gauss-Legendre rule on a 16 × 16 grid, which is exact for polynomials
+/// of degree up to 5 in each variable.
+pub fn integrate(integrand: &impl Integrand, domain: &Domain) -> Float {
+    integrate_with(integrand, domain, 16)
+}
+
+/// [`integrate`] on a `resolution × resolution` grid.
+pub fn integrate_with(integrand: &impl Integrand, domain: &Domain, resolution: u32) -> Float {
+    let partition = Partition::uniform(resolution);
+    let mut sum = 0.;
+    for i in 0..partition.nx {
+        for j in 0..partition.ny {
+            let (min_x, max_x, min_y, max_y) = partition.bounds(domain, i, j);
+            let (mid_x, half_x) = ((min_x + max_x) / 2., (max_x - min_x) / 2.);
+            let (mid_y, half_y) = ((min_y + max_y) / 2., (max_y - min_y) / 2.);
+            for (u, wu) in NODES {
+                for (v, wv) in NODES {
+                    let value = integrand.eval(mid_x + u * half_x, mid_y + v * half_y);
+                    sum += wu * wv * half_x * half_y * value;
+                }
+            }
+        }
+    }
+    sum
+}
+
+/// The coordinate a slice through the domain is fixed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Axis {
+    /// The slice `x = c`, running along `y`.
+    #[default]
+    X,
+    /// The slice `y = c`, running along `x`.
+    Y,
+}
+
+impl Axis {
+    pub const ALL: [Self; 2] = [Self::X, Self::Y];
+
+    /// Range of the fixed coordinate.
+    pub fn range(self, domain: &Domain) -> (Float, Float) {
+        match self {
+            Self::X => (domain.min_x, domain.max_x),
+            Self::Y => (domain.min_y, domain.max_y),
+        }
+    }
+
+    /// Range of the coordinate along the slice.
+    pub fn along(self, domain: &Domain) -> (Float, Float) {
+        self.other().range(domain)
+    }
+
+    /// Index of the column of cells of `partition` the slice `self = c` goes through.
+    ///
+    /// On a border between two columns it is the one after it, at the end of the domain
+    /// the last one.
+    pub fn column(self, domain: &Domain, partition: &Partition, c: Float) -> u32 {
+        let (min, max) = self.range(domain);
+        let columns = match self {
+            Self::X => partition.nx,
+            Self::Y => partition.ny,
+        };
+        let column = ((c - min) / (max - min) * columns as Float).floor().max(0.) as u32;
+        column.min(columns.saturating_sub(1))
+    }
+
+    pub fn other(self) -> Self {
+        match self {
+            Self::X => Self::Y,
+            Self::Y => Self::X,
+        }
+    }
+
+    /// The point at `t` along the slice `self = c`.
+    pub fn point(self, c: Float, t: Float) -> (Float, Float) {
+        match self {
+            Self::X => (c, t),
+            Self::Y => (t, c),
+        }
+    }
+}
+
+/// One step of the profile of a slice: the cell from `min` to `max` along the slice
+/// with the height it is drawn at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub min: Float,
+    pub max: Float,
+    pub height: Float,
+}
+
+/// The cross-section of the cubes of a Riemann sum at `axis = c`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slice {
+    pub axis: Axis,
+    pub c: Float,
+    pub steps: Vec<Step>,
+    /// The 1D Riemann sum of the profile, the area below the steps.
+    pub sum: Float,
+}
+
+/// Cuts the cells of the Riemann sum over `partition` at `axis = c`.
+///
+/// The cut goes through the [column](Axis::column) of cells containing `c`. Summing
+/// `slice(c).sum` over the columns, times their width, gives the 2D Riemann sum, which
+/// is Fubini's theorem for step functions.
+pub fn slice(
+    integrand: &impl Integrand,
+    domain: &Domain,
+    partition: &Partition,
+    rule: Rule,
+    axis: Axis,
+    c: Float,
+) -> Slice {
+    let column = axis.column(domain, partition, c);
+    let rows = match axis {
+        Axis::X => partition.ny,
+        Axis::Y => partition.nx,
+    };
+    let steps: Vec<_> = (0..rows)
+        .map(|row| {
+            let (i, j) = match axis {
+                Axis::X => (column, row),
+                Axis::Y => (row, column),
+            };
+            let cell = sample(integrand, domain, partition, rule, i, j, 0.);
+            let (min, max) = match axis {
+                Axis::X => (cell.min_y, cell.max_y),
+                Axis::Y => (cell.min_x, cell.max_x),
+            };
+            Step {
+                min,
+                max,
+                height: cell.height,
+            }
+        })
+        .collect();
+    let sum = steps
+        .iter()
+        .map(|step| (step.max - step.min) * step.height)
+        .sum();
+    Slice {
+        axis,
+        c,
+        steps,
+        sum,
+    }
+}
+
+/// The slices through the middle of every column of `partition` along `axis`, in order.
+///
+/// Times the column width, their sums are the inner integrals of the Riemann sum written as
+/// an iterated integral, and add up to it.
+pub fn slabs(
+    integrand: &impl Integrand,
+    domain: &Domain,
+    partition: &Partition,
+    rule: Rule,
+    axis: Axis,
+) -> Vec<Slice> {
+    let (min, max) = axis.range(domain);
+    let columns = match axis {
+        Axis::X => partition.nx,
+        Axis::Y => partition.ny,
+    };
+    let width = (max - min) / columns as Float;
+    (0..columns)
+        .map(|k| {
+            let c = min + width * (k as Float + 0.5);
+            slice(integrand, domain, partition, rule, axis, c)
+        })
+        .collect()
+}
+
+/// The exact integral of `integrand` along the slice `axis = c`, up to quadrature error.
+pub fn integrate_slice(integrand: &impl Integrand, domain: &Domain, axis: Axis, c: Float) -> Float {
+    let (min, max) = axis.along(domain);
+    let segments = 64;
+    let half = (max - min) / segments as Float / 2.;
+    let mut sum = 0.;
+    for k in 0..segments {
+        let mid = min + half * (2 * k + 1) as Float;
+        for (u, w) in NODES {
+            let (x, y) = axis.point(c, mid + u * half);
+            sum += w * half * integrand.eval(x, y);
+        }
+    }
+    sum
+}
@@ -0,0 +1,429 @@
+//! Data export of the currently displayed partition.
+//!
+//! Everything in here works without a Bevy `World`, so the same data can be produced
+//! from other tools or tests with [`Export::new`].
+
+use std::fmt::Write;
+
+use crate::{
+    core::{self, Cell, Domain, Integrand, Rule},
+    f, is_drawn, Float, Side,
+};
+
+/// One cell of the partition as it is drawn by `add_cubes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellRecord {
+    /// Refinement level, the cell has a side length of `2^-level`.
+    pub level: u8,
+    /// Level the cell is stacked on top of, `0` if it starts at the ground.
+    pub prev_level: u8,
+    pub i: u32,
+    pub j: u32,
+    pub min_x: Float,
+    pub max_x: Float,
+    pub min_y: Float,
+    pub max_y: Float,
+    pub sample_x: Float,
+    pub sample_y: Float,
+    /// Height the cell starts at, which is the height of the cell below it.
+    pub base: Float,
+    /// `f` at the sample point.
+    pub height: Float,
+    /// Contribution of this cell to the sum, `area * (height - base)`.
+    pub volume: Float,
+}
+
+/// Totals of one refinement level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelSummary {
+    pub level: u8,
+    pub cells: usize,
+    pub volume: Float,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub n: u8,
+    pub incremental: bool,
+    pub cells: usize,
+    pub volume: Float,
+    pub levels: Vec<LevelSummary>,
+}
+
+/// All cells of a setting together with their totals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Export {
+    pub cells: Vec<CellRecord>,
+    pub summary: Summary,
+}
+
+impl CellRecord {
+    fn new(level: u8, prev_level: u8, cell: Cell) -> Self {
+        Self {
+            level,
+            prev_level,
+            i: cell.i,
+            j: cell.j,
+            min_x: cell.min_x,
+            max_x: cell.max_x,
+            min_y: cell.min_y,
+            max_y: cell.max_y,
+            sample_x: cell.sample_x,
+            sample_y: cell.sample_y,
+            base: cell.base,
+            height: cell.height,
+            volume: cell.volume,
+        }
+    }
+}
+
+impl Export {
+    /// Collects the cells drawn for `side`.
+    pub fn new(side: &Side) -> Self {
+        Self::compute(
+            &f,
+            &Domain::UNIT,
+            side.rule,
+            side.n,
+            side.show_incremental_cubes,
+        )
+    }
+
+    /// Like [`Export::new`], but for any integrand, domain and rule.
+    pub fn compute(
+        integrand: &impl Integrand,
+        domain: &Domain,
+        rule: Rule,
+        n: u8,
+        incremental: bool,
+    ) -> Self {
+        let level = |n: u8, prev_n: u8| {
+            core::level_cells(integrand, domain, n, prev_n, rule)
+                .into_iter()
+                .filter(move |cell| is_drawn(cell, prev_n))
+                .map(move |cell| CellRecord::new(n, prev_n, cell))
+        };
+        let cells: Vec<CellRecord> = if incremental {
+            (1..=n).flat_map(|n| level(n, n - 1)).collect()
+        } else {
+            level(n, 0).collect()
+        };
+
+        let mut levels: Vec<LevelSummary> = Vec::new();
+        for cell in &cells {
+            match levels.last_mut() {
+                Some(level) if level.level == cell.level => {
+                    level.cells += 1;
+                    level.volume += cell.volume;
+                }
+                _ => levels.push(LevelSummary {
+                    level: cell.level,
+                    cells: 1,
+                    volume: cell.volume,
+                }),
+            }
+        }
+
+        let summary = Summary {
+            n,
+            incremental,
+            cells: cells.len(),
+            volume: levels.iter().map(|level| level.volume).sum(),
+            levels,
+        };
+        Self { cells, summary }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "level,prev_level,i,j,min_x,max_x,min_y,max_y,sample_x,sample_y,base,height,volume\n",
+        );
+        for c in &self.cells {
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                c.level,
+                c.prev_level,
+                c.i,
+                c.j,
+                c.min_x,
+                c.max_x,
+                c.min_y,
+                c.max_y,
+                c.sample_x,
+                c.sample_y,
+                c.base,
+                c.height,
+                c.volume
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// Writes the summary as CSV, one row per level and a final `total` row.
+    pub fn summary_csv(&self) -> String {
+        let mut out = String::from("level,cells,volume\n");
+        for level in &self.summary.levels {
+            writeln!(out, "{},{},{}", level.level, level.cells, level.volume).unwrap();
+        }
+        writeln!(out, "total,{},{}", self.summary.cells, self.summary.volume).unwrap();
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let s = &self.summary;
+        let mut out = String::from("{\n  \"summary\": {\n");
+        writeln!(out, "    \"n\": {},", s.n).unwrap();
+        writeln!(out, "    \"incremental\": {},", s.incremental).unwrap();
+        writeln!(out, "    \"cells\": {},", s.cells).unwrap();
+        writeln!(out, "    \"volume\": {},", json(s.volume)).unwrap();
+        out.push_str("    \"levels\": [");
+        for (idx, level) in s.levels.iter().enumerate() {
+            if idx > 0 {
+                out.push(',');
+            }
+            write!(
+                out,
+                "\n      {{\"level\": {}, \"cells\": {}, \"volume\": {}}}",
+                level.level,
+                level.cells,
+                json(level.volume)
+            )
+            .unwrap();
+        }
+        out.push_str("\n    ]\n  },\n  \"cells\": [");
+        for (idx, c) in self.cells.iter().enumerate() {
+            if idx > 0 {
+                out.push(',');
+            }
+            write!(
+                out,
+                "\n    {{\"level\": {}, \"prev_level\": {}, \"i\": {}, \"j\": {}, \
+                 \"min_x\": {}, \"max_x\": {}, \"min_y\": {}, \"max_y\": {}, \
+                 \"sample_x\": {}, \"sample_y\": {}, \"base\": {}, \"height\": {}, \"volume\": {}}}",
+                c.level,
+                c.prev_level,
+                c.i,
+                c.j,
+                json(c.min_x),
+                json(c.max_x),
+                json(c.min_y),
+                json(c.max_y),
+                json(c.sample_x),
+                json(c.sample_y),
+                json(c.base),
+                json(c.height),
+                json(c.volume)
+            )
+            .unwrap();
+        }
+        out.push_str("\n  ]\n}\n");
+        out
+    }
+
+    /// Writes `<stem>.csv`, `<stem>_summary.csv` and `<stem>.json`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn write_files(&self, stem: &std::path::Path) -> std::io::Result<()> {
+        std::fs::write(stem.with_extension("csv"), self.to_csv())?;
+        let mut summary = stem.as_os_str().to_owned();
+        summary.push("_summary.csv");
+        std::fs::write(summary, self.summary_csv())?;
+        std::fs::write(stem.with_extension("json"), self.to_json())
+    }
+}
+
+/// JSON has no infinities or NaN, those become `null`.
+fn json(value: Float) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The right half of the bottom row at `n = 1`, with made up heights.
+    fn one_cell() -> Export {
+        Export {
+            cells: vec![CellRecord {
+                level: 1,
+                prev_level: 0,
+                i: 1,
+                j: 0,
+                min_x: 0.5,
+                max_x: 1.,
+                min_y: 0.,
+                max_y: 0.5,
+                sample_x: 0.5,
+                sample_y: 0.,
+                base: 0.,
+                height: 2.,
+                volume: 0.5,
+            }],
+            summary: Summary {
+                n: 1,
+                incremental: false,
+                cells: 1,
+                volume: 0.5,
+                levels: vec![LevelSummary {
+                    level: 1,
+                    cells: 1,
+                    volume: 0.5,
+                }],
+            },
+        }
+    }
+
+    fn side(rule: Rule, n: u8, show_incremental_cubes: bool) -> Side {
+        Side {
+            n,
+            rule,
+            show_incremental_cubes,
+        }
+    }
+
+    fn parse_cell(row: &str) -> CellRecord {
+        let fields: Vec<&str> = row.split(',').collect();
+        assert_eq!(fields.len(), 13, "{row}");
+        let float = |idx: usize| fields[idx].parse::<Float>().unwrap();
+        CellRecord {
+            level: fields[0].parse().unwrap(),
+            prev_level: fields[1].parse().unwrap(),
+            i: fields[2].parse().unwrap(),
+            j: fields[3].parse().unwrap(),
+            min_x: float(4),
+            max_x: float(5),
+            min_y: float(6),
+            max_y: float(7),
+            sample_x: float(8),
+            sample_y: float(9),
+            base: float(10),
+            height: float(11),
+            volume: float(12),
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let export = one_cell();
+        assert_eq!(
+            export.to_csv(),
+            "level,prev_level,i,j,min_x,max_x,min_y,max_y,sample_x,sample_y,base,height,volume\n\
+             1,0,1,0,0.5,1,0,0.5,0.5,0,0,2,0.5\n"
+        );
+        assert_eq!(
+            export.summary_csv(),
+            "level,cells,volume\n1,1,0.5\ntotal,1,0.5\n"
+        );
+        assert_eq!(
+            export.to_json(),
+            r#"{
+  "summary": {
+    "n": 1,
+    "incremental": false,
+    "cells": 1,
+    "volume": 0.5,
+    "levels": [
+      {"level": 1, "cells": 1, "volume": 0.5}
+    ]
+  },
+  "cells": [
+    {"level": 1, "prev_level": 0, "i": 1, "j": 0, "min_x": 0.5, "max_x": 1, "min_y": 0, "max_y": 0.5, "sample_x": 0.5, "sample_y": 0, "base": 0, "height": 2, "volume": 0.5}
+  ]
+}
+"#
+        );
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let export = Export::new(&side(Rule::LowerLeft, 3, true));
+        let csv = export.to_csv();
+        let cells: Vec<CellRecord> = csv.lines().skip(1).map(parse_cell).collect();
+        assert_eq!(cells, export.cells);
+
+        let summary = export.summary_csv();
+        let mut rows: Vec<Vec<&str>> = summary
+            .lines()
+            .skip(1)
+            .map(|row| row.split(',').collect())
+            .collect();
+        let total = rows.pop().unwrap();
+        let levels: Vec<LevelSummary> = rows
+            .iter()
+            .map(|row| LevelSummary {
+                level: row[0].parse().unwrap(),
+                cells: row[1].parse().unwrap(),
+                volume: row[2].parse().unwrap(),
+            })
+            .collect();
+        assert_eq!(levels, export.summary.levels);
+        assert_eq!(
+            total,
+            [
+                "total",
+                &export.summary.cells.to_string(),
+                &export.summary.volume.to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn json_lists_every_cell_and_level() {
+        let export = Export::new(&side(Rule::LowerLeft, 2, true));
+        let json = export.to_json();
+        assert!(json.starts_with("{\n  \"summary\": {\n    \"n\": 2,\n    \"incremental\": true,"));
+        assert_eq!(json.matches("\"prev_level\"").count(), export.cells.len());
+        // the levels and the cells both have a `level`
+        assert_eq!(
+            json.matches("\"level\"").count(),
+            export.cells.len() + export.summary.levels.len()
+        );
+        assert!(json.contains(&format!("\"volume\": {},\n", export.summary.volume)));
+    }
+
+    #[test]
+    fn json_writes_null_for_non_finite_values() {
+        // `1 / x` is infinite in the lower left corner
+        let export = Export::compute(
+            &|x: Float, _: Float| 1. / x,
+            &Domain::UNIT,
+            Rule::LowerLeft,
+            1,
+            false,
+        );
+        assert!(export.cells[0].height.is_infinite());
+        let json = export.to_json();
+        assert!(
+            json.contains("\"height\": null, \"volume\": null}"),
+            "{json}"
+        );
+        assert!(json.contains("    \"volume\": null,\n"), "{json}");
+        assert!(!json.contains("inf") && !json.contains("NaN"), "{json}");
+    }
+
+    #[test]
+    fn only_drawn_cells_are_exported() {
+        // f vanishes in the lower left corner, and every refinement has one cell that
+        // samples the same corner as the cell below it
+        let export = Export::new(&side(Rule::LowerLeft, 2, true));
+        assert_eq!(export.cells.len(), 3 + 12);
+        assert!(export
+            .cells
+            .iter()
+            .all(|cell| cell.height != cell.base && cell.volume != 0.));
+
+        // a single cube for the whole square, if it has a height
+        assert!(Export::new(&side(Rule::LowerLeft, 0, false))
+            .cells
+            .is_empty());
+        let midpoint = Export::new(&side(Rule::Midpoint, 0, false));
+        assert_eq!(midpoint.cells.len(), 1);
+        assert_eq!(midpoint.summary.volume, 1.);
+        assert!(Export::new(&side(Rule::Midpoint, 0, true)).cells.is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+//! Builds the Riemann sum as an iterated integral: a sweep across the outer axis stacks
+//! one slab per column, every slab grows along the inner axis while its inner sum
+//! accumulates.
+//!
+//! The slabs are the cells of the primary side, which hides its cubes meanwhile.
+
+use bevy::prelude::*;
+
+use crate::{
+    cell_mesh,
+    core::{self, Axis, Cell, Domain, Partition, Rule, Slice},
+    f, Config, Float, Side, View,
+};
+
+/// Which integral is the inner one.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    /// Integrate over `y` first, the sweep goes across `x`.
+    #[default]
+    DyDx,
+    /// Integrate over `x` first, the sweep goes across `y`.
+    DxDy,
+}
+
+impl Order {
+    pub const ALL: [Self; 2] = [Self::DyDx, Self::DxDy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DyDx => "dy dx",
+            Self::DxDy => "dx dy",
+        }
+    }
+
+    /// The outer axis, the one the sweep moves along.
+    pub fn sweep(self) -> Axis {
+        match self {
+            Self::DyDx => Axis::X,
+            Self::DxDy => Axis::Y,
+        }
+    }
+}
+
+/// State of the running animation.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct Sweep {
+    pub order: Order,
+    /// The slab of every column, in the order they are stacked.
+    pub slabs: Vec<Slice>,
+    /// Width of a column along the outer axis.
+    pub width: Float,
+    /// Slabs built so far; the fraction is how far the current one has grown.
+    pub progress: Float,
+}
+
+impl Sweep {
+    /// Index of the growing slab and the number of its cells that are shown.
+    pub fn current(&self) -> Option<(usize, usize)> {
+        let column = self.progress.floor() as usize;
+        let slab = self.slabs.get(column)?;
+        let rows = ((self.progress - column as Float) * slab.steps.len() as Float) as usize;
+        Some((column, rows))
+    }
+
+    /// The part of the inner sum of the growing slab that is built.
+    pub fn inner(&self) -> Float {
+        self.current().map_or(0., |(column, rows)| {
+            self.slabs[column].steps[..rows]
+                .iter()
+                .map(|step| (step.max - step.min) * step.height)
+                .sum()
+        })
+    }
+
+    /// The outer sum so far: all finished slabs and the growing one.
+    pub fn partial(&self) -> Float {
+        let done = self.progress.floor() as usize;
+        let finished: Float = self.slabs.iter().take(done).map(|slab| slab.sum).sum();
+        self.width * (finished + self.inner())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.progress >= self.slabs.len() as Float
+    }
+}
+
+/// One cell of a slab.
+#[derive(Component)]
+pub(crate) struct Slab {
+    pub(crate) column: usize,
+    row: usize,
+}
+
+/// Runs the animation while [`Config::fubini`] is set and restarts it whenever the primary
+/// side or the order changes.
+pub(crate) fn sweep(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<Config>,
+    sides: Query<(&View, &Side)>,
+    mut sweep: ResMut<Sweep>,
+    mut slabs: Query<(Entity, &Slab, &mut Visibility)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut running: Local<Option<(u8, Rule, Order)>>,
+) {
+    let side = sides
+        .iter()
+        .find(|(view, _)| **view == View::Primary)
+        .map_or(Side::from(&*config), |(_, side)| *side);
+    let key = config
+        .fubini
+        .then_some((side.n, side.rule, config.fubini_order));
+    if *running != key {
+        for (entity, _, _) in &slabs {
+            commands.entity(entity).despawn_recursive();
+        }
+        *running = key;
+        *sweep = Sweep::default();
+        if let Some((n, rule, order)) = key {
+            start(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                &config,
+                &mut sweep,
+                n,
+                rule,
+                order,
+            );
+        }
+        return;
+    }
+    if key.is_none() || sweep.is_done() {
+        return;
+    }
+    let columns = sweep.slabs.len() as Float;
+    sweep.progress =
+        (sweep.progress + time.delta_seconds() * columns / config.fubini_seconds).min(columns);
+    let (column, rows) = sweep.current().unwrap_or((usize::MAX, 0));
+    for (_, slab, mut vis) in &mut slabs {
+        let shown = slab.column < column || slab.column == column && slab.row < rows;
+        vis.set_if_neq(if shown {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+fn start(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    config: &Config,
+    sweep: &mut Sweep,
+    n: u8,
+    rule: Rule,
+    order: Order,
+) {
+    let axis = order.sweep();
+    let partition = Partition::dyadic(n);
+    let slabs = core::slabs(&f, &Domain::UNIT, &partition, rule, axis);
+    let (min, max) = axis.range(&Domain::UNIT);
+    *sweep = Sweep {
+        order,
+        width: (max - min) / slabs.len() as Float,
+        slabs,
+        progress: 0.,
+    };
+    // the same cells `add_cubes` shows for level `n` on the ground
+    for cell in core::level_cells(&f, &Domain::UNIT, n, 0, rule) {
+        let (column, row) = match axis {
+            Axis::X => (cell.i, cell.j),
+            Axis::Y => (cell.j, cell.i),
+        };
+        let t = column as Float / (sweep.slabs.len() - 1).max(1) as Float;
+        commands.spawn((
+            slab_bundle(meshes, materials, &cell, config.palette.sample(t)),
+            Slab {
+                column: column as usize,
+                row: row as usize,
+            },
+            View::Primary.layers(),
+        ));
+    }
+}
+
+fn slab_bundle(
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    cell: &Cell,
+    color: Color,
+) -> PbrBundle {
+    let (mesh, transform) = cell_mesh(cell);
+    PbrBundle {
+        mesh: meshes.add(mesh),
+        material: materials.add(color.into()),
+        transform,
+        visibility: Visibility::Hidden,
+        ..default()
+    }
+}
+
+/// Marks where the sweep is.
+pub(crate) fn sweep_line(mut gizmos: Gizmos, config: Res<Config>, sweep: Res<Sweep>) {
+    if !config.fubini || sweep.slabs.is_empty() {
+        return;
+    }
+    let axis = sweep.order.sweep();
+    let (min, _) = axis.range(&Domain::UNIT);
+    let (low, high) = axis.along(&Domain::UNIT);
+    let c = min + sweep.width * sweep.progress;
+    let point = |t: Float, h: Float| {
+        let (x, y) = axis.point(c, t);
+        Vec3::new(x, h, y)
+    };
+    let top = sweep
+        .slabs
+        .iter()
+        .flat_map(|slab| slab.steps.iter().map(|step| step.height))
+        .fold(0., Float::max);
+    gizmos.linestrip(
+        [
+            point(low, 0.),
+            point(low, top),
+            point(high, top),
+            point(high, 0.),
+            point(low, 0.),
+        ],
+        Color::WHITE,
+    );
+}
+
+#[derive(Component)]
+pub(crate) struct FubiniText;
+
+pub(crate) fn setup_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(80.0),
+            left: Val::Percent(25.0),
+            display: Display::None,
+            ..default()
+        }),
+        FubiniText,
+    ));
+}
+
+/// Shows the inner sum of the growing slab and the outer sum so far.
+pub(crate) fn hud(
+    config: Res<Config>,
+    sweep: Res<Sweep>,
+    mut texts: Query<(&mut Text, &mut Style), With<FubiniText>>,
+) {
+    if !config.is_changed() && !sweep.is_changed() {
+        return;
+    }
+    for (mut text, mut style) in &mut texts {
+        let display = if config.fubini {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if style.display != display {
+            style.display = display;
+        }
+        if !config.fubini || sweep.slabs.is_empty() {
+            continue;
+        }
+        let (outer, inner) = match sweep.order.sweep() {
+            Axis::X => ("x", "y"),
+            Axis::Y => ("y", "x"),
+        };
+        let mut value = format!("Fubini, d{inner} d{outer}\n");
+        match sweep.current() {
+            Some((column, _)) => {
+                let (min, _) = sweep.order.sweep().range(&Domain::UNIT);
+                value.push_str(&format!(
+                    "Streifen {} von {} bei {outer} = {:.3}\nSumme entlang {inner}: {:.5}\n",
+                    column + 1,
+                    sweep.slabs.len(),
+                    min + sweep.width * (column as Float + 0.5),
+                    sweep.inner(),
+                ));
+            }
+            None => value.push_str(&format!("alle {} Streifen\n", sweep.slabs.len())),
+        }
+        value.push_str(&format!("Teilsumme: {:.5}", sweep.partial()));
+        text.sections[0].value = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controls::ControlInput;
+    use crate::testing::*;
+
+    /// Slabs per column as `(visible, hidden)`.
+    fn slabs(app: &mut App) -> Vec<(usize, usize)> {
+        let mut query = app.world.query::<(&Slab, &Visibility)>();
+        let mut out = Vec::new();
+        for (slab, vis) in query.iter(&app.world) {
+            if out.len() <= slab.column {
+                out.resize(slab.column + 1, (0, 0));
+            }
+            match vis {
+                Visibility::Hidden => out[slab.column].1 += 1,
+                _ => out[slab.column].0 += 1,
+            }
+        }
+        out
+    }
+
+    fn fubini_text(app: &mut App) -> String {
+        let mut query = app.world.query_filtered::<&Text, With<FubiniText>>();
+        query.single(&app.world).sections[0].value.clone()
+    }
+
+    #[test]
+    fn fubini_sweep_builds_the_sum_slab_by_slab() {
+        use bevy::time::TimeUpdateStrategy;
+
+        let mut app = test_app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            // the virtual clock never advances more than this per frame
+            std::time::Duration::from_millis(250),
+        ));
+        config(&mut app).n = 2;
+        config(&mut app).fubini_seconds = 2.;
+        app.update();
+        press(&mut app, "fubini", ControlInput::Press);
+        // the cubes of the primary side make room for the slabs
+        assert_eq!(cubes(&mut app, 2).0, 0);
+
+        // four columns, two frames each, every slab grows two rows per frame
+        let sweep = app.world.resource::<Sweep>().clone();
+        assert_eq!(sweep.slabs.len(), 4);
+        assert_eq!(sweep.progress, 0.5);
+        assert_eq!(slabs(&mut app), [(2, 2), (0, 4), (0, 4), (0, 4)]);
+        app.update();
+        app.update();
+        assert_eq!(slabs(&mut app), [(4, 0), (2, 2), (0, 4), (0, 4)]);
+        // lower left samples: the first column adds 0.25 * (0 + 0.25 + 0.5 + 0.75) / 4, the
+        // two rows of the second one 0.25 * (0.25 + 0.5) / 4
+        let sweep = app.world.resource::<Sweep>().clone();
+        assert!((sweep.partial() - (0.375 + 0.1875) / 4.).abs() < 1e-6);
+        let text = fubini_text(&mut app);
+        assert!(text.contains("Fubini, dy dx"), "{text}");
+        assert!(text.contains("Streifen 2 von 4 bei x = 0.375"), "{text}");
+
+        for _ in 0..10 {
+            app.update();
+        }
+        let riemann = core::riemann(&f, &Domain::UNIT, &Partition::dyadic(2), Rule::LowerLeft).sum;
+        let sweep = app.world.resource::<Sweep>().clone();
+        assert!(sweep.is_done());
+        assert!((sweep.partial() - riemann).abs() < 1e-6);
+        assert!(slabs(&mut app).iter().all(|&(_, hidden)| hidden == 0));
+        assert!(fubini_text(&mut app).contains("alle 4 Streifen"));
+
+        // the other order restarts the sweep across y
+        press(&mut app, "fubini_order", ControlInput::Press);
+        let sweep = app.world.resource::<Sweep>().clone();
+        assert_eq!(sweep.order, Order::DxDy);
+        assert!(sweep.progress < 1.);
+        assert!(fubini_text(&mut app).contains("Fubini, dx dy"));
+
+        press(&mut app, "fubini", ControlInput::Press);
+        assert!(slabs(&mut app).is_empty());
+        assert_eq!(cubes(&mut app, 2), (12, 0));
+    }
+}
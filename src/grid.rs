@@ -0,0 +1,476 @@
+//! The coordinate grid and the labels of its axes.
+//!
+//! The grid covers the domain and the range of the function, see [`GridBounds`]; its
+//! density follows the distance of the camera, see [`GridSpacing`]. All lines are one mesh
+//! that is only rebuilt when one of them changes.
+
+use bevy::{
+    prelude::*,
+    render::{mesh::PrimitiveTopology, view::RenderLayers},
+};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{
+    core::{Domain, Integrand},
+    default_camera, Config, View,
+};
+
+/// The world axes with their colors and the names of the coordinates they show. The
+/// domain's `y` is the world's `z`, the height is the world's `y`.
+const AXES: [(Vec3, Color, &str); 3] = [
+    (Vec3::X, Color::rgb(1.0, 0.2, 0.2), "x"),
+    (Vec3::Y, Color::rgb(0.2, 1.0, 0.2), "z"),
+    (Vec3::Z, Color::rgb(0.2, 0.2, 1.0), "y"),
+];
+
+/// How far the axes reach beyond the grid, so their names don't sit on a line.
+const AXIS_OVERHANG: f32 = 0.25;
+
+const MINOR_LINE_COLOR: Color = Color::rgba(0.01, 0.01, 0.01, 0.01);
+const MAJOR_LINE_COLOR: Color = Color::rgba(0.25, 0.25, 0.25, 0.5);
+
+/// How finely the grid is divided and labeled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridSpacing {
+    /// A whole number from `1` to `3` that grows with the distance of the camera. Up to `1`
+    /// the minor lines are labeled too.
+    pub fadeout_distance: f32,
+    /// Minor lines per unit, the major lines are at the whole numbers.
+    pub minor_per_major: i32,
+}
+
+impl GridSpacing {
+    /// The spacing for a camera `target_radius` away from its focus: the closer, the
+    /// finer the grid.
+    pub fn new(target_radius: f32) -> Self {
+        Self {
+            fadeout_distance: (target_radius / 2.).clamp(1.0, 3.0).round(),
+            minor_per_major: ((6. / target_radius.sqrt()).round() as i32).max(1),
+        }
+    }
+
+    /// The coordinates from `min` to `max` that get a label, without the origin: every
+    /// major line, and every minor one too when the camera is close.
+    pub fn ticks(&self, min: f32, max: f32) -> Vec<f32> {
+        let per_unit = if self.fadeout_distance <= 1. {
+            self.minor_per_major
+        } else {
+            1
+        };
+        steps(min, max, per_unit)
+            .filter(|&(k, _)| k != 0)
+            .map(|(_, value)| value)
+            .collect()
+    }
+}
+
+/// The multiples `k / per_unit` from `min` to `max`, with their `k`.
+fn steps(min: f32, max: f32, per_unit: i32) -> impl Iterator<Item = (i32, f32)> {
+    let scale = per_unit as f32;
+    let first = (min * scale - 1e-4).ceil() as i32;
+    let last = (max * scale + 1e-4).floor() as i32;
+    (first..=last).map(move |k| (k, k as f32 / scale))
+}
+
+/// The box the grid covers, in world coordinates and whole units. The resource holds the
+/// bounds of `f` over the unit square, computed once when the plugin is built.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct GridBounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl GridBounds {
+    /// Covers the origin, `domain` and the heights of `integrand` over it, sampled on a
+    /// `32 × 32` grid. Every axis reaches at least `1` into the positive direction.
+    pub fn new(domain: &Domain, integrand: &impl Integrand) -> Self {
+        const SAMPLES: u32 = 32;
+        let (mut low, mut high) = (0., 1.);
+        for i in 0..=SAMPLES {
+            for j in 0..=SAMPLES {
+                let x = domain.min_x + domain.width() * i as f32 / SAMPLES as f32;
+                let y = domain.min_y + domain.depth() * j as f32 / SAMPLES as f32;
+                let z = integrand.eval(x, y);
+                if z.is_finite() {
+                    low = z.min(low);
+                    high = z.max(high);
+                }
+            }
+        }
+        let min = Vec3::new(domain.min_x, low, domain.min_y).min(Vec3::ZERO);
+        let max = Vec3::new(domain.max_x, high, domain.max_y).max(Vec3::ONE);
+        Self {
+            min: min.floor(),
+            max: max.ceil(),
+        }
+    }
+}
+
+/// The target radius of the primary camera, or of the default one without a camera of
+/// our own.
+fn target_radius(orbit_cameras: &Query<(&PanOrbitCamera, &View)>) -> f32 {
+    orbit_cameras
+        .iter()
+        .find(|(_, view)| **view == View::Primary)
+        .map_or(default_camera().target_radius, |(camera, _)| {
+            camera.target_radius
+        })
+}
+
+/// Collects line segments with a color per vertex.
+#[derive(Default)]
+struct Lines {
+    positions: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+}
+
+impl Lines {
+    fn line(&mut self, start: Vec3, end: Vec3, color: Color) {
+        self.positions.extend([start.to_array(), end.to_array()]);
+        self.colors.extend([color.as_rgba_f32(); 2]);
+    }
+
+    fn into_mesh(self) -> Mesh {
+        Mesh::new(PrimitiveTopology::LineList)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, self.colors)
+    }
+}
+
+/// The axes and the grid lines. With `full` the lines fill the whole box, otherwise they
+/// lie on the three coordinate planes.
+pub fn grid_mesh(bounds: &GridBounds, spacing: &GridSpacing, full: bool) -> Mesh {
+    let per_unit = spacing.minor_per_major;
+    let color = |major: bool| {
+        if major {
+            MAJOR_LINE_COLOR
+        } else {
+            MINOR_LINE_COLOR
+        }
+    };
+    let along = |axis: Vec3| {
+        let min = bounds.min.dot(axis);
+        let max = bounds.max.dot(axis);
+        steps(min, max, per_unit).map(|(k, v)| (k.rem_euclid(per_unit) == 0, v))
+    };
+    let mut lines = Lines::default();
+
+    for (axis, color, _) in AXES {
+        let min = bounds.min.dot(axis) - AXIS_OVERHANG * f32::from(bounds.min.dot(axis) < 0.);
+        let max = bounds.max.dot(axis) + AXIS_OVERHANG;
+        lines.line(axis * min, axis * max, color);
+    }
+
+    let directions = [
+        (Vec3::X, Vec3::Y, Vec3::Z),
+        (Vec3::Y, Vec3::Z, Vec3::X),
+        (Vec3::Z, Vec3::X, Vec3::Y),
+    ];
+    for (dir, a_axis, b_axis) in directions {
+        let start = bounds.min * dir;
+        let end = bounds.max * dir;
+        if full {
+            for (a_major, a) in along(a_axis) {
+                for (b_major, b) in along(b_axis) {
+                    let offset = a_axis * a + b_axis * b;
+                    lines.line(offset + start, offset + end, color(a_major || b_major));
+                }
+            }
+        } else {
+            for offset_axis in [a_axis, b_axis] {
+                for (major, a) in along(offset_axis) {
+                    let offset = offset_axis * a;
+                    lines.line(offset + start, offset + end, color(major));
+                }
+            }
+        }
+    }
+    lines.into_mesh()
+}
+
+#[derive(Component)]
+pub(crate) struct GridLines;
+
+/// Rebuilds the grid mesh when the bounds, the spacing or [`Config::show_full_grid`] change.
+pub(crate) fn grid(
+    mut commands: Commands,
+    orbit_cameras: Query<(&PanOrbitCamera, &View)>,
+    bounds: Res<GridBounds>,
+    config: Res<Config>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    grid: Query<&Handle<Mesh>, With<GridLines>>,
+    mut built: Local<Option<(GridBounds, GridSpacing, bool)>>,
+) {
+    let key = (
+        *bounds,
+        GridSpacing::new(target_radius(&orbit_cameras)),
+        config.show_full_grid,
+    );
+    if *built == Some(key) {
+        return;
+    }
+    *built = Some(key);
+    let (bounds, spacing, full) = key;
+    let mesh = grid_mesh(&bounds, &spacing, full);
+    if let Ok(handle) = grid.get_single() {
+        if let Some(old) = meshes.get_mut(handle) {
+            *old = mesh;
+            return;
+        }
+    }
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(mesh),
+            material: materials.add(StandardMaterial {
+                // the vertex colors are used as they are
+                base_color: Color::WHITE,
+                unlit: true,
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
+            ..default()
+        },
+        GridLines,
+        RenderLayers::all(),
+    ));
+}
+
+/// A tick or axis name, kept over its point of the world in the viewport of a view by
+/// `axis_labels`.
+#[derive(Component)]
+pub(crate) struct AxisLabel(Vec3, View);
+
+/// Respawns the labels when the grid changes and moves them over their points.
+pub(crate) fn axis_labels(
+    mut commands: Commands,
+    orbit_cameras: Query<(&PanOrbitCamera, &View)>,
+    bounds: Res<GridBounds>,
+    cameras: Query<(&Camera, &GlobalTransform, &View)>,
+    ui_scale: Res<UiScale>,
+    mut labels: Query<(Entity, &AxisLabel, &mut Style)>,
+    mut spawned: Local<Option<(GridBounds, GridSpacing)>>,
+) {
+    let key = (*bounds, GridSpacing::new(target_radius(&orbit_cameras)));
+    if *spawned != Some(key) {
+        *spawned = Some(key);
+        for (entity, _, _) in &labels {
+            commands.entity(entity).despawn_recursive();
+        }
+        let text = |value: String, font_size, color, position, view| {
+            (
+                TextBundle::from_section(
+                    value,
+                    TextStyle {
+                        font_size,
+                        color,
+                        ..default()
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    display: Display::None,
+                    ..default()
+                }),
+                AxisLabel(position, view),
+            )
+        };
+        let (bounds, spacing) = key;
+        // the secondary labels stay hidden while there is no secondary camera
+        for view in View::ALL {
+            commands.spawn(text("0".into(), 14., Color::GRAY, Vec3::ZERO, view));
+            for (axis, color, name) in AXES {
+                for tick in spacing.ticks(bounds.min.dot(axis), bounds.max.dot(axis)) {
+                    commands.spawn(text(tick_label(tick), 14., color, axis * tick, view));
+                }
+                let end = axis * (bounds.max.dot(axis) + AXIS_OVERHANG);
+                commands.spawn(text(name.into(), 20., color, end, view));
+            }
+        }
+        // spawned labels are placed in the next frame
+        return;
+    }
+
+    for (_, AxisLabel(position, view), mut style) in &mut labels {
+        let camera = cameras.iter().find(|(_, _, v)| *v == view);
+        let screen = camera.and_then(|(camera, transform, _)| {
+            let rect = camera.logical_viewport_rect()?;
+            let p = camera.world_to_viewport(transform, *position)?;
+            (p.cmpge(Vec2::ZERO).all() && p.cmple(rect.size()).all()).then_some(rect.min + p)
+        });
+        let (display, left, top) = match screen {
+            // the layout is scaled by `UiScale`, the viewports are not. A few pixels off the
+            // point, so the label doesn't cover the line
+            Some(p) => {
+                let p = p / ui_scale.0 as f32;
+                (Display::Flex, Val::Px(p.x + 4.), Val::Px(p.y + 2.))
+            }
+            None => (Display::None, style.left, style.top),
+        };
+        if style.display != display || style.left != left || style.top != top {
+            style.display = display;
+            style.left = left;
+            style.top = top;
+        }
+    }
+}
+
+/// `value` with at most two decimals and without trailing zeros.
+pub fn tick_label(value: f32) -> String {
+    let text = format!("{value:.2}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        render::camera::{camera_system, ManualTextureViews, Viewport},
+        window::{PrimaryWindow, WindowCreated, WindowResized},
+    };
+
+    use super::*;
+    use crate::testing::*;
+
+    fn axis_labels(app: &mut App) -> Vec<(String, Display)> {
+        let mut query = app.world.query::<(&Text, &Style, &AxisLabel)>();
+        let mut labels: Vec<_> = query
+            .iter(&app.world)
+            .map(|(text, style, _)| (text.sections[0].value.clone(), style.display))
+            .collect();
+        labels.sort_by(|a, b| a.0.cmp(&b.0));
+        labels
+    }
+
+    #[test]
+    fn axis_labels_follow_the_camera_distance() {
+        let mut app = test_app();
+        app.update();
+        // the origin, the whole numbers up to the bounds of the grid and a name per axis, for
+        // both views
+        let labels = axis_labels(&mut app);
+        assert_eq!(labels.len(), 2 * (1 + 2 + 3 + 2), "{labels:?}");
+        assert_eq!(labels.iter().filter(|(text, _)| text == "1").count(), 2 * 3);
+        assert_eq!(labels.iter().filter(|(text, _)| text == "2").count(), 2);
+        for name in ["x", "y", "z"] {
+            assert!(labels.iter().any(|(text, _)| text == name));
+        }
+        // nothing to project them with
+        assert!(labels.iter().all(|(_, display)| *display == Display::None));
+
+        app.world.spawn((
+            PanOrbitCamera {
+                target_radius: 1.,
+                ..default()
+            },
+            View::Primary,
+        ));
+        app.update();
+        app.update();
+        let labels = axis_labels(&mut app);
+        assert_eq!(labels.len(), 2 * (1 + 7 + 13 + 7));
+        assert!(labels.iter().any(|(text, _)| text == "0.5"));
+    }
+
+    /// Where the visible labels `0` at the origin are, from left to right.
+    fn origin_labels(app: &mut App) -> Vec<Vec2> {
+        let mut query = app.world.query::<(&Text, &Style, &AxisLabel)>();
+        let mut labels: Vec<_> = query
+            .iter(&app.world)
+            .filter(|(text, style, _)| {
+                text.sections[0].value == "0" && style.display == Display::Flex
+            })
+            .map(|(_, style, _)| match (style.left, style.top) {
+                (Val::Px(left), Val::Px(top)) => Vec2::new(left, top),
+                position => panic!("{position:?}"),
+            })
+            .collect();
+        labels.sort_by(|a, b| a.x.total_cmp(&b.x));
+        labels
+    }
+
+    #[test]
+    fn axis_labels_in_every_viewport() {
+        let mut app = test_app();
+        app.init_asset::<Image>()
+            .init_resource::<ManualTextureViews>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_systems(PostUpdate, camera_system::<Projection>);
+        app.world.spawn((Window::default(), PrimaryWindow));
+        // looking straight at the origin, which is in the middle of the viewport
+        let camera = |app: &mut App, view, viewport| {
+            app.world.spawn((
+                Camera {
+                    viewport,
+                    ..default()
+                },
+                Projection::default(),
+                GlobalTransform::from(Transform::from_xyz(0., 0., 5.)),
+                view,
+            ));
+        };
+        camera(&mut app, View::Primary, None);
+        app.update();
+        app.update();
+        assert_eq!(origin_labels(&mut app), [Vec2::new(644., 362.)]);
+
+        // the layout is scaled, the viewport is not
+        app.insert_resource(UiScale(2.));
+        app.update();
+        assert_eq!(origin_labels(&mut app), [Vec2::new(324., 182.)]);
+
+        app.insert_resource(UiScale(1.));
+        let half = |x| {
+            Some(Viewport {
+                physical_position: UVec2::new(x, 0),
+                physical_size: UVec2::new(640, 720),
+                ..default()
+            })
+        };
+        let mut query = app.world.query::<&mut Camera>();
+        query.single_mut(&mut app.world).viewport = half(0);
+        camera(&mut app, View::Secondary, half(640));
+        app.update();
+        app.update();
+        assert_eq!(
+            origin_labels(&mut app),
+            [Vec2::new(324., 362.), Vec2::new(964., 362.)]
+        );
+    }
+
+    #[test]
+    fn grid_mesh_is_cached() {
+        let mut app = test_app();
+        app.update();
+        let mut query = app.world.query_filtered::<&Handle<Mesh>, With<GridLines>>();
+        let handle = query.single(&app.world).clone();
+        let vertices = |app: &App| {
+            app.world
+                .resource::<Assets<Mesh>>()
+                .get(&handle)
+                .unwrap()
+                .count_vertices()
+        };
+        let full = vertices(&app);
+
+        let changed = |app: &mut App| {
+            app.update();
+            app.world
+                .resource_mut::<Events<AssetEvent<Mesh>>>()
+                .drain()
+                .any(|event| event == AssetEvent::Modified { id: handle.id() })
+        };
+        changed(&mut app);
+        assert!(!changed(&mut app));
+
+        config(&mut app).show_full_grid = false;
+        assert!(changed(&mut app));
+        assert!(vertices(&app) < full);
+        assert_eq!(query.iter(&app.world).count(), 1);
+
+        // the bounds are only computed once, but can be replaced
+        assert!(!changed(&mut app));
+        app.world.resource_mut::<GridBounds>().max.x += 1.;
+        assert!(changed(&mut app));
+    }
+}
@@ -0,0 +1,397 @@
+//! Keyboard shortcuts for every control and the help overlay listing them.
+//!
+//! The default bindings can be overridden with a `keybindings.cfg` file in the working
+//! directory. Every line has the form `action = Key, Key`, where the key names are the
+//! variants of [`KeyCode`], e.g. `more = Right, PageDown`. Lines starting with `#` are ignored.
+//!
+//! Every control of the panel can be bound: the action is named like the key of the control,
+//! steppers and sliders have a `<key>_more` and a `<key>_less` action instead. Only `n` keeps
+//! the short `more` and `less`.
+
+use bevy::{
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, TypeInfo, Typed},
+};
+
+use crate::{
+    controls::{Control, ControlKind, ControlRegistry},
+    Config,
+};
+
+/// Everything that can be triggered by a key.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Action {
+    /// Toggles, cycles or runs the control with the key, like clicking it does.
+    Press(&'static str),
+    /// Steps the stepper or slider with the key up.
+    More(&'static str),
+    /// Steps the stepper or slider with the key down.
+    Less(&'static str),
+    ResetCamera,
+    Help,
+}
+
+impl Action {
+    /// The actions that operate `control`.
+    pub fn of(control: &Control) -> Vec<Self> {
+        match control.kind {
+            ControlKind::Stepper { .. } | ControlKind::Slider { .. } => {
+                vec![Self::More(control.key), Self::Less(control.key)]
+            }
+            _ => vec![Self::Press(control.key)],
+        }
+    }
+
+    /// Name of the action in the key binding file.
+    pub fn name(self) -> String {
+        match self {
+            // the refinement keeps the short names
+            Self::More("n") => "more".into(),
+            Self::Less("n") => "less".into(),
+            Self::Press(key) => key.into(),
+            Self::More(key) => format!("{key}_more"),
+            Self::Less(key) => format!("{key}_less"),
+            Self::ResetCamera => "reset_camera".into(),
+            Self::Help => "help".into(),
+        }
+    }
+
+    fn description(self, registry: &ControlRegistry) -> String {
+        let control = |key| {
+            registry
+                .get(key)
+                .map(|control| (control.label, &control.kind))
+        };
+        match self {
+            Self::Press(key) => control(key).map_or(key, |(label, _)| label).into(),
+            // the texts of the buttons of a stepper
+            Self::More(key) => match control(key) {
+                Some((label, ControlKind::Stepper { more, .. })) => format!("{label}: {more}"),
+                Some((label, _)) => format!("{label} +"),
+                None => key.into(),
+            },
+            Self::Less(key) => match control(key) {
+                Some((label, ControlKind::Stepper { less, .. })) => format!("{label}: {less}"),
+                Some((label, _)) => format!("{label} -"),
+                None => key.into(),
+            },
+            Self::ResetCamera => "Kamera zurücksetzen".into(),
+            Self::Help => "Hilfe".into(),
+        }
+    }
+
+    fn default_keys(self) -> Vec<KeyCode> {
+        use KeyCode::*;
+        match self {
+            Self::More("n") => vec![Right, Up, PageDown, Plus, NumpadAdd],
+            Self::Less("n") => vec![Left, Down, PageUp, Minus, NumpadSubtract],
+            Self::Press("function") => vec![F],
+            Self::Press("incremental") => vec![I],
+            Self::Press("grid") => vec![G],
+            Self::Press("party") => vec![P],
+            Self::Press("export") => vec![E],
+            Self::Press("autoplay") => vec![Space],
+            Self::Press("compare") => vec![C],
+            Self::Press("slice") => vec![S],
+            Self::Press("bookmark") => vec![B],
+            Self::Press("next_bookmark") => vec![V],
+            Self::ResetCamera => vec![R, Home],
+            Self::Help => vec![H, F1],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The keys bound to each [`Action`], one for every control of the [`ControlRegistry`] and
+/// the ones without a control.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub(crate) struct InputMap(pub Vec<(Action, Vec<KeyCode>)>);
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    // `from_reflect` panics on unknown variants, so look the name up first
+    let TypeInfo::Enum(info) = KeyCode::type_info() else {
+        return None;
+    };
+    info.variant(name)?;
+    KeyCode::from_reflect(&DynamicEnum::new(name, DynamicVariant::Unit))
+}
+
+impl InputMap {
+    pub const FILE: &'static str = "keybindings.cfg";
+
+    /// The default keys for the controls of `registry`.
+    pub fn new(registry: &ControlRegistry) -> Self {
+        let actions = registry
+            .iter()
+            .flat_map(Action::of)
+            .chain([Action::ResetCamera, Action::Help]);
+        Self(
+            actions
+                .map(|action| (action, action.default_keys()))
+                .collect(),
+        )
+    }
+
+    /// Parses a key binding file. Actions that are not listed keep their default keys.
+    pub fn parse(s: &str, registry: &ControlRegistry) -> Result<Self, String> {
+        let mut map = Self::new(registry);
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, keys) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `action = keys`", idx + 1))?;
+            let name = name.trim();
+            let action = map
+                .0
+                .iter()
+                .map(|(action, _)| *action)
+                .find(|action| action.name() == name)
+                .ok_or_else(|| format!("line {}: unknown action `{name}`", idx + 1))?;
+            let keys = keys
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(|key| {
+                    parse_key(key).ok_or_else(|| format!("line {}: unknown key `{key}`", idx + 1))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            for (a, k) in &mut map.0 {
+                if *a == action {
+                    *k = keys.clone();
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    /// Reads [`InputMap::FILE`] if it exists and falls back to the defaults otherwise.
+    pub fn load(registry: &ControlRegistry) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(s) = std::fs::read_to_string(Self::FILE) {
+            match Self::parse(&s, registry) {
+                Ok(map) => return map,
+                Err(e) => error!("invalid {}: {e}", Self::FILE),
+            }
+        }
+        Self::new(registry)
+    }
+
+    /// The bound actions with their keys, the others are only in the binding file.
+    fn help_text(&self, registry: &ControlRegistry) -> String {
+        let mut out = String::from("Tastenkürzel");
+        for (action, keys) in self.0.iter().filter(|(_, keys)| !keys.is_empty()) {
+            let keys: Vec<String> = keys.iter().map(|key| format!("{key:?}")).collect();
+            out.push_str(&format!(
+                "\n{} - {}",
+                keys.join(" / "),
+                action.description(registry)
+            ));
+        }
+        out
+    }
+}
+
+pub(crate) fn keyboard_system(
+    keys: Res<Input<KeyCode>>,
+    map: Res<InputMap>,
+    mut actions: EventWriter<Action>,
+) {
+    for (action, bound) in &map.0 {
+        if keys.any_just_pressed(bound.iter().copied()) {
+            actions.send(*action);
+        }
+    }
+}
+
+/// Loads the key bindings once all controls are registered, unless the app inserted its own.
+pub(crate) fn load_input_map(
+    mut commands: Commands,
+    registry: Res<ControlRegistry>,
+    map: Option<Res<InputMap>>,
+) {
+    if map.is_none() {
+        commands.insert_resource(InputMap::load(&registry));
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct HelpOverlay;
+
+pub(crate) fn setup_help(
+    mut commands: Commands,
+    map: Res<InputMap>,
+    registry: Res<ControlRegistry>,
+) {
+    commands.spawn((
+        TextBundle::from_section(
+            map.help_text(&registry),
+            TextStyle {
+                font_size: 18.,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(25.0),
+            right: Val::Px(25.0),
+            padding: UiRect::all(Val::Px(10.0)),
+            display: Display::None,
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.7)),
+        HelpOverlay,
+    ));
+}
+
+pub(crate) fn help_overlay(
+    config: Res<Config>,
+    map: Res<InputMap>,
+    registry: Res<ControlRegistry>,
+    mut overlay: Query<(&mut Style, &mut Text), With<HelpOverlay>>,
+) {
+    if !config.is_changed() && !map.is_changed() {
+        return;
+    }
+    for (mut style, mut text) in &mut overlay {
+        style.display = if config.show_help {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if map.is_changed() {
+            text.sections[0].value = map.help_text(&registry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controls::ControlInput;
+    use crate::core::Rule;
+    use crate::testing::*;
+    use crate::SHOW_FUN;
+
+    #[test]
+    fn keys_trigger_actions() {
+        let mut app = test_app();
+        key(&mut app, KeyCode::Right);
+        assert_eq!(config(&mut app).n, 2);
+        assert_eq!(n_label(&mut app), "n: 2");
+        key(&mut app, KeyCode::PageUp);
+        assert_eq!(config(&mut app).n, 1);
+
+        key(&mut app, KeyCode::F);
+        assert!(!config(&mut app).show_function);
+        assert_eq!(plane_visibility(&mut app), [Visibility::Hidden]);
+        let button = button(&mut app, "function", ControlInput::Press);
+        assert_eq!(label(&mut app, button), SHOW_FUN);
+
+        key(&mut app, KeyCode::G);
+        assert!(!config(&mut app).show_full_grid);
+        key(&mut app, KeyCode::Space);
+        assert!(config(&mut app).autoplay);
+    }
+
+    #[test]
+    fn help_overlay_toggles() {
+        let mut app = test_app();
+        let mut query = app.world.query_filtered::<&Style, With<HelpOverlay>>();
+        assert_eq!(query.single(&app.world).display, Display::None);
+        key(&mut app, KeyCode::H);
+        assert_eq!(query.single(&app.world).display, Display::Flex);
+        key(&mut app, KeyCode::F1);
+        assert_eq!(query.single(&app.world).display, Display::None);
+    }
+
+    fn keys(map: &InputMap, action: Action) -> Vec<KeyCode> {
+        map.0
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(Vec::new(), |(_, keys)| keys.clone())
+    }
+
+    #[test]
+    fn remapped_keys() {
+        let mut app = test_app();
+        let registry = app.world.resource::<ControlRegistry>();
+        let map = InputMap::parse(
+            "# presenter clicker\nmore = B\nless = Key1, Key2\n",
+            registry,
+        )
+        .unwrap();
+        assert_eq!(keys(&map, Action::More("n")), [KeyCode::B]);
+        assert_eq!(
+            keys(&map, Action::Less("n")),
+            [KeyCode::Key1, KeyCode::Key2]
+        );
+        assert_eq!(
+            keys(&map, Action::Help),
+            keys(&InputMap::new(registry), Action::Help)
+        );
+        app.insert_resource(map);
+
+        key(&mut app, KeyCode::Right);
+        assert_eq!(config(&mut app).n, 1);
+        key(&mut app, KeyCode::B);
+        assert_eq!(config(&mut app).n, 2);
+        key(&mut app, KeyCode::Key2);
+        assert_eq!(config(&mut app).n, 1);
+
+        let mut query = app.world.query_filtered::<&Text, With<HelpOverlay>>();
+        assert!(query.single(&app.world).sections[0]
+            .value
+            .contains("Key1 / Key2 - n: (weniger)"));
+    }
+
+    #[test]
+    fn every_control_can_be_bound() {
+        let mut app = test_app();
+        let registry = app.world.resource::<ControlRegistry>();
+        let map = InputMap::new(registry);
+        for control in registry.iter() {
+            for action in Action::of(control) {
+                assert!(map.0.iter().any(|(a, _)| *a == action), "{action:?}");
+            }
+        }
+        let map = InputMap::parse(
+            "rule = Key1\nslice_position_more = Key2\ncompare_n_less = Key3\nsave_scene = Key4",
+            registry,
+        )
+        .unwrap();
+        assert_eq!(keys(&map, Action::Press("rule")), [KeyCode::Key1]);
+        assert_eq!(keys(&map, Action::Press("save_scene")), [KeyCode::Key4]);
+        app.insert_resource(map);
+
+        key(&mut app, KeyCode::Key1);
+        assert_eq!(config(&mut app).rule, Rule::ALL[1]);
+        // sliders step by a tenth of their range
+        let position = config(&mut app).slice_position;
+        key(&mut app, KeyCode::Key2);
+        assert!((config(&mut app).slice_position - position - 0.1).abs() < 1e-6);
+
+        let mut query = app.world.query_filtered::<&Text, With<HelpOverlay>>();
+        let help = query.single(&app.world).sections[0].value.clone();
+        assert!(help.contains("Key1 - Regel"), "{help}");
+        assert!(help.contains("Key3 - n rechts: (weniger)"), "{help}");
+        // unbound controls are left out
+        assert!(!help.contains("Farbe"), "{help}");
+    }
+
+    #[test]
+    fn invalid_key_bindings() {
+        let app = test_app();
+        let registry = app.world.resource::<ControlRegistry>();
+        assert!(InputMap::parse("more Right", registry).is_err());
+        assert!(InputMap::parse("faster = Right", registry).is_err());
+        assert!(InputMap::parse("more = NotAKey", registry).is_err());
+        assert!(InputMap::parse("n_more = Right", registry).is_err());
+        assert_eq!(
+            InputMap::parse("", registry).unwrap(),
+            InputMap::new(registry)
+        );
+    }
+}
@@ -1,3 +1,8 @@
+use std::{
+    f32::consts::{PI, TAU},
+    time::Duration,
+};
+
 use bevy::{
     core_pipeline::{
         bloom::{BloomCompositeMode, BloomSettings},
@@ -6,7 +11,9 @@ use bevy::{
     ecs::query::QuerySingleError,
     log::LogPlugin,
     prelude::*,
-    window::WindowMode,
+    render::{camera::Viewport, view::RenderLayers},
+    utils::{HashMap, HashSet},
+    window::{PrimaryWindow, WindowMode},
 };
 #[cfg(feature = "embedded")]
 use bevy_embedded_assets::EmbeddedAssetPlugin;
@@ -14,23 +21,79 @@ use bevy_embedded_assets::EmbeddedAssetPlugin;
 use bevy_inspector_egui::prelude::*;
 #[cfg(feature = "inspect")]
 use bevy_inspector_egui::quick::ResourceInspectorPlugin;
-use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
+use bevy_panorbit_camera::{
+    ActiveCameraData, PanOrbitCamera, PanOrbitCameraPlugin, PanOrbitCameraSystemSet,
+};
+
+pub mod colors;
+pub mod controls;
+pub mod core;
+pub mod export;
+pub mod fubini;
+pub mod grid;
+mod input;
+pub mod picking;
+pub mod scene;
+pub mod slice;
+#[cfg(test)]
+mod testing;
+pub mod views;
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+use crate::colors::{ColorMode, ColorScale, Palette};
+use crate::controls::{
+    Binding, Control, ControlButton, ControlEvent, ControlInput, ControlKind, ControlPanel,
+    ControlValue, RegisterControl,
+};
+pub use crate::core::Float;
+use crate::core::{Axis, Cell, Domain, Partition, Rule};
+use crate::fubini::Order;
+use crate::input::Action;
+use crate::views::Preset;
 
 pub const LAUNCHER_TITLE: &str = "Anninenonanulysis";
 
-#[derive(Reflect, Resource, Clone)]
+/// Settings of the visualization. Insert it through [`IntegralPlugin::config`] or change
+/// the resource at runtime.
+#[derive(Reflect, Resource, Clone, Debug, PartialEq)]
 #[cfg_attr(
     feature = "inspect",
     derive(InspectorOptions),
     reflect(InspectorOptions)
 )]
-struct Config {
+pub struct Config {
+    /// Refinement level, the cubes have a side length of `2^-n`.
     #[cfg_attr(feature = "inspect", inspector(min = 0, max = 40))]
-    n: u8,
-    show_incremental_cubes: bool,
-    show_function: bool,
-    show_full_grid: bool,
-    show_party: bool,
+    pub n: u8,
+    /// Stack the cubes of every level up to `n` instead of showing only level `n`.
+    pub show_incremental_cubes: bool,
+    /// Where the height of each cube is sampled.
+    #[reflect(ignore)]
+    pub rule: Rule,
+    pub show_function: bool,
+    pub show_full_grid: bool,
+    pub show_party: bool,
+    /// Step through the levels automatically, one every `autoplay_seconds`.
+    pub autoplay: bool,
+    pub autoplay_seconds: f32,
+    pub show_help: bool,
+    /// Show the [`View::Secondary`] side next to the primary one.
+    pub split_screen: bool,
+    /// Move both cameras together in split screen.
+    pub sync_cameras: bool,
+    pub color_mode: ColorMode,
+    pub palette: Palette,
+    /// Cut the scene at `slice_axis = slice_position` and show the 1D sum of the cut.
+    pub show_slice: bool,
+    #[reflect(ignore)]
+    pub slice_axis: Axis,
+    pub slice_position: f32,
+    /// Build the sum of the primary side slab by slab as an iterated integral.
+    pub fubini: bool,
+    pub fubini_order: Order,
+    /// Duration of the whole sweep.
+    pub fubini_seconds: f32,
 }
 
 impl Default for Config {
@@ -39,56 +102,317 @@ impl Default for Config {
             n: 1,
             show_full_grid: true,
             show_incremental_cubes: true,
+            rule: Rule::LowerLeft,
             show_function: true,
             show_party: false,
+            autoplay: false,
+            autoplay_seconds: 1.5,
+            show_help: false,
+            split_screen: false,
+            sync_cameras: true,
+            color_mode: ColorMode::Level,
+            palette: Palette::Classic,
+            show_slice: false,
+            slice_axis: Axis::X,
+            slice_position: 0.5,
+            fubini: false,
+            fubini_order: Order::DyDx,
+            fubini_seconds: 8.0,
         }
     }
 }
 
-pub fn app(fullscreen: bool) -> App {
-    let mode = if fullscreen {
-        WindowMode::BorderlessFullscreen
-    } else {
-        WindowMode::Windowed
-    };
+/// One side of the split screen comparison. The entity with the [`Side`] of a view, its
+/// cubes, its camera and its readout all carry the view.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum View {
+    /// Follows the [`Config`] and fills the whole window outside of split screen.
+    Primary,
+    Secondary,
+}
 
-    let mut app = App::new();
-    let default_plugins = DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            mode,
-            title: LAUNCHER_TITLE.to_string(),
-            fit_canvas_to_parent: true,
-            prevent_default_event_handling: true,
-            present_mode: bevy::window::PresentMode::AutoVsync,
-            decorations: false,
+impl View {
+    pub const ALL: [Self; 2] = [Self::Primary, Self::Secondary];
+
+    /// Cubes of a view are only rendered by the camera of that view, everything else is on
+    /// all layers.
+    pub fn layers(self) -> RenderLayers {
+        match self {
+            Self::Primary => RenderLayers::layer(0),
+            Self::Secondary => RenderLayers::layer(1),
+        }
+    }
+}
+
+/// The approximation shown in a [`View`], kept apart from the [`Config`] so both sides
+/// can differ. The primary side is overwritten whenever the [`Config`] changes.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Side {
+    pub n: u8,
+    pub rule: Rule,
+    pub show_incremental_cubes: bool,
+}
+
+impl From<&Config> for Side {
+    fn from(config: &Config) -> Self {
+        Self {
+            n: config.n,
+            rule: config.rule,
+            show_incremental_cubes: config.show_incremental_cubes,
+        }
+    }
+}
+
+/// Ordering of the visualization's `Update` systems, the sets run in the listed order.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IntegralSet {
+    /// Keyboard, buttons and autoplay, which only send [`ControlEvent`]s or touch the [`Config`].
+    Input,
+    /// Applies the [`ControlEvent`]s to the [`Config`].
+    Controls,
+    /// Sends [`AddCubes`] and [`DeleteCubes`] when the [`Config`] changed and handles them.
+    Cubes,
+    /// Grid, function graph, labels and everything else that only follows the [`Config`].
+    Visuals,
+}
+
+/// The visualization itself: cubes, function graph, grid, light and the control registry.
+/// It expects the default plugins (or an equivalent) to be present.
+///
+/// Window, camera, sky box, control panel and keyboard shortcuts are left to the host app, or
+/// can be added with [`IntegralWindowPlugin`], [`IntegralCameraPlugin`],
+/// [`IntegralSkyboxPlugin`], [`IntegralUiPlugin`] and [`IntegralKeyboardPlugin`].
+#[derive(Default)]
+pub struct IntegralPlugin {
+    pub config: Config,
+}
+
+impl Plugin for IntegralPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config.clone())
+            .register_type::<Config>()
+            .init_resource::<ColorScale>()
+            .init_resource::<fubini::Sweep>()
+            .init_resource::<views::CameraViews>()
+            .insert_resource(grid::GridBounds::new(&Domain::UNIT, &f))
+            .add_event::<Action>()
+            .add_event::<ControlEvent>()
+            .add_event::<AddCubes>()
+            .add_event::<DeleteCubes>()
+            .add_event::<scene::SaveScene>();
+
+        app.world.spawn((View::Primary, Side::from(&self.config)));
+        let secondary = app
+            .world
+            .spawn((
+                View::Secondary,
+                Side {
+                    rule: Rule::Midpoint,
+                    ..Side::from(&self.config)
+                },
+            ))
+            .id();
+
+        register_controls(app, secondary);
+
+        app.configure_sets(
+            Update,
+            (
+                IntegralSet::Input,
+                IntegralSet::Controls,
+                IntegralSet::Cubes,
+                IntegralSet::Visuals,
+            )
+                .chain(),
+        )
+        .add_systems(Startup, (setup, slice::setup_plane))
+        .add_systems(Update, (apply_actions, autoplay).in_set(IntegralSet::Input))
+        .add_systems(
+            Update,
+            (
+                controls::apply_controls,
+                scene::save_requested,
+                follow_config,
+            )
+                .chain()
+                .in_set(IntegralSet::Controls),
+        )
+        .add_systems(
+            Update,
+            (change_cubes, delete_cubes, add_cubes)
+                .chain()
+                .in_set(IntegralSet::Cubes),
+        )
+        // new cubes are colored in the same frame
+        .add_systems(
+            Update,
+            apply_deferred
+                .after(IntegralSet::Cubes)
+                .before(IntegralSet::Visuals),
+        )
+        .add_systems(
+            Update,
+            (
+                grid::grid,
+                plane,
+                party_system,
+                color_cubes.after(party_system),
+                slice::cut_cubes.after(color_cubes),
+                slice::cut_surface.after(plane),
+                slice::slice_plane,
+                fubini::sweep,
+                fubini::sweep_line.after(fubini::sweep),
+            )
+                .in_set(IntegralSet::Visuals),
+        );
+    }
+}
+
+/// Adds the default plugins with a borderless window titled [`LAUNCHER_TITLE`].
+pub struct IntegralWindowPlugin {
+    pub fullscreen: bool,
+}
+
+impl Plugin for IntegralWindowPlugin {
+    fn build(&self, app: &mut App) {
+        let mode = if self.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+
+        let default_plugins = DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                mode,
+                title: LAUNCHER_TITLE.to_string(),
+                fit_canvas_to_parent: true,
+                prevent_default_event_handling: true,
+                present_mode: bevy::window::PresentMode::AutoVsync,
+                decorations: false,
+                ..default()
+            }),
             ..default()
-        }),
-        ..default()
-    });
+        });
+
+        #[cfg(not(feature = "inspect"))]
+        let default_plugins = default_plugins.disable::<LogPlugin>();
+
+        app.add_plugins(default_plugins)
+            .insert_resource(ClearColor(Color::rgb(0.1, 0.1, 0.1)));
+
+        #[cfg(feature = "embedded")]
+        app.add_plugins(EmbeddedAssetPlugin::default());
+    }
+}
+
+/// The keyboard shortcuts, which can be changed in `keybindings.cfg`, and the help overlay
+/// listing them.
+/// They apply to the whole window, so a host app with keys of its own can leave this out.
+pub struct IntegralKeyboardPlugin;
+
+impl Plugin for IntegralKeyboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (input::load_input_map, input::setup_help).chain())
+            .add_systems(
+                Update,
+                input::keyboard_system
+                    .before(apply_actions)
+                    .in_set(IntegralSet::Input),
+            )
+            .add_systems(Update, input::help_overlay.in_set(IntegralSet::Visuals));
+    }
+}
+
+/// Spawns a [`PanOrbitCamera`] looking at the unit square, and a second one for the
+/// [`View::Secondary`] side in split screen.
+pub struct IntegralCameraPlugin;
+
+impl Plugin for IntegralCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(PanOrbitCameraPlugin)
+            .add_systems(Startup, setup_camera)
+            .add_systems(Update, split_viewports.in_set(IntegralSet::Visuals))
+            .add_systems(
+                Update,
+                (touch_camera, panel_blocks_mouse).before(PanOrbitCameraSystemSet),
+            )
+            .add_systems(Update, sync_cameras.after(PanOrbitCameraSystemSet));
+    }
+}
+
+/// Spawns a large dark box around the scene.
+pub struct IntegralSkyboxPlugin;
+
+impl Plugin for IntegralSkyboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_skybox);
+    }
+}
+
+/// The control panel, the intro text, the readout of each side, the
+/// tooltip of clicked cubes, the chart of the slice, the sums of the Fubini animation and
+/// the labels of the axes.
+pub struct IntegralUiPlugin;
+
+impl Plugin for IntegralUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<picking::PickRay>()
+            .init_resource::<picking::Picked>()
+            .add_systems(
+                Startup,
+                (
+                    setup_intro,
+                    setup_readouts,
+                    colors::setup_legend,
+                    picking::setup_tooltip,
+                    slice::setup_chart,
+                    fubini::setup_hud,
+                    controls::setup_panel,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    controls::button_system,
+                    controls::slider_system,
+                    controls::scroll_panel,
+                )
+                    .in_set(IntegralSet::Input),
+            )
+            .add_systems(
+                Update,
+                (
+                    controls::update_labels,
+                    readout,
+                    slice::chart,
+                    grid::axis_labels,
+                    fubini::hud.after(fubini::sweep),
+                    colors::legend.after(color_cubes),
+                    (picking::cursor_ray, picking::pick, picking::tooltip)
+                        .chain()
+                        .after(slice::cut_cubes),
+                    picking::highlight.after(picking::pick).after(color_cubes),
+                )
+                    .in_set(IntegralSet::Visuals),
+            );
+    }
+}
+
+/// The standalone app with all sub-plugins, starting with the [scene file](scene::FILE) if
+/// there is one.
+pub fn app(fullscreen: bool) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        IntegralWindowPlugin { fullscreen },
+        IntegralPlugin::default(),
+        IntegralCameraPlugin,
+        IntegralSkyboxPlugin,
+        IntegralUiPlugin,
+        IntegralKeyboardPlugin,
+    ));
+
+    app.add_systems(PostStartup, scene::load);
 
-    #[cfg(not(feature = "inspect"))]
-    let default_plugins = default_plugins.disable::<LogPlugin>();
-
-    app.add_plugins(default_plugins)
-        .add_plugins(PanOrbitCameraPlugin);
-
-    app.init_resource::<Config>()
-        .insert_resource(ClearColor(Color::rgb(0.1, 0.1, 0.1)))
-        .register_type::<Config>()
-        .add_event::<AddCubes>()
-        .add_event::<DeleteCubes>();
-
-    app.add_systems(Startup, setup)
-        .add_systems(Update, grid)
-        .add_systems(Update, plane)
-        .add_systems(Update, party_system)
-        .add_systems(Update, button_system)
-        .add_systems(Update, change_cubes)
-        .add_systems(Update, delete_cubes.after(change_cubes))
-        .add_systems(Update, add_cubes.after(delete_cubes));
-
-    #[cfg(feature = "embedded")]
-    app.add_plugins(EmbeddedAssetPlugin::default());
     #[cfg(feature = "inspect")]
     app.add_plugins(ResourceInspectorPlugin::<Config>::default());
     #[cfg(feature = "inspect")]
@@ -107,30 +431,70 @@ struct Plane;
 struct Cube {
     size_n: u8,
     prev_n: u8,
+    view: View,
+    rule: Rule,
+    cell: Cell,
+    error: Float,
 }
 
-#[derive(Event)]
-struct DeleteCubes {
-    prev_n: u8,
-    new_n: u8,
+impl Cube {
+    fn value(&self, mode: ColorMode) -> Float {
+        match mode {
+            ColorMode::Level => self.size_n.into(),
+            ColorMode::Height => self.cell.height,
+            ColorMode::Error => self.error,
+            ColorMode::Sign => self.cell.height.signum(),
+        }
+    }
 }
 
-#[derive(Event)]
-struct AddCubes {
-    prev_n: u8,
-    new_n: u8,
-    show_incremental_cubes: bool,
+/// Hides all cubes of `view` finer than level `new_n`.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct DeleteCubes {
+    pub view: View,
+    pub new_n: u8,
 }
 
-#[derive(Resource, Default)]
-struct SpawnedCubes(Vec<(u8, u8)>);
+/// Shows the cubes of `view` for the levels `prev_n + 1..=new_n`, spawning them the first
+/// time. Without `show_incremental_cubes` only level `new_n` is shown, standing on the ground.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct AddCubes {
+    pub view: View,
+    pub rule: Rule,
+    pub prev_n: u8,
+    pub new_n: u8,
+    pub show_incremental_cubes: bool,
+}
 
-type Float = f32;
+#[derive(Resource, Default)]
+struct SpawnedCubes(Vec<(View, Rule, u8, u8)>);
 
-fn f(x: Float, y: Float) -> Float {
+/// The function that is integrated over [`Domain::UNIT`].
+pub fn f(x: Float, y: Float) -> Float {
     x + y
 }
 
+/// The box drawn for `cell`, from its base to its height.
+fn cell_mesh(cell: &Cell) -> (Mesh, Transform) {
+    // with other rules than the lower left one a refinement can also lower the sum
+    let bottom = cell.base.min(cell.height);
+    let mesh = Mesh::from(shape::Box {
+        min_x: 0.,
+        max_x: cell.max_x - cell.min_x,
+        min_y: 0.,
+        max_y: (cell.height - cell.base).abs(),
+        min_z: 0.,
+        max_z: cell.max_y - cell.min_y,
+    });
+    (mesh, Transform::from_xyz(cell.min_x, bottom, cell.min_y))
+}
+
+/// Whether a cube is drawn for `cell` stacked on level `prev_n`, cubes without any height
+/// are left out.
+pub(crate) fn is_drawn(cell: &Cell, prev_n: u8) -> bool {
+    !(cell.height.abs() < 1e-8 && prev_n == 0 || cell.base == cell.height)
+}
+
 fn add_cubes(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -139,69 +503,53 @@ fn add_cubes(
     mut spawned: Local<SpawnedCubes>,
     mut cubes: Query<(&Cube, &mut Visibility)>,
 ) {
-    let mut run = |n: u8, prev_n: u8| {
-        if spawned.0.contains(&(n, prev_n)) {
+    let mut run = |view: View, rule: Rule, n: u8, prev_n: u8| {
+        if spawned.0.contains(&(view, rule, n, prev_n)) {
             for (cube, mut vis) in &mut cubes {
-                if cube.size_n == n && cube.prev_n == prev_n {
+                if cube.view == view
+                    && cube.rule == rule
+                    && cube.size_n == n
+                    && cube.prev_n == prev_n
+                {
                     *vis = Visibility::Visible;
                 }
             }
             return;
         }
-        let pow2n = 2i32.pow(n.into());
-        let size = Float::powi(2.0, -(n as i32));
-        let prev_size = if prev_n == 0 {
-            0.0
-        } else {
-            Float::powi(2.0, -(prev_n as i32))
-        };
+        spawned.0.push((view, rule, n, prev_n));
 
-        spawned.0.push((n, prev_n));
-
-        for i in 0..pow2n {
-            for j in 0..pow2n {
-                let x = size * i as Float;
-                let y = size * j as Float;
-                let previous_height = f(
-                    prev_size * (i / 2i32.pow((n - prev_n).into())) as Float,
-                    prev_size * (j / 2i32.pow((n - prev_n).into())) as Float,
-                );
-                let this_height = f(x, y);
-                if this_height < 1e-8 || previous_height == this_height {
-                    continue;
-                }
-                assert!(
-                    previous_height <= this_height,
-                    "n={n}, i={i}, j={j}, prev={previous_height}, this={this_height}"
-                );
-
-                commands.spawn((
-                    Cube { size_n: n, prev_n },
-                    PbrBundle {
-                        mesh: meshes.add(Mesh::from(shape::Box {
-                            min_x: 0.,
-                            max_x: size,
-                            min_y: 0.,
-                            max_y: this_height - previous_height,
-                            min_z: 0.,
-                            max_z: size,
-                        })),
-                        transform: Transform::from_xyz(x, previous_height, y),
-                        material: materials
-                            .add(Color::rgb_u8(124, (40 * n.min(6)).min(255), 255 / n).into()),
-                        ..default()
-                    },
-                ));
+        for cell in core::level_cells(&f, &Domain::UNIT, n, prev_n, rule) {
+            if !is_drawn(&cell, prev_n) {
+                continue;
             }
+            let (mesh, transform) = cell_mesh(&cell);
+            commands.spawn((
+                Cube {
+                    size_n: n,
+                    prev_n,
+                    view,
+                    rule,
+                    error: cell.error(&f),
+                    cell,
+                },
+                PbrBundle {
+                    mesh: meshes.add(mesh),
+                    transform,
+                    // colored by `color_cubes`
+                    material: materials.add(StandardMaterial::default()),
+                    ..default()
+                },
+                view.layers(),
+            ));
         }
     };
     for ev in er.read() {
         if ev.show_incremental_cubes {
             for n in (ev.prev_n + 1)..=ev.new_n {
-                run(n, n - 1);
+                run(ev.view, ev.rule, n, n - 1);
             }
         } else {
-            run(ev.new_n, 0);
+            run(ev.view, ev.rule, ev.new_n, 0);
         }
     }
 }
@@ -213,7 +561,7 @@ fn delete_cubes(
 ) {
     for ev in er.read() {
         for (/*id,*/ cube, mut vis) in &mut query {
-            if ev.new_n < cube.size_n {
+            if cube.view == ev.view && ev.new_n < cube.size_n {
                 *vis = Visibility::Hidden;
                 // commands.entity(id).despawn_recursive();
             }
@@ -263,15 +611,7 @@ fn plane(
         use bevy::render::mesh::Indices;
         use bevy::render::render_resource::PrimitiveTopology;
         let mesh = Mesh::new(PrimitiveTopology::TriangleList)
-            .with_inserted_attribute(
-                Mesh::ATTRIBUTE_POSITION,
-                vec![
-                    [0.0, 0.0, 0.0],
-                    [1.0, 1.0, 0.0],
-                    [0.0, 1.0, 1.0],
-                    [1.0, 2.0, 1.0],
-                ],
-            )
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, slice::surface(&Domain::UNIT))
             .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 4])
             .with_indices(Some(Indices::U32(vec![
                 0, 2, 1, // front lower
@@ -287,212 +627,448 @@ fn plane(
                 ..default()
             },
             Plane,
+            RenderLayers::all(),
         ));
     }
 }
 
-fn change_cubes(
+/// Fits the [`ColorScale`] to the visible cubes and colors all cubes with it.
+fn color_cubes(
     config: Res<Config>,
-    mut last_config: Local<Option<Config>>,
-    mut add: EventWriter<AddCubes>,
-    mut delete: EventWriter<DeleteCubes>,
+    mut scale: ResMut<ColorScale>,
+    cubes: Query<(&Cube, &Visibility, &Handle<StandardMaterial>)>,
+    changed: Query<(), (With<Cube>, Changed<Visibility>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    if config.is_changed() {
-        if let Some(last_config) = &*last_config {
-            // TODO remove this
-            delete.send(DeleteCubes {
-                prev_n: last_config.n,
-                new_n: 0,
-            });
-
-            add.send(AddCubes {
-                prev_n: 0,
-                new_n: config.n,
-                show_incremental_cubes: config.show_incremental_cubes,
-            });
-            /*
-            if config.n > last_config.n {
-                if !config.show_incremental_cubes {
-                    delete.send(DeleteCubes {
-                        prev_n: last_config.n,
-                        new_n: config.n - 1,
-                    });
-                }
-                add.send(AddCubes {
-                    prev_n: last_config.n,
-                    new_n: config.n,
-                    show_incremental_cubes: config.show_incremental_cubes,
-                });
-            } else if config.n < last_config.n {
-                delete.send(DeleteCubes {
-                    prev_n: last_config.n,
-                    new_n: config.n,
-                });
-            } else if config.show_incremental_cubes
-                && !last_config.show_incremental_cubes
-                && config.n > 0
-            {
-                add.send(AddCubes {
-                    prev_n: 0,
-                    new_n: config.n,
-                    show_incremental_cubes: config.show_incremental_cubes,
-                });
-            } else if !config.show_incremental_cubes
-                && last_config.show_incremental_cubes
-                && last_config.n > 0
-            {
-                delete.send(DeleteCubes {
-                    prev_n: last_config.n,
-                    new_n: config.n - 1,
-                });
-                add.send(AddCubes {
-                    prev_n: 0,
-                    new_n: config.n,
-                    show_incremental_cubes: config.show_incremental_cubes,
-                });
-            }*/
-        } else {
-            add.send(AddCubes {
-                prev_n: 0,
-                new_n: config.n,
-                show_incremental_cubes: config.show_incremental_cubes,
-            })
+    // the party has colors of its own
+    if config.show_party || (!config.is_changed() && changed.is_empty()) {
+        return;
+    }
+    let values = cubes
+        .iter()
+        .filter(|(_, vis, _)| **vis != Visibility::Hidden)
+        .map(|(cube, _, _)| cube.value(config.color_mode));
+    let (min, max) = match config.color_mode {
+        ColorMode::Level => (1., values.fold(MAX_N.into(), Float::max)),
+        ColorMode::Height => values.fold((Float::INFINITY, Float::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        }),
+        ColorMode::Error => {
+            let max = values.map(Float::abs).fold(0., Float::max);
+            (-max, max)
+        }
+        ColorMode::Sign => (-1., 1.),
+    };
+    let (min, max) = if min <= max { (min, max) } else { (0., 1.) };
+    scale.set_if_neq(ColorScale {
+        mode: config.color_mode,
+        palette: config.palette,
+        min,
+        max,
+    });
+    for (cube, _, material) in &cubes {
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = scale.color(cube.value(config.color_mode));
         }
-        *last_config = Some((*config).clone());
     }
 }
 
-fn grid(mut gizmos: Gizmos, orbit_cameras: Query<&PanOrbitCamera>, config: Res<Config>) {
-    let pan_cam = orbit_cameras.get_single().unwrap();
-    let target_radius = pan_cam.target_radius;
-
-    let x_axis_color = Color::rgb(1.0, 0.2, 0.2);
-    let y_axis_color = Color::rgb(0.2, 1.0, 0.2);
-    let z_axis_color = Color::rgb(0.2, 0.2, 1.0);
-    let minor_line_color = Color::rgba(0.01, 0.01, 0.01, 0.01);
-    let major_line_color = Color::rgba(0.25, 0.25, 0.25, 0.5);
-
-    let fadeout_distance = (target_radius / 2.).clamp(1.0, 3.0).round();
-    let fadeout_distance_int = fadeout_distance as i32;
-
-    let minor_per_major = 6. / target_radius.sqrt();
-    let minor_per_major = minor_per_major.round() as i32;
-
-    // major axis
-    for (axis, color) in [
-        (Vec3::X, x_axis_color),
-        (Vec3::Y, y_axis_color),
-        (Vec3::Z, z_axis_color),
-    ] {
-        // gizmos.ray(Vec3::ZERO, axis * 2. * fadeout_distance, color);
-        gizmos.ray(Vec3::ZERO, axis * fadeout_distance.ceil(), color);
+fn follow_config(config: Res<Config>, mut sides: Query<(&View, &mut Side)>) {
+    if !config.is_changed() {
+        return;
     }
+    for (view, mut side) in &mut sides {
+        if *view == View::Primary {
+            side.set_if_neq(Side::from(&*config));
+        }
+    }
+}
 
-    if config.show_full_grid {
-        let directions = [
-            |x, y| (Vec3 { x, y, z: 0. }, Vec3::Z),
-            |x, z| (Vec3 { x, y: 0., z }, Vec3::Y),
-            |y, z| (Vec3 { x: 0., y, z }, Vec3::X),
-        ];
-        for direction in directions {
-            for a in 0..=(fadeout_distance_int * minor_per_major) {
-                for b in 0..=(fadeout_distance_int * minor_per_major) {
-                    let color = if a % minor_per_major == 0 || b % minor_per_major == 0 {
-                        major_line_color
-                    } else {
-                        minor_line_color
-                    };
-                    let (offset, dir) = direction(
-                        a as f32 / minor_per_major as f32,
-                        b as f32 / minor_per_major as f32,
-                    );
-                    gizmos.ray(offset, fadeout_distance * dir, color);
-                }
+fn change_cubes(
+    config: Res<Config>,
+    sides: Query<(&View, Ref<Side>)>,
+    mut shown: Local<Vec<View>>,
+    mut add: EventWriter<AddCubes>,
+    mut delete: EventWriter<DeleteCubes>,
+) {
+    for (&view, side) in &sides {
+        // the secondary side only has cubes in split screen, the primary one makes room for
+        // the slabs of the Fubini animation
+        let hidden = match view {
+            View::Primary => config.fubini,
+            View::Secondary => !config.split_screen,
+        };
+        if hidden {
+            if let Some(idx) = shown.iter().position(|v| *v == view) {
+                shown.remove(idx);
+                delete.send(DeleteCubes { view, new_n: 0 });
             }
+            continue;
         }
-    } else {
-        let directions = [
-            (Vec3::X, [Vec3::Y, Vec3::Z]),
-            (Vec3::Y, [Vec3::Z, Vec3::X]),
-            (Vec3::Z, [Vec3::X, Vec3::Y]),
-        ];
-        for (main, dirs) in directions {
-            for dir in dirs {
-                for a in 0..=(fadeout_distance_int * minor_per_major) {
-                    let color = if a % minor_per_major == 0 {
-                        major_line_color
-                    } else {
-                        minor_line_color
-                    };
-                    gizmos.ray(
-                        main * a as f32 / minor_per_major as f32,
-                        fadeout_distance * dir,
-                        color,
-                    );
-                }
+        if shown.contains(&view) {
+            if !side.is_changed() {
+                continue;
             }
+            // TODO remove this
+            delete.send(DeleteCubes { view, new_n: 0 });
+        } else {
+            shown.push(view);
         }
+        add.send(AddCubes {
+            view,
+            rule: side.rule,
+            prev_n: 0,
+            new_n: side.n,
+            show_incremental_cubes: side.show_incremental_cubes,
+        });
     }
 }
 
 // const SCALE: Float = 4.;
 
-fn setup(
-    mut commands: Commands,
-    mut gizmo: ResMut<GizmoConfig>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    // sky
-    commands.spawn(PbrBundle {
-        mesh: meshes.add(Mesh::from(shape::Box::default())),
-        material: materials.add(StandardMaterial {
-            base_color: Color::hex("080808").unwrap(),
-            unlit: true,
-            cull_mode: None,
-            ..default()
-        }),
-        transform: Transform::from_scale(Vec3::splat(1_000_000.0)),
+fn default_camera() -> PanOrbitCamera {
+    PanOrbitCamera {
+        focus: Vec3::new(0.8, 1.5, 0.7),
+        target_focus: Vec3::new(0.8, 1.5, 0.7),
+        alpha: Some(-1.5),
+        target_alpha: -1.5,
+        beta: Some(0.2),
+        target_beta: 0.2,
+        radius: Some(5.5),
+        target_radius: 5.5,
+        scale: Some(1.0),
+        initialized: false,
         ..default()
-    });
+    }
+}
+
+fn setup(mut commands: Commands, mut gizmo: ResMut<GizmoConfig>) {
     // light
-    commands.spawn(PointLightBundle {
-        point_light: PointLight {
-            intensity: 1500.0,
-            shadows_enabled: true,
+    commands.spawn((
+        PointLightBundle {
+            point_light: PointLight {
+                intensity: 1500.0,
+                shadows_enabled: true,
+                ..default()
+            },
+            transform: Transform::from_xyz(4.0, 8.0, 4.0),
             ..default()
         },
-        transform: Transform::from_xyz(4.0, 8.0, 4.0),
-        ..default()
-    });
-    // camera
+        RenderLayers::all(),
+    ));
+    // set grid line width
+    gizmo.line_width = 0.5;
+    gizmo.render_layers = RenderLayers::all();
+}
+
+fn setup_skybox(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
     commands.spawn((
-        Camera3dBundle {
-            camera: Camera {
-                hdr: true, // 1. HDR is required for bloom
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::default())),
+            material: materials.add(StandardMaterial {
+                base_color: Color::hex("080808").unwrap(),
+                unlit: true,
+                cull_mode: None,
                 ..default()
-            },
-            transform: Transform::from_xyz(-4.5, 2., 1.0)
-                .looking_at(Vec3::new(2., 2., 2.), Vec3::Y),
-            tonemapping: Tonemapping::TonyMcMapface,
+            }),
+            transform: Transform::from_scale(Vec3::splat(1_000_000.0)),
             ..default()
         },
-        PanOrbitCamera {
-            focus: Vec3::new(0.8, 1.5, 0.7),
-            target_focus: Vec3::new(0.8, 1.5, 0.7),
-            alpha: Some(-1.5),
-            target_alpha: -1.5,
-            beta: Some(0.2),
-            target_beta: 0.2,
-            radius: Some(5.5),
-            target_radius: 5.5,
-            scale: Some(1.0),
-            initialized: false,
+        RenderLayers::all(),
+    ));
+}
+
+fn camera_bundle(view: View) -> Camera3dBundle {
+    Camera3dBundle {
+        camera: Camera {
+            hdr: true, // 1. HDR is required for bloom
+            order: view as isize,
             ..default()
         },
+        transform: Transform::from_xyz(-4.5, 2., 1.0).looking_at(Vec3::new(2., 2., 2.), Vec3::Y),
+        tonemapping: Tonemapping::TonyMcMapface,
+        ..default()
+    }
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn((
+        camera_bundle(View::Primary),
+        default_camera(),
+        View::Primary,
     ));
+}
+
+/// Splits the window in split screen and spawns or despawns the secondary camera, which
+/// starts where the primary one is.
+fn split_viewports(
+    mut commands: Commands,
+    config: Res<Config>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(Entity, &mut Camera, &PanOrbitCamera, &View)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let size = UVec2::new(window.physical_width(), window.physical_height());
+    let half = UVec2::new(size.x / 2, size.y);
+    let mut primary = None;
+    let mut secondary = None;
+    for (entity, mut camera, orbit, view) in &mut cameras {
+        let viewport = match (view, config.split_screen) {
+            (View::Primary, false) => None,
+            (View::Primary, true) => Some(Viewport {
+                physical_position: UVec2::ZERO,
+                physical_size: half,
+                ..default()
+            }),
+            (View::Secondary, true) => Some(Viewport {
+                physical_position: UVec2::new(half.x, 0),
+                physical_size: UVec2::new(size.x - half.x, size.y),
+                ..default()
+            }),
+            (View::Secondary, false) => {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+        };
+        let rect = |viewport: &Option<Viewport>| {
+            viewport
+                .as_ref()
+                .map(|v| (v.physical_position, v.physical_size))
+        };
+        if rect(&camera.viewport) != rect(&viewport) {
+            camera.viewport = viewport;
+        }
+        match view {
+            View::Primary => primary = Some(*orbit),
+            View::Secondary => secondary = Some(entity),
+        }
+    }
+    if let (true, Some(orbit), None) = (config.split_screen, primary, secondary) {
+        commands.spawn((
+            camera_bundle(View::Secondary),
+            PanOrbitCamera {
+                initialized: false,
+                ..orbit
+            },
+            View::Secondary,
+            View::Secondary.layers(),
+            // the panel is drawn once, by the primary camera
+            UiCameraConfig { show_ui: false },
+        ));
+    }
+}
+
+/// Copies the targets of the camera the user moves to the other one.
+fn sync_cameras(
+    config: Res<Config>,
+    active: Res<ActiveCameraData>,
+    mut cameras: Query<&mut PanOrbitCamera>,
+) {
+    if !config.split_screen || !config.sync_cameras {
+        return;
+    }
+    let Some(leader) = active.entity.and_then(|entity| cameras.get(entity).ok()) else {
+        return;
+    };
+    let (focus, alpha, beta, radius) = (
+        leader.target_focus,
+        leader.target_alpha,
+        leader.target_beta,
+        leader.target_radius,
+    );
+    for mut camera in &mut cameras {
+        camera.target_focus = focus;
+        camera.target_alpha = alpha;
+        camera.target_beta = beta;
+        camera.target_radius = radius;
+    }
+}
+
+/// Keeps the mouse from moving the cameras while it is over the control panel, so the wheel
+/// scrolls the panel and the sliders can be dragged. A drag that started in the scene goes
+/// on over the panel.
+fn panel_blocks_mouse(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mouse: Res<Input<MouseButton>>,
+    panel: Query<(&Node, &GlobalTransform), With<ControlPanel>>,
+    ui_scale: Res<UiScale>,
+    mut cameras: Query<&mut PanOrbitCamera>,
+) {
+    if mouse.get_pressed().next().is_some() {
+        return;
+    }
+    let over = windows
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+        .is_some_and(|position| {
+            panel.iter().any(|(node, transform)| {
+                ControlPanel::contains(node, transform, &ui_scale, position)
+            })
+        });
+    for mut camera in &mut cameras {
+        if camera.enabled == over {
+            camera.enabled = !over;
+        }
+    }
+}
+
+/// The fingers on the screen, kept by `touch_camera` between frames.
+#[derive(Default)]
+struct TouchGesture {
+    /// The camera the gesture moves, chosen where the first finger lands.
+    camera: Option<Entity>,
+    /// Where each finger was in the last frame. [`Touch::previous_position`] only changes
+    /// when a finger moves, so it can't tell a finger that stopped.
+    last: HashMap<u64, Vec2>,
+    /// Fingers that started on the control panel and belong to its buttons.
+    ignored: HashSet<u64>,
+    /// A second finger touched; the remaining one doesn't orbit until all are lifted.
+    pinched: bool,
+}
+
+/// Moves a [`PanOrbitCamera`] with the fingers: one finger orbits, two fingers pan and
+/// pinch to zoom. Only the targets are set, so touches are smoothed like the mouse.
+fn touch_camera(
+    touches: Res<Touches>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    panel: Query<(&Node, &GlobalTransform), With<ControlPanel>>,
+    ui_scale: Res<UiScale>,
+    mut active: ResMut<ActiveCameraData>,
+    mut cameras: Query<(
+        Entity,
+        &Camera,
+        &Transform,
+        &Projection,
+        &mut PanOrbitCamera,
+        &View,
+    )>,
+    mut gesture: Local<TouchGesture>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    for touch in touches.iter_just_pressed() {
+        if panel.iter().any(|(node, transform)| {
+            ControlPanel::contains(node, transform, &ui_scale, touch.start_position())
+        }) {
+            gesture.ignored.insert(touch.id());
+        }
+    }
+    gesture
+        .ignored
+        .retain(|id| touches.get_pressed(*id).is_some());
+    let fingers: Vec<(u64, Vec2)> = touches
+        .iter()
+        .filter(|touch| !gesture.ignored.contains(&touch.id()))
+        .map(|touch| (touch.id(), touch.position()))
+        .collect();
+    let Some(&(_, first)) = fingers.first() else {
+        gesture.camera = None;
+        gesture.last.clear();
+        gesture.pinched = false;
+        return;
+    };
+
+    let started = gesture.camera.is_none();
+    if started {
+        // the camera of the viewport below the finger, the primary one if that's unknown
+        let below = cameras
+            .iter()
+            .filter(|(_, camera, ..)| {
+                camera
+                    .logical_viewport_rect()
+                    .is_some_and(|rect| rect.contains(first))
+            })
+            .max_by_key(|(_, camera, ..)| camera.order)
+            .or_else(|| cameras.iter().find(|(.., view)| **view == View::Primary));
+        let Some((entity, camera, ..)) = below else {
+            return;
+        };
+        gesture.camera = Some(entity);
+        // lets `sync_cameras` follow the touched camera
+        active.set_if_neq(ActiveCameraData {
+            entity: Some(entity),
+            viewport_size: camera.logical_viewport_size(),
+            window_size: Some(Vec2::new(window.width(), window.height())),
+            manual: false,
+        });
+    }
+    let Some(Ok((_, camera, transform, projection, mut orbit, _))) =
+        gesture.camera.map(|entity| cameras.get_mut(entity))
+    else {
+        return;
+    };
+    if started {
+        // like the mouse, decide once per gesture which way is left
+        let wrapped_beta = (orbit.target_beta % TAU).abs();
+        orbit.is_upside_down = wrapped_beta > TAU / 4.0 && wrapped_beta < 3.0 * TAU / 4.0;
+    }
+
+    let last = std::mem::take(&mut gesture.last);
+    let moved = |id: u64, position: Vec2| last.get(&id).map(|last| position - *last);
+    match fingers[..] {
+        [(id, position)] if !gesture.pinched => {
+            if let Some(delta) = moved(id, position) {
+                // the same angles per pixel as the mouse
+                let delta =
+                    delta * orbit.orbit_sensitivity / Vec2::new(window.width(), window.height());
+                let delta_alpha = delta.x * TAU;
+                orbit.target_alpha += if orbit.is_upside_down {
+                    delta_alpha
+                } else {
+                    -delta_alpha
+                };
+                orbit.target_beta += delta.y * PI;
+            }
+        }
+        [(id_a, a), (id_b, b)] => {
+            gesture.pinched = true;
+            if let (Some(delta_a), Some(delta_b)) = (moved(id_a, a), moved(id_b, b)) {
+                let viewport = camera
+                    .logical_viewport_size()
+                    .unwrap_or(Vec2::new(window.width(), window.height()));
+                let mut pan = (delta_a + delta_b) / 2. * orbit.pan_sensitivity;
+                let mut multiplier = 1.0;
+                match projection {
+                    Projection::Perspective(p) => {
+                        pan *= Vec2::new(p.fov * p.aspect_ratio, p.fov) / viewport;
+                        multiplier = orbit.radius.unwrap_or(orbit.target_radius);
+                    }
+                    Projection::Orthographic(p) => {
+                        pan *= p.area.size() / viewport;
+                    }
+                }
+                let right = transform.rotation * Vec3::X * -pan.x;
+                let up = transform.rotation * Vec3::Y * pan.y;
+                orbit.target_focus += (right + up) * multiplier;
+
+                let before = (a - delta_a).distance(b - delta_b);
+                let after = a.distance(b);
+                if before > 0. && after > 0. {
+                    // within the limits of the camera, `PanOrbitCamera` never gets closer than
+                    // 0.05 either
+                    orbit.target_radius = (orbit.target_radius * before / after)
+                        .min(orbit.zoom_upper_limit.unwrap_or(f32::INFINITY))
+                        .max(orbit.zoom_lower_limit.unwrap_or(0.05));
+                }
+            }
+        }
+        // more fingers are left to the system
+        _ => {}
+    }
+    gesture.last = fingers.into_iter().collect();
+}
+
+fn setup_intro(mut commands: Commands) {
     commands.spawn(
-        TextBundle::from_section("Nutze deine Maus, um die Kamera zu bewegen. linke Maus - drehen | rechte Maus - bewegen | zoom Maus - nicht zoomen\nFalls du ein Mensch bist und noch keine Maus gefangen hast, Pech gehabt!",
+        TextBundle::from_section("Nutze deine Maus, um die Kamera zu bewegen. linke Maus - drehen | rechte Maus - bewegen | zoom Maus - nicht zoomen\nAuf dem Tablet: ein Finger - drehen | zwei Finger - bewegen und zoomen\nFalls du ein Mensch bist und noch keine Maus gefangen hast, Pech gehabt! Tastenkürzel gibt es mit H.",
             TextStyle { font_size: 16., ..default() }).with_style(
             Style {
                 position_type: PositionType::Absolute,
@@ -502,233 +1078,398 @@ fn setup(
             },
         ),
     );
-    commands
-        .spawn(NodeBundle {
-            style: Style {
-                left: Val::Px(25.0),
-                width: Val::Percent(20.0),
-                height: Val::Percent(100.0),
-                justify_content: JustifyContent::Center,
-                //align_self: AlignSelf::Center,
-                // align_items: AlignItems::Center,
-                flex_direction: FlexDirection::Column,
-                ..default()
-            },
-            ..default()
-        })
-        .with_children(|parent| {
-            let button = || ButtonBundle {
-                style: Style {
-                    width: Val::Px(200.0),
-                    height: Val::Px(65.0),
-                    border: UiRect::all(Val::Px(5.0)),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    padding: UiRect::all(Val::Px(5.0)),
-                    margin: UiRect::vertical(Val::Px(5.0)),
+}
+
+#[derive(Component)]
+struct Readout(View);
+
+fn setup_readouts(mut commands: Commands) {
+    for view in View::ALL {
+        let left = match view {
+            View::Primary => Val::Percent(0.0),
+            View::Secondary => Val::Percent(50.0),
+        };
+        commands.spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 20.,
                     ..default()
                 },
-                border_color: BorderColor(Color::BLACK),
-                background_color: NORMAL_BUTTON.into(),
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(25.0),
+                left,
+                margin: UiRect::left(Val::Px(25.0)),
                 ..default()
-            };
-            let text_child = |s: String| {
-                move |parent: &mut ChildBuilder| {
-                    parent.spawn(TextBundle::from_section(
-                        s,
-                        TextStyle {
-                            font_size: 20.0,
-                            color: Color::rgb(0.9, 0.9, 0.9),
-                            ..default()
-                        },
-                    ));
-                }
-            };
-            parent
-                .spawn(NodeBundle {
-                    style: Style {
-                        width: Val::Px(200.0),
-                        height: Val::Px(65.0),
-                        border: UiRect::all(Val::Px(5.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        padding: UiRect::all(Val::Px(5.0)),
-                        margin: UiRect::vertical(Val::Px(5.0)),
-                        ..default()
-                    },
-                    border_color: BorderColor(Color::BLACK),
-                    background_color: NORMAL_BUTTON.into(),
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn((
-                        TextBundle::from_section(
-                            format!("n: {}", Config::default().n),
-                            TextStyle {
-                                font_size: 22.0,
-                                color: Color::rgb(0.9, 0.9, 0.9),
-                                ..default()
-                            },
-                        ),
-                        NText,
-                    ));
-                });
-
-            parent
-                .spawn((button(), ConfigMore))
-                .with_children(text_child(String::from("Meeehr")));
-            parent
-                .spawn((button(), ConfigLess))
-                .with_children(text_child(String::from("(weniger)")));
-            parent
-                .spawn((button(), ConfigFunctionGraph))
-                .with_children(text_child(format!("{} :)", SHOW_FUN)));
-            parent
-                .spawn((button(), ConfigIncremental))
-                .with_children(text_child(format!("{} :)", SHOW_INC)));
-            parent
-                .spawn((button(), ConfigCoord))
-                .with_children(text_child(format!("{} :)", SHOW_COORD)));
-            parent
-                .spawn((button(), ConfigParty))
-                .with_children(text_child(format!("{}", SHOW_PARTY)));
-        });
+            }),
+            Readout(view),
+        ));
+    }
+}
 
-    // set grid line width
-    gizmo.line_width = 0.5;
+/// German names of [`Rule::ALL`].
+const RULES: [&str; 5] = [
+    "links unten",
+    "rechts unten",
+    "links oben",
+    "rechts oben",
+    "Mittelpunkt",
+];
+
+fn rule_index(rule: Rule) -> usize {
+    Rule::ALL.iter().position(|r| *r == rule).unwrap()
+}
+
+fn readout(
+    config: Res<Config>,
+    sides: Query<(&View, Ref<Side>)>,
+    mut readouts: Query<(&mut Text, &mut Style, &Readout)>,
+    mut exact: Local<Option<Float>>,
+) {
+    let exact = *exact.get_or_insert_with(|| core::integrate(&f, &Domain::UNIT));
+    for (view, side) in &sides {
+        if !side.is_changed() && !config.is_changed() {
+            continue;
+        }
+        for (mut text, mut style, readout) in &mut readouts {
+            if readout.0 != *view {
+                continue;
+            }
+            // outside of split screen only the primary side is shown
+            let display = if *view == View::Primary || config.split_screen {
+                Display::Flex
+            } else {
+                Display::None
+            };
+            if style.display != display {
+                style.display = display;
+            }
+            let sum = core::riemann(&f, &Domain::UNIT, &Partition::dyadic(side.n), side.rule).sum;
+            text.sections[0].value = format!(
+                "n = {}, {}\nSumme: {sum:.5}\nFehler: {:+.5}",
+                side.n,
+                RULES[rule_index(side.rule)],
+                sum - exact
+            );
+        }
+    }
 }
 
 const SHOW_FUN: &str = "Zeige Funktionsgraph";
 const SHOW_INC: &str = "Zeige Zwischendinge";
 const SHOW_COORD: &str = "Zeige alle Koordinaten";
 const SHOW_PARTY: &str = "party? :o";
+const EXPORT: &str = "Exportieren";
+const AUTOPLAY: &str = "Automatisch abspielen";
+const COMPARE: &str = "Vergleichen";
+const SLICE: &str = "Schnitt zeigen";
+const BOOKMARK: &str = "Ansicht merken";
+const NEXT_BOOKMARK: &str = "Nächste gemerkte Ansicht";
 
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+const MAX_N: u8 = 6;
 
-#[derive(Component)]
-struct ConfigMore;
-#[derive(Component)]
-struct ConfigLess;
-#[derive(Component)]
-struct ConfigFunctionGraph;
-#[derive(Component)]
-struct ConfigIncremental;
-#[derive(Component)]
-struct ConfigCoord;
-#[derive(Component)]
-struct ConfigParty;
-#[derive(Component)]
-struct NText;
-
-fn button_system(
-    mut interaction_query: Query<
-        (
-            &Interaction,
-            &mut BackgroundColor,
-            &mut BorderColor,
-            &Children,
-            (
-                Option<&ConfigMore>,
-                Option<&ConfigLess>,
-                Option<&ConfigFunctionGraph>,
-                Option<&ConfigIncremental>,
-                Option<&ConfigCoord>,
-                Option<&ConfigParty>,
+/// Registers all controls, the ones of the right side of the comparison bind to `secondary`.
+fn register_controls(app: &mut App, secondary: Entity) {
+    app.register_control(Control::new(
+        "n",
+        "n",
+        ControlKind::Stepper {
+            binding: Binding::resource::<Config>(|c| c.n.into(), |c, n| c.n = n as u8),
+            min: 0,
+            max: MAX_N.into(),
+            more: "Meeehr",
+            less: "(weniger)",
+        },
+    ))
+    .register_control(Control::new(
+        "function",
+        SHOW_FUN,
+        ControlKind::Toggle(Binding::resource::<Config>(
+            |c| c.show_function,
+            |c, v| c.show_function = v,
+        )),
+    ))
+    .register_control(Control::new(
+        "incremental",
+        SHOW_INC,
+        ControlKind::Toggle(Binding::resource::<Config>(
+            |c| c.show_incremental_cubes,
+            |c, v| c.show_incremental_cubes = v,
+        )),
+    ))
+    .register_control(Control::new(
+        "grid",
+        SHOW_COORD,
+        ControlKind::Toggle(Binding::resource::<Config>(
+            |c| c.show_full_grid,
+            |c, v| c.show_full_grid = v,
+        )),
+    ))
+    .register_control(Control::new(
+        "party",
+        SHOW_PARTY,
+        ControlKind::Toggle(Binding::resource::<Config>(
+            |c| c.show_party,
+            |c, v| c.show_party = v,
+        )),
+    ))
+    .register_control(Control::new(
+        "autoplay",
+        AUTOPLAY,
+        ControlKind::Toggle(Binding::resource::<Config>(
+            |c| c.autoplay,
+            |c, v| c.autoplay = v,
+        )),
+    ))
+    .register_control(Control::new(
+        "autoplay_seconds",
+        "Sekunden",
+        ControlKind::Slider {
+            binding: Binding::resource::<Config>(
+                |c| c.autoplay_seconds,
+                |c, v| c.autoplay_seconds = v,
             ),
-        ),
-        (Changed<Interaction>, With<Button>),
-    >,
-    mut text_query: Query<&mut Text, Without<NText>>,
-    mut n_text_query: Query<&mut Text, With<NText>>,
+            min: 0.2,
+            max: 5.0,
+        },
+    ))
+    .register_control(Control::new(
+        "rule",
+        "Regel",
+        ControlKind::Choice {
+            binding: Binding::resource::<Config>(
+                |c| rule_index(c.rule),
+                |c, idx| c.rule = Rule::ALL[idx],
+            ),
+            options: RULES.to_vec(),
+        },
+    ))
+    .register_control(Control::new(
+        "compare",
+        COMPARE,
+        ControlKind::Toggle(Binding::resource::<Config>(
+            |c| c.split_screen,
+            |c, v| c.split_screen = v,
+        )),
+    ))
+    .register_control(Control::new(
+        "sync_cameras",
+        "Kameras koppeln",
+        ControlKind::Toggle(Binding::resource::<Config>(
+            |c| c.sync_cameras,
+            |c, v| c.sync_cameras = v,
+        )),
+    ))
+    .register_control(Control::new(
+        "compare_n",
+        "n rechts",
+        ControlKind::Stepper {
+            binding: Binding::component::<Side>(secondary, |s| s.n.into(), |s, n| s.n = n as u8),
+            min: 0,
+            max: MAX_N.into(),
+            more: "Meeehr",
+            less: "(weniger)",
+        },
+    ))
+    .register_control(Control::new(
+        "compare_rule",
+        "Regel rechts",
+        ControlKind::Choice {
+            binding: Binding::component::<Side>(
+                secondary,
+                |s| rule_index(s.rule),
+                |s, idx| s.rule = Rule::ALL[idx],
+            ),
+            options: RULES.to_vec(),
+        },
+    ))
+    .register_control(Control::new(
+        "color_mode",
+        "Farbe",
+        ControlKind::Choice {
+            binding: Binding::resource::<Config>(
+                |c| {
+                    ColorMode::ALL
+                        .iter()
+                        .position(|m| *m == c.color_mode)
+                        .unwrap()
+                },
+                |c, idx| c.color_mode = ColorMode::ALL[idx],
+            ),
+            options: ColorMode::ALL.map(ColorMode::name).to_vec(),
+        },
+    ))
+    .register_control(Control::new(
+        "palette",
+        "Palette",
+        ControlKind::Choice {
+            binding: Binding::resource::<Config>(
+                |c| Palette::ALL.iter().position(|p| *p == c.palette).unwrap(),
+                |c, idx| c.palette = Palette::ALL[idx],
+            ),
+            options: Palette::ALL.map(Palette::name).to_vec(),
+        },
+    ))
+    .register_control(Control::new(
+        "slice",
+        SLICE,
+        ControlKind::Toggle(Binding::resource::<Config>(
+            |c| c.show_slice,
+            |c, v| c.show_slice = v,
+        )),
+    ))
+    .register_control(Control::new(
+        "slice_axis",
+        "Schnitt bei",
+        ControlKind::Choice {
+            binding: Binding::resource::<Config>(
+                |c| slice::axis_index(c.slice_axis),
+                |c, idx| c.slice_axis = Axis::ALL[idx],
+            ),
+            options: slice::AXES.to_vec(),
+        },
+    ))
+    .register_control(Control::new(
+        "slice_position",
+        "c",
+        ControlKind::Slider {
+            binding: Binding::resource::<Config>(|c| c.slice_position, |c, v| c.slice_position = v),
+            min: 0.0,
+            max: 1.0,
+        },
+    ))
+    .register_control(Control::new(
+        "fubini",
+        "Fubini-Animation",
+        ControlKind::Toggle(Binding::resource::<Config>(
+            |c| c.fubini,
+            |c, v| c.fubini = v,
+        )),
+    ))
+    .register_control(Control::new(
+        "fubini_order",
+        "Reihenfolge",
+        ControlKind::Choice {
+            binding: Binding::resource::<Config>(
+                |c| {
+                    Order::ALL
+                        .iter()
+                        .position(|o| *o == c.fubini_order)
+                        .unwrap()
+                },
+                |c, idx| c.fubini_order = Order::ALL[idx],
+            ),
+            options: Order::ALL.map(Order::name).to_vec(),
+        },
+    ))
+    .register_control(Control::new(
+        "view",
+        "Ansicht",
+        ControlKind::Choice {
+            binding: Binding::new(
+                |world| {
+                    let preset = world.resource::<views::CameraViews>().preset;
+                    Preset::ALL.iter().position(|p| *p == preset).unwrap()
+                },
+                |world, idx| views::show_preset(world, Preset::ALL[idx]),
+            ),
+            options: Preset::ALL.map(Preset::name).to_vec(),
+        },
+    ))
+    .register_control(Control::new(
+        "bookmark",
+        BOOKMARK,
+        ControlKind::Trigger(Box::new(views::add_bookmark)),
+    ))
+    .register_control(Control::new(
+        "next_bookmark",
+        NEXT_BOOKMARK,
+        ControlKind::Trigger(Box::new(views::next_bookmark)),
+    ))
+    .register_control(Control::new(
+        "save_scene",
+        "Szene speichern",
+        ControlKind::Trigger(Box::new(|world| world.send_event(scene::SaveScene))),
+    ))
+    .register_control(Control::new(
+        "export",
+        EXPORT,
+        ControlKind::Trigger(Box::new(|world| {
+            export_partition(&Side::from(world.resource::<Config>()))
+        })),
+    ));
+}
+
+fn apply_actions(
+    mut actions: EventReader<Action>,
+    mut controls: EventWriter<ControlEvent>,
     mut config: ResMut<Config>,
+    mut cameras: Query<&mut PanOrbitCamera>,
+    mut views: ResMut<views::CameraViews>,
 ) {
-    for (
-        interaction,
-        mut color,
-        mut border_color,
-        children,
-        (more, less, fun, inc, coord, party),
-    ) in &mut interaction_query
-    {
-        let mut text = text_query.get_mut(children[0]).unwrap();
-        let mut n_text = n_text_query.get_single_mut().unwrap();
-        match *interaction {
-            Interaction::Pressed => {
-                *color = HOVERED_BUTTON.into();
-                border_color.0 = Color::RED;
-                if more.is_some() {
-                    config.n = (config.n + 1).min(6);
-                    n_text.sections[0].value = format!("n: {}", config.n);
-                } else if less.is_some() {
-                    config.n = config.n.saturating_sub(1);
-                    n_text.sections[0].value = format!("n: {}", config.n);
-                } else if fun.is_some() {
-                    config.show_function = !config.show_function;
-                    text.sections[0].value = format!(
-                        "{}{}",
-                        SHOW_FUN,
-                        if config.show_function { " :)" } else { "" }
-                    );
-                } else if inc.is_some() {
-                    config.show_incremental_cubes = !config.show_incremental_cubes;
-                    text.sections[0].value = format!(
-                        "{}{}",
-                        SHOW_INC,
-                        if config.show_incremental_cubes {
-                            " :)"
-                        } else {
-                            ""
-                        }
-                    );
-                } else if coord.is_some() {
-                    config.show_full_grid = !config.show_full_grid;
-                    text.sections[0].value = format!(
-                        "{}{}",
-                        SHOW_COORD,
-                        if config.show_full_grid { " :)" } else { "" }
-                    );
-                } else if party.is_some() {
-                    config.show_party = !config.show_party;
-                    text.sections[0].value = format!(
-                        "{}{}",
-                        SHOW_PARTY,
-                        if config.show_party { " :^)" } else { "" }
-                    );
+    for action in actions.read() {
+        let control = |key, input| ControlEvent { key, input };
+        match *action {
+            Action::Press(key) => controls.send(control(key, ControlInput::Press)),
+            Action::More(key) => controls.send(control(key, ControlInput::Increase)),
+            Action::Less(key) => controls.send(control(key, ControlInput::Decrease)),
+            Action::ResetCamera => {
+                views.preset = Preset::Default;
+                for mut camera in &mut cameras {
+                    Preset::Default.view().apply(&mut camera);
                 }
             }
-            Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
-                border_color.0 = Color::WHITE;
-            }
-            Interaction::None => {
-                *color = NORMAL_BUTTON.into();
-                border_color.0 = Color::BLACK;
-            }
+            Action::Help => config.show_help = !config.show_help,
         }
     }
 }
 
-fn party_system(
-    time: Res<Time>,
-    mut interaction_query: Query<
+fn autoplay(time: Res<Time>, mut config: ResMut<Config>, mut timer: Local<Option<Timer>>) {
+    if !config.autoplay {
+        *timer = None;
+        return;
+    }
+    let timer = timer
+        .get_or_insert_with(|| Timer::from_seconds(config.autoplay_seconds, TimerMode::Repeating));
+    timer.set_duration(Duration::from_secs_f32(config.autoplay_seconds));
+    if timer.tick(time.delta()).just_finished() {
+        config.n = if config.n >= MAX_N { 0 } else { config.n + 1 };
+    }
+}
+
+fn export_partition(side: &Side) {
+    let export = export::Export::new(side);
+    let stem = format!("integral_n{}", side.n);
+    #[cfg(not(target_arch = "wasm32"))]
+    match export.write_files(std::path::Path::new(&stem)) {
+        Ok(()) => info!("exported partition to {stem}.csv, {stem}_summary.csv and {stem}.json"),
+        Err(e) => error!("failed to export partition: {e}"),
+    }
+    // the browser has no files to write, it downloads them instead
+    #[cfg(target_arch = "wasm32")]
+    for (name, contents, mime) in [
+        (format!("{stem}.csv"), export.to_csv(), "text/csv"),
         (
-            &mut BorderColor,
-            &Children,
-            (
-                Option<&ConfigMore>,
-                Option<&ConfigLess>,
-                Option<&ConfigFunctionGraph>,
-                Option<&ConfigIncremental>,
-                Option<&ConfigCoord>,
-                Option<&ConfigParty>,
-            ),
+            format!("{stem}_summary.csv"),
+            export.summary_csv(),
+            "text/csv",
         ),
-        With<Button>,
-    >,
-    mut text_query: Query<&mut Text, Without<NText>>,
-    mut n_text_query: Query<&mut Text, With<NText>>,
-    camera: Query<Entity, With<PanOrbitCamera>>,
+        (format!("{stem}.json"), export.to_json(), "application/json"),
+    ] {
+        if let Err(e) = web::download(&name, &contents, mime) {
+            error!("failed to download {name}: {e:?}");
+        }
+    }
+}
+
+fn party_system(
+    time: Res<Time>,
+    mut interaction_query: Query<(&mut BorderColor, &Children, &ControlButton)>,
+    mut text_query: Query<&mut Text, Without<ControlValue>>,
+    mut n_text_query: Query<&mut Text, With<ControlValue>>,
+    cameras: Query<Entity, With<PanOrbitCamera>>,
     music: Query<Entity, With<MusicTag>>,
     config: Res<Config>,
     mut in_party: Local<bool>,
@@ -739,58 +1480,46 @@ fn party_system(
 ) {
     let seconds = time.elapsed_seconds();
 
-    let mut n_text = n_text_query.get_single_mut().unwrap();
-    let Ok(camera) = camera.get_single() else {
+    if cameras.is_empty() {
         return;
-    };
+    }
     if config.show_party {
-        n_text.sections[0].style.color = Color::Rgba {
-            red: (4.25 * seconds).sin() / 2.0 + 0.5,
-            green: (3.75 * seconds).sin() / 2.0 + 0.5,
-            blue: (2.50 * seconds).sin() / 2.0 + 0.5,
-            alpha: 1.0,
-        };
+        for mut n_text in &mut n_text_query {
+            n_text.sections[0].style.color = Color::Rgba {
+                red: (4.25 * seconds).sin() / 2.0 + 0.5,
+                green: (3.75 * seconds).sin() / 2.0 + 0.5,
+                blue: (2.50 * seconds).sin() / 2.0 + 0.5,
+                alpha: 1.0,
+            };
+        }
 
-        for (mut border_color, children, (more, less, fun, inc, coord, party)) in
-            &mut interaction_query
-        {
+        let colors = [
+            (4.2, 1.5, 0.4),
+            (5., 0., 3.5),
+            (0., 7.5, 6.),
+            (9., 0., 0.5),
+            (0.8, 8.5, 7.5),
+            (10., 15., 8.),
+            (3., 0.5, 12.),
+            (6.5, 2., 1.),
+            (1., 11., 3.),
+            (7., 4., 9.5),
+        ];
+        for (mut border_color, children, button) in &mut interaction_query {
             let mut text = text_query.get_mut(children[0]).unwrap();
-
-            let colors = [
-                (4.2, 1.5, 0.4),
-                (5., 0., 3.5),
-                (0., 7.5, 6.),
-                (9., 0., 0.5),
-                (0.8, 8.5, 7.5),
-                (10., 15., 8.),
-            ];
-            let math = [
-                more.is_some(),
-                less.is_some(),
-                fun.is_some(),
-                inc.is_some(),
-                coord.is_some(),
-                party.is_some(),
-            ];
-            for ((r, g, b), m) in colors.into_iter().zip(math) {
-                if m {
-                    let color = Color::Rgba {
-                        red: (r * seconds).sin() / 2.0 + 0.5,
-                        green: (g * seconds).sin() / 2.0 + 0.5,
-                        blue: (b * seconds).sin() / 2.0 + 0.5,
-                        alpha: 1.0,
-                    };
-                    text.sections[0].style.color = color;
-                    *border_color = BorderColor(color.with_l(0.5));
-                }
-            }
+            // the two buttons of a stepper get different colors
+            let slot = button.index * 2 + usize::from(button.input == ControlInput::Decrease);
+            let (r, g, b) = colors[slot % colors.len()];
+            let color = Color::Rgba {
+                red: (r * seconds).sin() / 2.0 + 0.5,
+                green: (g * seconds).sin() / 2.0 + 0.5,
+                blue: (b * seconds).sin() / 2.0 + 0.5,
+                alpha: 1.0,
+            };
+            text.sections[0].style.color = color;
+            *border_color = BorderColor(color.with_l(0.5));
         }
         if !*in_party {
-            commands.entity(camera).insert(BloomSettings {
-                intensity: 0.7,
-                composite_mode: BloomCompositeMode::EnergyConserving,
-                ..default()
-            });
             commands.spawn((
                 AudioBundle {
                     #[cfg(feature = "embedded")]
@@ -802,16 +1531,26 @@ fn party_system(
                 MusicTag,
             ));
         }
-        commands.entity(camera).insert(FogSettings {
-            color: Color::Rgba {
-                red: ((1. * seconds).sin() / 2.0 + 0.5) / 3.0,
-                green: ((0.5 * seconds).sin() / 2.0 + 0.5) / 3.0,
-                blue: ((2. * seconds).sin() / 2.0 + 0.5) / 3.0,
-                alpha: 0.2,
-            },
-            falloff: FogFalloff::Exponential { density: 0.05 },
-            ..default()
-        });
+        // every frame, so a camera spawned during the party joins in
+        for camera in &cameras {
+            commands.entity(camera).insert((
+                BloomSettings {
+                    intensity: 0.7,
+                    composite_mode: BloomCompositeMode::EnergyConserving,
+                    ..default()
+                },
+                FogSettings {
+                    color: Color::Rgba {
+                        red: ((1. * seconds).sin() / 2.0 + 0.5) / 3.0,
+                        green: ((0.5 * seconds).sin() / 2.0 + 0.5) / 3.0,
+                        blue: ((2. * seconds).sin() / 2.0 + 0.5) / 3.0,
+                        alpha: 0.2,
+                    },
+                    falloff: FogFalloff::Exponential { density: 0.05 },
+                    ..default()
+                },
+            ));
+        }
         for (cube, mut material) in &mut cubes {
             //let mut material = materials.get_mut(material);
             *material = materials.add(StandardMaterial {
@@ -825,22 +1564,402 @@ fn party_system(
             });
         }
     } else if *in_party {
-        commands.entity(camera).remove::<FogSettings>();
-        commands.entity(camera).remove::<BloomSettings>();
+        for camera in &cameras {
+            commands.entity(camera).remove::<FogSettings>();
+            commands.entity(camera).remove::<BloomSettings>();
+        }
         if let Ok(music) = music.get_single() {
             commands.entity(music).despawn_recursive();
         }
-        n_text.sections[0].style.color = Color::WHITE;
+        for mut n_text in &mut n_text_query {
+            n_text.sections[0].style.color = Color::WHITE;
+        }
         for (mut border_color, children, _) in &mut interaction_query {
             let mut text = text_query.get_mut(children[0]).unwrap();
             text.sections[0].style.color = Color::WHITE;
             border_color.0 = Color::BLACK;
         }
-        for (cube, mut material) in &mut cubes {
-            //let mut material = materials.get_mut(material);
-            *material = materials
-                .add(Color::rgb_u8(124, (40 * cube.size_n).min(255), 255 / cube.size_n).into());
+        // `color_cubes` colors them again
+        for (_, mut material) in &mut cubes {
+            *material = materials.add(StandardMaterial::default());
         }
     }
     *in_party = config.show_party;
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        input::touch::{TouchInput, TouchPhase},
+        render::mesh::VertexAttributeValues,
+    };
+
+    use super::*;
+    use crate::controls::ControlInput;
+    use crate::testing::*;
+
+    fn mesh_height(app: &App, mesh: &Handle<Mesh>) -> Float {
+        let mesh = app.world.resource::<Assets<Mesh>>().get(mesh).unwrap();
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("mesh without positions");
+        };
+        let min = positions.iter().map(|p| p[1]).fold(Float::MAX, Float::min);
+        let max = positions.iter().map(|p| p[1]).fold(Float::MIN, Float::max);
+        max - min
+    }
+
+    #[test]
+    fn startup_spawns_first_level() {
+        let mut app = test_app();
+        // the cell at the origin has height 0 and is skipped
+        assert_eq!(cubes(&mut app, 1), (3, 0));
+        assert_eq!(n_label(&mut app), "n: 1");
+    }
+
+    #[test]
+    fn incremental_cubes_are_stacked() {
+        let mut app = test_app();
+        config(&mut app).n = 3;
+        app.update();
+        // cells sharing the sample point with their parent add nothing and are skipped
+        assert_eq!(cubes(&mut app, 1), (3, 0));
+        assert_eq!(cubes(&mut app, 2), (12, 0));
+        assert_eq!(cubes(&mut app, 3), (48, 0));
+
+        let mut query = app.world.query::<(&Cube, &Transform, &Handle<Mesh>)>();
+        let cubes: Vec<_> = query
+            .iter(&app.world)
+            .map(|(cube, transform, mesh)| (cube.size_n, transform.translation, mesh.clone()))
+            .collect();
+        for (level, translation, mesh) in cubes {
+            let size = Float::powi(2., -(level as i32));
+            let (x, y) = (translation.x, translation.z);
+            let parent = Float::powi(2., -(level as i32 - 1));
+            let base = if level == 1 {
+                0.
+            } else {
+                f((x / parent).floor() * parent, (y / parent).floor() * parent)
+            };
+            assert!((translation.y - base).abs() < 1e-5);
+            assert!((mesh_height(&app, &mesh) - (f(x, y) - base)).abs() < 1e-5);
+            assert!((x / size).fract().abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn lowering_n_hides_finer_levels() {
+        let mut app = test_app();
+        config(&mut app).n = 3;
+        app.update();
+        config(&mut app).n = 1;
+        app.update();
+        assert_eq!(cubes(&mut app, 1), (3, 0));
+        assert_eq!(cubes(&mut app, 2), (0, 12));
+        assert_eq!(cubes(&mut app, 3), (0, 48));
+
+        // raising it again reuses the hidden cubes
+        config(&mut app).n = 2;
+        app.update();
+        assert_eq!(cubes(&mut app, 2), (12, 0));
+        assert_eq!(cubes(&mut app, 3), (0, 48));
+    }
+
+    #[test]
+    fn non_incremental_cubes_start_at_the_ground() {
+        let mut app = test_app();
+        {
+            let mut config = config(&mut app);
+            config.n = 2;
+            config.show_incremental_cubes = false;
+        }
+        app.update();
+        assert_eq!(cubes(&mut app, 1), (0, 3));
+        assert_eq!(cubes(&mut app, 2), (15, 0));
+
+        let mut query = app.world.query::<(&Cube, &Transform, &Visibility)>();
+        for (cube, transform, vis) in query.iter(&app.world) {
+            if cube.size_n == 2 {
+                assert_eq!(cube.prev_n, 0);
+                assert_eq!(transform.translation.y, 0.);
+                assert_ne!(vis, Visibility::Hidden);
+            }
+        }
+    }
+
+    #[test]
+    fn plane_follows_show_function() {
+        let mut app = test_app();
+        assert_eq!(plane_visibility(&mut app), [Visibility::Inherited]);
+
+        let mut query = app.world.query_filtered::<&Handle<Mesh>, With<Plane>>();
+        let mesh = query.single(&app.world).clone();
+        assert!((mesh_height(&app, &mesh) - (f(1., 1.) - f(0., 0.))).abs() < 1e-5);
+
+        config(&mut app).show_function = false;
+        app.update();
+        assert_eq!(plane_visibility(&mut app), [Visibility::Hidden]);
+
+        config(&mut app).show_function = true;
+        app.update();
+        assert_eq!(plane_visibility(&mut app), [Visibility::Visible]);
+    }
+
+    fn side(app: &mut App, view: View) -> Side {
+        let mut query = app.world.query::<(&View, &Side)>();
+        *query.iter(&app.world).find(|(v, _)| **v == view).unwrap().1
+    }
+
+    fn readout_text(app: &mut App, view: View) -> (String, Display) {
+        let mut query = app.world.query::<(&Text, &Style, &Readout)>();
+        let (text, style, _) = query
+            .iter(&app.world)
+            .find(|(_, _, readout)| readout.0 == view)
+            .unwrap();
+        (text.sections[0].value.clone(), style.display)
+    }
+
+    #[test]
+    fn comparison_controls() {
+        let mut app = test_app();
+        assert_eq!(
+            readout_text(&mut app, View::Primary),
+            (
+                "n = 1, links unten\nSumme: 0.50000\nFehler: -0.50000".to_string(),
+                Display::Flex
+            )
+        );
+        assert_eq!(readout_text(&mut app, View::Secondary).1, Display::None);
+
+        press(&mut app, "compare", ControlInput::Press);
+        assert!(config(&mut app).split_screen);
+        assert_eq!(
+            readout_text(&mut app, View::Secondary),
+            (
+                "n = 1, Mittelpunkt\nSumme: 1.00000\nFehler: +0.00000".to_string(),
+                Display::Flex
+            )
+        );
+
+        press(&mut app, "compare_n", ControlInput::Increase);
+        press(&mut app, "compare_rule", ControlInput::Press);
+        assert_eq!(config(&mut app).n, 1);
+        assert_eq!(
+            side(&mut app, View::Secondary),
+            Side {
+                n: 2,
+                rule: Rule::LowerLeft,
+                show_incremental_cubes: true
+            }
+        );
+        assert!(readout_text(&mut app, View::Secondary)
+            .0
+            .starts_with("n = 2, links unten"));
+
+        press(&mut app, "rule", ControlInput::Press);
+        assert_eq!(config(&mut app).rule, Rule::LowerRight);
+        assert_eq!(side(&mut app, View::Primary).rule, Rule::LowerRight);
+    }
+
+    #[test]
+    fn side_controls_without_the_side() {
+        let mut app = test_app();
+        let mut sides = app.world.query::<(Entity, &View, &Side)>();
+        let secondary = sides
+            .iter(&app.world)
+            .find(|(_, view, _)| **view == View::Secondary)
+            .unwrap()
+            .0;
+        app.world.despawn(secondary);
+        press(&mut app, "compare_n", ControlInput::Increase);
+        let button = press(&mut app, "compare_rule", ControlInput::Press);
+        assert_eq!(label(&mut app, button), "Regel rechts: links unten");
+    }
+
+    #[test]
+    fn secondary_cubes_are_on_their_own_layer() {
+        let mut app = test_app();
+        config(&mut app).split_screen = true;
+        app.update();
+        let mut query = app.world.query::<(&Cube, &RenderLayers, &Visibility)>();
+        let visible = |view: View| {
+            move |(cube, _, vis): &(&Cube, &RenderLayers, &Visibility)| {
+                cube.view == view && **vis != Visibility::Hidden
+            }
+        };
+        // f vanishes only in the lower left corner of the first cell
+        assert_eq!(
+            query
+                .iter(&app.world)
+                .filter(visible(View::Secondary))
+                .count(),
+            4
+        );
+        for (cube, layers, _) in query.iter(&app.world) {
+            assert_eq!(*layers, cube.view.layers());
+        }
+
+        config(&mut app).split_screen = false;
+        app.update();
+        let mut query = app.world.query::<(&Cube, &RenderLayers, &Visibility)>();
+        assert_eq!(
+            query
+                .iter(&app.world)
+                .filter(visible(View::Secondary))
+                .count(),
+            0
+        );
+        assert_eq!(
+            query
+                .iter(&app.world)
+                .filter(visible(View::Primary))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn synced_cameras_follow_the_active_one() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(Config {
+                split_screen: true,
+                ..default()
+            })
+            .init_resource::<ActiveCameraData>()
+            .add_systems(Update, sync_cameras);
+        let primary = app.world.spawn(default_camera()).id();
+        let secondary = app.world.spawn(default_camera()).id();
+        app.world.resource_mut::<ActiveCameraData>().entity = Some(secondary);
+        app.world
+            .get_mut::<PanOrbitCamera>(secondary)
+            .unwrap()
+            .target_alpha = 0.5;
+        app.update();
+        let alpha = |app: &App, entity| {
+            app.world
+                .get::<PanOrbitCamera>(entity)
+                .unwrap()
+                .target_alpha
+        };
+        assert_eq!(alpha(&app, primary), 0.5);
+
+        app.world.resource_mut::<Config>().sync_cameras = false;
+        app.world
+            .get_mut::<PanOrbitCamera>(secondary)
+            .unwrap()
+            .target_alpha = 1.;
+        app.update();
+        assert_eq!(alpha(&app, primary), 0.5);
+    }
+
+    fn touch(app: &mut App, id: u64, phase: TouchPhase, x: f32, y: f32) {
+        app.world.send_event(TouchInput {
+            phase,
+            position: Vec2::new(x, y),
+            force: None,
+            id,
+        });
+    }
+
+    #[test]
+    fn fingers_orbit_pan_and_pinch() {
+        use std::f32::consts::TAU;
+        use TouchPhase::*;
+
+        let (mut app, camera) = touch_app();
+        let start = heading(&app, camera);
+
+        // one finger orbits, by a tenth of a turn for a tenth of the window
+        touch(&mut app, 0, Started, 640., 360.);
+        app.update();
+        touch(&mut app, 0, Moved, 768., 360.);
+        app.update();
+        let turned = heading(&app, camera);
+        assert!((turned.alpha - (start.alpha - 0.1 * TAU)).abs() < 1e-5);
+        assert_eq!((turned.focus, turned.radius), (start.focus, start.radius));
+        assert_eq!(
+            app.world.resource::<ActiveCameraData>().entity,
+            Some(camera)
+        );
+        touch(&mut app, 0, Ended, 768., 360.);
+        app.update();
+
+        // spreading two fingers to twice their distance halves the radius
+        touch(&mut app, 1, Started, 500., 360.);
+        touch(&mut app, 2, Started, 700., 360.);
+        app.update();
+        touch(&mut app, 1, Moved, 400., 360.);
+        touch(&mut app, 2, Moved, 800., 360.);
+        app.update();
+        let pinched = heading(&app, camera);
+        assert!((pinched.radius - start.radius / 2.).abs() < 1e-5);
+        assert_eq!((pinched.focus, pinched.alpha), (turned.focus, turned.alpha));
+
+        // moving both the same way pans
+        touch(&mut app, 1, Moved, 400., 400.);
+        touch(&mut app, 2, Moved, 800., 400.);
+        app.update();
+        let panned = heading(&app, camera);
+        assert_ne!(panned.focus, pinched.focus);
+        assert_eq!(panned.radius, pinched.radius);
+
+        // the finger left after a pinch doesn't orbit
+        touch(&mut app, 1, Ended, 400., 400.);
+        app.update();
+        touch(&mut app, 2, Moved, 900., 500.);
+        app.update();
+        assert_eq!(heading(&app, camera), panned);
+        touch(&mut app, 2, Ended, 900., 500.);
+        app.update();
+
+        // fingers on the control panel belong to the buttons
+        touch(&mut app, 3, Started, 100., 360.);
+        app.update();
+        touch(&mut app, 3, Moved, 600., 200.);
+        app.update();
+        assert_eq!(heading(&app, camera), panned);
+        touch(&mut app, 3, Ended, 600., 200.);
+        app.update();
+
+        // a new gesture orbits again
+        touch(&mut app, 4, Started, 640., 360.);
+        app.update();
+        touch(&mut app, 4, Moved, 640., 432.);
+        app.update();
+        assert!(heading(&app, camera).beta > panned.beta);
+    }
+
+    #[test]
+    fn fingers_follow_the_scaled_panel_and_the_zoom_limit() {
+        use TouchPhase::*;
+
+        let (mut app, camera) = touch_app();
+        app.insert_resource(UiScale(2.));
+        app.world
+            .get_mut::<PanOrbitCamera>(camera)
+            .unwrap()
+            .zoom_lower_limit = Some(4.);
+        app.update();
+        let start = heading(&app, camera);
+
+        // the panel is twice as wide in the window, but still a fifth of it in the layout
+        touch(&mut app, 0, Started, 300., 360.);
+        app.update();
+        touch(&mut app, 0, Moved, 600., 360.);
+        app.update();
+        assert_eq!(heading(&app, camera), start);
+        touch(&mut app, 0, Ended, 600., 360.);
+        app.update();
+
+        // spreading to twice the distance stops at the limit instead of halving the radius
+        touch(&mut app, 1, Started, 500., 360.);
+        touch(&mut app, 2, Started, 700., 360.);
+        app.update();
+        touch(&mut app, 1, Moved, 400., 360.);
+        touch(&mut app, 2, Moved, 800., 360.);
+        app.update();
+        assert_eq!(heading(&app, camera).radius, 4.);
+    }
+}
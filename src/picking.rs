@@ -0,0 +1,422 @@
+//! Hovering and clicking single cubes to see what they stand for.
+//!
+//! `cursor_ray` turns the cursor into a [`PickRay`], `pick` finds the cube below it with
+//! [`nearest_box`], which only needs the boxes of the cubes and works without rendering.
+
+use std::cmp::Ordering;
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{core::Cell, slice, Config, Cube, Float, View};
+
+/// Clicks that move the cursor further than this are drags of the camera.
+const CLICK_DISTANCE: f32 = 4.;
+
+/// The cursor and the ray through it, from the camera of the view below the cursor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pointer {
+    pub position: Vec2,
+    pub view: View,
+    pub ray: Ray,
+}
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct PickRay(pub Option<Pointer>);
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Picked {
+    /// The cube below the cursor.
+    pub hovered: Option<Entity>,
+    /// The cube that was clicked last and where the click was.
+    pub pinned: Option<(Entity, Vec2)>,
+}
+
+/// The box a cell is drawn as, in world coordinates.
+pub fn cell_box(cell: &Cell) -> (Vec3, Vec3) {
+    let (bottom, top) = if cell.base <= cell.height {
+        (cell.base, cell.height)
+    } else {
+        (cell.height, cell.base)
+    };
+    (
+        Vec3::new(cell.min_x, bottom, cell.min_y),
+        Vec3::new(cell.max_x, top, cell.max_y),
+    )
+}
+
+/// The box of a cube drawn for `cell` after `transform` moved and scaled it, like the cut
+/// of a slice does.
+pub fn drawn_box(cell: &Cell, transform: &Transform) -> (Vec3, Vec3) {
+    let (min, max) = cell_box(cell);
+    (
+        transform.translation,
+        transform.translation + transform.scale * (max - min),
+    )
+}
+
+/// Distance along `ray` to the axis aligned box from `min` to `max`, if it is hit.
+pub fn ray_box(ray: Ray, min: Vec3, max: Vec3) -> Option<f32> {
+    let inv = ray.direction.recip();
+    let a = (min - ray.origin) * inv;
+    let b = (max - ray.origin) * inv;
+    let near = a.min(b).max_element();
+    let far = a.max(b).min_element();
+    (near <= far && far >= 0.).then_some(near.max(0.))
+}
+
+/// The first of `cells` hit by `ray`.
+pub fn nearest<'a, T>(ray: Ray, cells: impl IntoIterator<Item = (T, &'a Cell)>) -> Option<T> {
+    nearest_box(
+        ray,
+        cells.into_iter().map(|(key, cell)| (key, cell_box(cell))),
+    )
+}
+
+/// The first of the boxes from `min` to `max` hit by `ray`.
+pub fn nearest_box<T>(ray: Ray, boxes: impl IntoIterator<Item = (T, (Vec3, Vec3))>) -> Option<T> {
+    boxes
+        .into_iter()
+        .filter_map(|(key, (min, max))| ray_box(ray, min, max).map(|distance| (key, distance)))
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(key, _)| key)
+}
+
+/// The text of the tooltip of a cube of level `level` stacked on `prev_level`.
+pub fn describe(cell: &Cell, level: u8, prev_level: u8) -> String {
+    let mut out = format!(
+        "Zelle (i, j) = ({}, {}) auf Stufe {level}\n\
+         x: {:.4} bis {:.4}\n\
+         y: {:.4} bis {:.4}\n\
+         Stützstelle: ({:.4}, {:.4})\n\
+         f = {:.5}\n\
+         Beitrag zur Summe: {:.6}",
+        cell.i,
+        cell.j,
+        cell.min_x,
+        cell.max_x,
+        cell.min_y,
+        cell.max_y,
+        cell.sample_x,
+        cell.sample_y,
+        cell.height,
+        cell.volume,
+    );
+    if prev_level > 0 {
+        out.push_str(&format!(
+            "\n(Änderung gegenüber Stufe {prev_level}, Höhe vorher {:.5})",
+            cell.base
+        ));
+    }
+    out
+}
+
+pub(crate) fn cursor_ray(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform, &View)>,
+    mut pick_ray: ResMut<PickRay>,
+) {
+    // without a window the ray is left to whoever sets it, e.g. tests
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let pointer = window.cursor_position().and_then(|position| {
+        cameras.iter().find_map(|(camera, transform, view)| {
+            let inside = camera.logical_viewport_rect()?.contains(position);
+            let ray = camera.viewport_to_world(transform, position)?;
+            inside.then_some(Pointer {
+                position,
+                view: *view,
+                ray,
+            })
+        })
+    });
+    pick_ray.set_if_neq(PickRay(pointer));
+}
+
+pub(crate) fn pick(
+    pick_ray: Res<PickRay>,
+    mouse: Res<Input<MouseButton>>,
+    buttons: Query<&Interaction>,
+    config: Res<Config>,
+    cubes: Query<(Entity, &Cube, &Transform, &Visibility)>,
+    mut picked: ResMut<Picked>,
+    mut pressed_at: Local<Option<Vec2>>,
+) {
+    let visible = |entity| {
+        cubes
+            .get(entity)
+            .is_ok_and(|(_, _, _, vis)| *vis != Visibility::Hidden)
+    };
+    let hovered = pick_ray.0.and_then(|pointer| {
+        // the faded cubes in front of a slice are looked through
+        let boxes = cubes
+            .iter()
+            .filter(|(_, cube, _, vis)| {
+                cube.view == pointer.view
+                    && **vis != Visibility::Hidden
+                    && slice::cut_side(&config, cube) != Some(Ordering::Less)
+            })
+            .map(|(entity, cube, transform, _)| (entity, drawn_box(&cube.cell, transform)));
+        nearest_box(pointer.ray, boxes)
+    });
+    if picked.hovered != hovered {
+        picked.hovered = hovered;
+    }
+    if let Some((entity, _)) = picked.pinned {
+        if !visible(entity) {
+            picked.pinned = None;
+        }
+    }
+
+    let Some(pointer) = pick_ray.0 else {
+        return;
+    };
+    // clicks on the panel are not meant for the cubes
+    let on_ui = buttons.iter().any(|i| *i != Interaction::None);
+    if mouse.just_pressed(MouseButton::Left) && !on_ui {
+        *pressed_at = Some(pointer.position);
+    }
+    if mouse.just_released(MouseButton::Left) {
+        if let Some(start) = pressed_at.take() {
+            if start.distance(pointer.position) <= CLICK_DISTANCE {
+                picked.pinned = hovered.map(|entity| (entity, pointer.position));
+            }
+        }
+    }
+}
+
+const HIGHLIGHT: Color = Color::rgb(0.35, 0.35, 0.35);
+
+pub(crate) fn highlight(
+    picked: Res<Picked>,
+    config: Res<Config>,
+    cubes: Query<&Handle<StandardMaterial>, With<Cube>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut lit: Local<Vec<Entity>>,
+) {
+    // the party has its own glow
+    if config.show_party || !picked.is_changed() {
+        return;
+    }
+    let mut set = |entity: Entity, color: Color| {
+        if let Some(material) = cubes
+            .get(entity)
+            .ok()
+            .and_then(|handle| materials.get_mut(handle))
+        {
+            material.emissive = color;
+        }
+    };
+    for entity in lit.drain(..) {
+        set(entity, Color::BLACK);
+    }
+    for entity in picked
+        .hovered
+        .into_iter()
+        .chain(picked.pinned.map(|(entity, _)| entity))
+    {
+        set(entity, HIGHLIGHT);
+        lit.push(entity);
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct Tooltip;
+
+pub(crate) fn setup_tooltip(mut commands: Commands) {
+    let mut text = TextBundle::from_section(
+        "",
+        TextStyle {
+            font_size: 16.,
+            ..default()
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        padding: UiRect::all(Val::Px(8.0)),
+        display: Display::None,
+        ..default()
+    })
+    .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.8));
+    // above the legend and the readouts
+    text.z_index = ZIndex::Global(10);
+    commands.spawn((text, Tooltip));
+}
+
+pub(crate) fn tooltip(
+    picked: Res<Picked>,
+    cubes: Query<&Cube>,
+    ui_scale: Res<UiScale>,
+    mut tooltip: Query<(&mut Text, &mut Style), With<Tooltip>>,
+) {
+    if !picked.is_changed() && !ui_scale.is_changed() {
+        return;
+    }
+    let pinned = picked
+        .pinned
+        .and_then(|(entity, position)| Some((cubes.get(entity).ok()?, position)));
+    for (mut text, mut style) in &mut tooltip {
+        let Some((cube, position)) = pinned else {
+            style.display = Display::None;
+            continue;
+        };
+        // the layout is scaled by `UiScale`, the cursor position is not
+        let position = position / ui_scale.0 as f32;
+        let offset: Float = 12.;
+        style.display = Display::Flex;
+        style.left = Val::Px(position.x + offset);
+        style.top = Val::Px(position.y + offset);
+        text.sections[0].value = describe(&cube.cell, cube.size_n, cube.prev_n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{mouse::MouseButtonInput, ButtonState};
+
+    use super::*;
+    use crate::core::Axis;
+    use crate::testing::*;
+
+    fn point_at(app: &mut App, x: f32, z: f32) {
+        app.world.resource_mut::<PickRay>().0 = Some(Pointer {
+            position: Vec2::new(100., 200.),
+            view: View::Primary,
+            ray: Ray {
+                origin: Vec3::new(x, 10., z),
+                direction: Vec3::NEG_Y,
+            },
+        });
+    }
+
+    fn click(app: &mut App) {
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            app.world.send_event(MouseButtonInput {
+                button: MouseButton::Left,
+                state,
+                window: Entity::PLACEHOLDER,
+            });
+            app.update();
+        }
+    }
+
+    fn tooltip(app: &mut App) -> (String, Display) {
+        let mut query = app.world.query_filtered::<(&Text, &Style), With<Tooltip>>();
+        let (text, style) = query.single(&app.world);
+        (text.sections[0].value.clone(), style.display)
+    }
+
+    #[test]
+    fn hover_and_pin_cells() {
+        let mut app = test_app();
+        point_at(&mut app, 0.7, 0.2);
+        app.update();
+        let hovered = app.world.resource::<Picked>().hovered.unwrap();
+        let cube = app.world.get::<Cube>(hovered).unwrap();
+        assert_eq!((cube.cell.i, cube.cell.j), (1, 0));
+        let material = app
+            .world
+            .get::<Handle<StandardMaterial>>(hovered)
+            .unwrap()
+            .clone();
+        let emissive = app
+            .world
+            .resource::<Assets<StandardMaterial>>()
+            .get(&material)
+            .unwrap()
+            .emissive;
+        assert_ne!(emissive, Color::BLACK);
+        assert_eq!(tooltip(&mut app).1, Display::None);
+
+        click(&mut app);
+        assert_eq!(
+            app.world.resource::<Picked>().pinned,
+            Some((hovered, Vec2::new(100., 200.)))
+        );
+        let (text, display) = tooltip(&mut app);
+        assert_eq!(display, Display::Flex);
+        assert!(
+            text.starts_with("Zelle (i, j) = (1, 0) auf Stufe 1"),
+            "{text}"
+        );
+        assert!(text.contains("Beitrag zur Summe: 0.125000"), "{text}");
+
+        // the pinned cell stays when the cursor moves away
+        point_at(&mut app, 3., 3.);
+        app.update();
+        assert_eq!(app.world.resource::<Picked>().hovered, None);
+        assert_eq!(tooltip(&mut app).1, Display::Flex);
+
+        // clicking into the void unpins it
+        click(&mut app);
+        assert_eq!(app.world.resource::<Picked>().pinned, None);
+        assert_eq!(tooltip(&mut app).1, Display::None);
+        let emissive = app
+            .world
+            .resource::<Assets<StandardMaterial>>()
+            .get(&material)
+            .unwrap()
+            .emissive;
+        assert_eq!(emissive, Color::BLACK);
+    }
+
+    #[test]
+    fn hidden_cells_are_not_picked() {
+        let mut app = test_app();
+        point_at(&mut app, 0.8, 0.3);
+        app.update();
+        click(&mut app);
+        assert!(app.world.resource::<Picked>().pinned.is_some());
+
+        config(&mut app).n = 2;
+        app.update();
+        // the level 2 cube on top is hovered now and the level 1 cube below is still visible
+        let hovered = app.world.resource::<Picked>().hovered.unwrap();
+        assert_eq!(app.world.get::<Cube>(hovered).unwrap().size_n, 2);
+        assert!(app.world.resource::<Picked>().pinned.is_some());
+
+        config(&mut app).n = 0;
+        app.update();
+        assert_eq!(*app.world.resource::<Picked>(), Picked::default());
+    }
+
+    #[test]
+    fn cut_cubes_are_picked_where_they_are_drawn() {
+        let mut app = test_app();
+        let hovered = |app: &mut App, x, z| {
+            point_at(app, x, z);
+            app.update();
+            let cube = app.world.resource::<Picked>().hovered?;
+            let cell = &app.world.get::<Cube>(cube).unwrap().cell;
+            Some((cell.i, cell.j))
+        };
+        assert_eq!(hovered(&mut app, 0.2, 0.7), Some((0, 1)));
+        {
+            let mut config = config(&mut app);
+            config.show_slice = true;
+            config.slice_axis = Axis::X;
+            config.slice_position = 0.3;
+        }
+        // the cube of the cut column only starts at the cut
+        assert_eq!(hovered(&mut app, 0.2, 0.7), None);
+        assert_eq!(hovered(&mut app, 0.4, 0.7), Some((0, 1)));
+
+        // the faded cubes in front of the cut are looked through
+        config(&mut app).slice_position = 0.6;
+        assert_eq!(hovered(&mut app, 0.4, 0.7), None);
+        assert_eq!(hovered(&mut app, 0.8, 0.7), Some((1, 1)));
+    }
+
+    #[test]
+    fn tooltip_follows_the_ui_scale() {
+        let mut app = test_app();
+        app.insert_resource(UiScale(2.));
+        point_at(&mut app, 0.7, 0.2);
+        app.update();
+        click(&mut app);
+        let mut query = app.world.query_filtered::<&Style, With<Tooltip>>();
+        let style = query.single(&app.world);
+        // the cursor at (100, 200) is at (50, 100) in the scaled layout
+        assert_eq!((style.left, style.top), (Val::Px(62.), Val::Px(112.)));
+    }
+}
@@ -0,0 +1,143 @@
+//! The scene file, which keeps the values of all controls and the camera bookmarks, so a
+//! lecture can be prepared once and cycled through later.
+//!
+//! Every line has the form `key = value`. The keys are those of the [controls](crate::controls::Control), choices
+//! are stored by the text of their option, e.g. `rule = Mittelpunkt`. Every bookmark is a
+//! line `bookmark = x, y, z, alpha, beta, radius` with the focus of the camera, its angles
+//! and its distance. Lines starting with `#` are ignored.
+
+use bevy::prelude::*;
+
+use crate::{
+    controls::ControlRegistry,
+    views::{CameraView, CameraViews},
+};
+
+pub const FILE: &str = "scene.cfg";
+
+const BOOKMARK: &str = "bookmark";
+
+/// The scene file for the current state of `world`.
+pub fn to_string(world: &World) -> String {
+    let mut out = String::from("# key = value, see the documentation of `integral::scene`\n");
+    for control in world.resource::<ControlRegistry>().iter() {
+        if let Some(value) = control.save(world) {
+            out.push_str(&format!("{} = {value}\n", control.key));
+        }
+    }
+    for view in &world.resource::<CameraViews>().bookmarks {
+        let CameraView {
+            focus,
+            alpha,
+            beta,
+            radius,
+        } = view;
+        out.push_str(&format!(
+            "{BOOKMARK} = {}, {}, {}, {alpha}, {beta}, {radius}\n",
+            focus.x, focus.y, focus.z
+        ));
+    }
+    out
+}
+
+fn parse_bookmark(value: &str) -> Option<CameraView> {
+    let numbers = value
+        .split(',')
+        .map(|n| n.trim().parse::<f32>().ok().filter(|n| n.is_finite()))
+        .collect::<Option<Vec<_>>>()?;
+    let [x, y, z, alpha, beta, radius] = numbers[..] else {
+        return None;
+    };
+    Some(CameraView {
+        focus: Vec3::new(x, y, z),
+        alpha,
+        beta,
+        radius,
+    })
+}
+
+/// Applies a scene file to `world`. Controls that are not listed keep their values, the
+/// bookmarks are replaced. Nothing is changed if a line is invalid.
+pub fn apply(world: &mut World, s: &str) -> Result<(), String> {
+    let mut values = Vec::new();
+    let mut bookmarks = Vec::new();
+    for (idx, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected `key = value`", idx + 1))?;
+        let (key, value) = (key.trim(), value.trim());
+        if key == BOOKMARK {
+            let view = parse_bookmark(value)
+                .ok_or_else(|| format!("line {}: invalid bookmark `{value}`", idx + 1))?;
+            bookmarks.push(view);
+            continue;
+        }
+        let control = world
+            .resource::<ControlRegistry>()
+            .get(key)
+            .ok_or_else(|| format!("line {}: unknown key `{key}`", idx + 1))?;
+        control
+            .check(value)
+            .map_err(|e| format!("line {}: {e}", idx + 1))?;
+        values.push((key, value));
+    }
+
+    world.resource_scope(|world, registry: Mut<ControlRegistry>| {
+        for (key, value) in values {
+            // checked above
+            let _ = registry.get(key).unwrap().load(world, value);
+        }
+    });
+    let mut views = world.resource_mut::<CameraViews>();
+    views.bookmarks = bookmarks;
+    views.current = None;
+    Ok(())
+}
+
+/// Asks for the scene to be saved at the end of [`IntegralSet::Controls`](crate::IntegralSet).
+/// Controls run while the registry is taken out of the world, so they can't save right away.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SaveScene;
+
+pub(crate) fn save_requested(world: &mut World) {
+    if world.resource_mut::<Events<SaveScene>>().drain().count() > 0 {
+        save(world);
+    }
+}
+
+/// Writes the scene to [`FILE`]. The browser keeps it in its local storage under that name.
+pub fn save(world: &World) {
+    let scene = to_string(world);
+    #[cfg(not(target_arch = "wasm32"))]
+    match std::fs::write(FILE, scene) {
+        Ok(()) => info!("saved the scene to {FILE}"),
+        Err(e) => error!("failed to save the scene: {e}"),
+    }
+    #[cfg(target_arch = "wasm32")]
+    match crate::web::store(FILE, &scene) {
+        Ok(()) => info!("saved the scene to the local storage"),
+        Err(e) => error!("failed to save the scene: {e:?}"),
+    }
+}
+
+/// Reads [`FILE`], or the local storage in the browser, and shows the first bookmark of the
+/// scene if there is one. Runs in `PostStartup` of [`app`](crate::app), once the cameras are there.
+pub fn load(world: &mut World) {
+    #[cfg(not(target_arch = "wasm32"))]
+    let scene = std::fs::read_to_string(FILE).ok();
+    #[cfg(target_arch = "wasm32")]
+    let scene = crate::web::stored(FILE).unwrap_or_else(|e| {
+        error!("failed to read the local storage: {e:?}");
+        None
+    });
+    if let Some(s) = scene {
+        match apply(world, &s) {
+            Ok(()) => crate::views::next_bookmark(world),
+            Err(e) => error!("invalid {FILE}: {e}"),
+        }
+    }
+}
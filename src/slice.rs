@@ -0,0 +1,531 @@
+//! A slicing plane at `x = c` or `y = c` that shows the 1D Riemann sum of one column of
+//! cells, the way into Fubini's theorem.
+//!
+//! Cubes in front of the cut fade, the cubes of the cut column are clipped at `c`, so their
+//! faces show the profile of [`core::slice`]. The function graph is cut the same way and
+//! the profile is drawn next to the scene as a bar chart.
+
+use std::cmp::Ordering;
+
+use bevy::{prelude::*, render::view::RenderLayers};
+
+use crate::{
+    core::{self, Axis, Domain, Partition, Slice},
+    f, Config, Cube, Float, Plane, Side, View,
+};
+
+/// Opacity of the cubes in front of the cut.
+const FADED: f32 = 0.15;
+const PROFILE_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const CURVE_COLOR: Color = Color::rgb(1.0, 0.45, 1.0);
+
+/// German names of [`Axis::ALL`].
+pub(crate) const AXES: [&str; 2] = ["x = c", "y = c"];
+
+pub(crate) fn axis_index(axis: Axis) -> usize {
+    Axis::ALL.iter().position(|a| *a == axis).unwrap()
+}
+
+/// The slice of the primary side, if it is shown.
+fn current(config: &Config, sides: impl IntoIterator<Item = (View, Side)>) -> Option<Slice> {
+    if !config.show_slice {
+        return None;
+    }
+    let side = sides
+        .into_iter()
+        .find(|(view, _)| *view == View::Primary)
+        .map_or(Side::from(config), |(_, side)| side);
+    Some(core::slice(
+        &f,
+        &Domain::UNIT,
+        &Partition::dyadic(side.n),
+        side.rule,
+        config.slice_axis,
+        config.slice_position,
+    ))
+}
+
+/// The component of `v` along `axis`; the domain's `y` is the world's `z`.
+fn coordinate_mut(axis: Axis, v: &mut Vec3) -> &mut f32 {
+    match axis {
+        Axis::X => &mut v.x,
+        Axis::Y => &mut v.z,
+    }
+}
+
+/// The world position of the point at `t` along the slice, at height `h`.
+fn world(axis: Axis, c: Float, t: Float, h: Float) -> Vec3 {
+    let (x, y) = axis.point(c, t);
+    Vec3::new(x, h, y)
+}
+
+/// Where the column of `cube` lies relative to the cut column, `None` without a slice.
+pub(crate) fn cut_side(config: &Config, cube: &Cube) -> Option<Ordering> {
+    if !config.show_slice {
+        return None;
+    }
+    let partition = Partition::dyadic(cube.size_n);
+    let axis = config.slice_axis;
+    let column = axis.column(&Domain::UNIT, &partition, config.slice_position);
+    let own = match axis {
+        Axis::X => cube.cell.i,
+        Axis::Y => cube.cell.j,
+    };
+    Some(own.cmp(&column))
+}
+
+/// Fades the cubes in front of the cut and clips the cut column at `c`.
+pub(crate) fn cut_cubes(
+    config: Res<Config>,
+    mut cubes: Query<(&Cube, &mut Transform, &Handle<StandardMaterial>)>,
+    changed: Query<(), (With<Cube>, Changed<Visibility>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // `color_cubes` runs on the same changes and resets the colors
+    if !config.is_changed() && changed.is_empty() {
+        return;
+    }
+    let axis = config.slice_axis;
+    let c = config.slice_position;
+    for (cube, mut transform, material) in &mut cubes {
+        let cell = &cube.cell;
+        let mut translation = Vec3::new(cell.min_x, transform.translation.y, cell.min_y);
+        let mut scale = Vec3::ONE;
+        let mut alpha = 1.;
+        match cut_side(&config, cube) {
+            Some(Ordering::Less) => alpha = FADED,
+            Some(Ordering::Equal) => {
+                let (min, max) = axis.range(&cell.domain());
+                let start = c.clamp(min, max);
+                *coordinate_mut(axis, &mut translation) = start;
+                // a sliver stays at the end of the domain so the face can be seen
+                *coordinate_mut(axis, &mut scale) = ((max - start) / (max - min)).max(0.01);
+            }
+            _ => {}
+        }
+        if transform.translation != translation || transform.scale != scale {
+            transform.translation = translation;
+            transform.scale = scale;
+        }
+        if config.show_party {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color.set_a(alpha);
+            material.alpha_mode = if alpha < 1. {
+                AlphaMode::Blend
+            } else {
+                AlphaMode::Opaque
+            };
+        }
+    }
+}
+
+/// The function graph over `domain`, the vertices of the mesh spawned by `plane`.
+pub(crate) fn surface(domain: &Domain) -> Vec<[f32; 3]> {
+    let Domain {
+        min_x,
+        max_x,
+        min_y,
+        max_y,
+    } = *domain;
+    vec![
+        [min_x, f(min_x, min_y), min_y],
+        [max_x, f(max_x, min_y), min_y],
+        [min_x, f(min_x, max_y), max_y],
+        [max_x, f(max_x, max_y), max_y],
+    ]
+}
+
+/// Cuts the function graph at the slice, the part in front of it is removed.
+pub(crate) fn cut_surface(
+    config: Res<Config>,
+    plane: Query<&Handle<Mesh>, With<Plane>>,
+    added: Query<(), Added<Plane>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if !config.is_changed() && added.is_empty() {
+        return;
+    }
+    let mut domain = Domain::UNIT;
+    if config.show_slice {
+        let c = config.slice_position.clamp(0., 1.);
+        match config.slice_axis {
+            Axis::X => domain.min_x = c,
+            Axis::Y => domain.min_y = c,
+        }
+    }
+    for mesh in &plane {
+        if let Some(mesh) = meshes.get_mut(mesh) {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, surface(&domain));
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct SlicePlane;
+
+pub(crate) fn setup_plane(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(1., 1., 1.))),
+            material: materials.add(StandardMaterial {
+                base_color: PROFILE_COLOR.with_a(0.12),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                cull_mode: None,
+                ..default()
+            }),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        SlicePlane,
+        RenderLayers::all(),
+    ));
+}
+
+/// Moves the translucent plane to the slice and draws the profile and the cut through the
+/// function graph on it.
+pub(crate) fn slice_plane(
+    mut gizmos: Gizmos,
+    config: Res<Config>,
+    sides: Query<(&View, &Side)>,
+    mut planes: Query<(&mut Transform, &mut Visibility), With<SlicePlane>>,
+) {
+    let slice = current(&config, sides.iter().map(|(view, side)| (*view, *side)));
+    for (mut transform, mut vis) in &mut planes {
+        let Some(slice) = &slice else {
+            vis.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        vis.set_if_neq(Visibility::Visible);
+        let (low, high) = heights(slice);
+        let (min, max) = slice.axis.along(&Domain::UNIT);
+        let center = world(slice.axis, slice.c, (min + max) / 2., (low + high) / 2.);
+        let mut scale = Vec3::new(max - min, high - low, max - min);
+        *coordinate_mut(slice.axis, &mut scale) = 0.002;
+        let target = Transform::from_translation(center).with_scale(scale);
+        if *transform != target {
+            *transform = target;
+        }
+    }
+    let Some(slice) = slice else {
+        return;
+    };
+    let point = |t, h| world(slice.axis, slice.c, t, h);
+    for step in &slice.steps {
+        gizmos.linestrip(
+            [
+                point(step.min, 0.),
+                point(step.min, step.height),
+                point(step.max, step.height),
+                point(step.max, 0.),
+            ],
+            PROFILE_COLOR,
+        );
+    }
+    let (min, max) = slice.axis.along(&Domain::UNIT);
+    gizmos.linestrip(
+        (0..=CURVE_SEGMENTS).map(|k| {
+            let t = min + (max - min) * k as Float / CURVE_SEGMENTS as Float;
+            let (x, y) = slice.axis.point(slice.c, t);
+            point(t, f(x, y))
+        }),
+        CURVE_COLOR,
+    );
+}
+
+const CURVE_SEGMENTS: u32 = 32;
+
+/// Range of heights the plane and the chart have to cover: the profile, the function along
+/// the slice and the ground.
+fn heights(slice: &Slice) -> (Float, Float) {
+    let (min, max) = slice.axis.along(&Domain::UNIT);
+    let curve = (0..=CURVE_SEGMENTS).map(|k| {
+        let t = min + (max - min) * k as Float / CURVE_SEGMENTS as Float;
+        let (x, y) = slice.axis.point(slice.c, t);
+        f(x, y)
+    });
+    let (low, high) = slice
+        .steps
+        .iter()
+        .map(|step| step.height)
+        .chain(curve)
+        .fold((0., 0.), |(lo, hi): (Float, Float), h| {
+            (lo.min(h), hi.max(h))
+        });
+    if high > low {
+        (low, high)
+    } else {
+        (low, low + 1.)
+    }
+}
+
+#[derive(Component)]
+pub(crate) enum SliceChart {
+    Panel,
+    Bars,
+    Bar,
+    Text,
+}
+
+pub(crate) fn setup_chart(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(30.0),
+                    right: Val::Px(25.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(8.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    display: Display::None,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            SliceChart::Panel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.,
+                        ..default()
+                    },
+                ),
+                SliceChart::Text,
+            ));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(220.0),
+                        height: Val::Px(120.0),
+                        ..default()
+                    },
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.05).into(),
+                    ..default()
+                },
+                SliceChart::Bars,
+            ));
+        });
+}
+
+/// Shows the profile of the slice as bars together with its 1D Riemann sum.
+pub(crate) fn chart(
+    mut commands: Commands,
+    config: Res<Config>,
+    sides: Query<(&View, Ref<Side>)>,
+    mut parts: Query<(
+        Entity,
+        &SliceChart,
+        Option<&mut Style>,
+        Option<&mut Text>,
+        Option<&Children>,
+    )>,
+) {
+    if !config.is_changed() && !sides.iter().any(|(_, side)| side.is_changed()) {
+        return;
+    }
+    let slice = current(&config, sides.iter().map(|(view, side)| (*view, *side)));
+    let mut bars = Vec::new();
+    for (entity, part, style, text, children) in &mut parts {
+        match (part, style, text) {
+            (SliceChart::Panel, Some(mut style), _) => {
+                let display = if slice.is_some() {
+                    Display::Flex
+                } else {
+                    Display::None
+                };
+                if style.display != display {
+                    style.display = display;
+                }
+            }
+            (SliceChart::Text, _, Some(mut text)) => {
+                let Some(slice) = &slice else { continue };
+                let exact = core::integrate_slice(&f, &Domain::UNIT, slice.axis, slice.c);
+                let (fixed, along) = match slice.axis {
+                    Axis::X => ("x", "y"),
+                    Axis::Y => ("y", "x"),
+                };
+                text.sections[0].value = format!(
+                    "Schnitt bei {fixed} = {:.3}\n\
+                     Summe entlang {along}: {:.5}\n\
+                     Integral entlang {along}: {exact:.5}\n\
+                     Fehler: {:+.5}",
+                    slice.c,
+                    slice.sum,
+                    slice.sum - exact
+                );
+            }
+            (SliceChart::Bars, _, _) => {
+                bars.push((entity, children.map(|c| c.to_vec()).unwrap_or_default()))
+            }
+            _ => {}
+        }
+    }
+    let Some(slice) = slice else {
+        return;
+    };
+    let (low, high) = heights(&slice);
+    let (min, max) = slice.axis.along(&Domain::UNIT);
+    let percent = |v: Float| Val::Percent(100. * v);
+    for (container, children) in bars {
+        // the bars are rebuilt, there are at most `2^MAX_N` of them
+        for child in children {
+            commands.entity(child).despawn_recursive();
+        }
+        commands.entity(container).with_children(|parent| {
+            for step in &slice.steps {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: percent((step.min - min) / (max - min)),
+                            width: percent((step.max - step.min) / (max - min)),
+                            bottom: percent((step.height.min(0.) - low) / (high - low)),
+                            height: percent(step.height.abs() / (high - low)),
+                            border: UiRect::all(Val::Px(1.0)),
+                            ..default()
+                        },
+                        background_color: PROFILE_COLOR.with_a(0.7).into(),
+                        border_color: Color::BLACK.into(),
+                        ..default()
+                    },
+                    SliceChart::Bar,
+                ));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::mesh::VertexAttributeValues;
+
+    use super::*;
+    use crate::controls::{ControlEvent, ControlInput};
+    use crate::testing::*;
+    use crate::Plane;
+
+    /// `(column along x, alpha, start x, scale x)` of the visible cubes of `level`.
+    fn cut(app: &mut App, level: u8) -> Vec<(u32, f32, f32, f32)> {
+        let mut query = app
+            .world
+            .query::<(&Cube, &Visibility, &Transform, &Handle<StandardMaterial>)>();
+        let cubes: Vec<_> = query
+            .iter(&app.world)
+            .filter(|(cube, vis, _, _)| cube.size_n == level && **vis != Visibility::Hidden)
+            .map(|(cube, _, transform, handle)| (cube.cell.i, *transform, handle.clone()))
+            .collect();
+        let materials = app.world.resource::<Assets<StandardMaterial>>();
+        cubes
+            .into_iter()
+            .map(|(i, transform, handle)| {
+                let alpha = materials.get(&handle).unwrap().base_color.a();
+                (i, alpha, transform.translation.x, transform.scale.x)
+            })
+            .collect()
+    }
+
+    fn chart(app: &mut App) -> (String, Display, usize) {
+        let mut query = app.world.query::<(&SliceChart, Option<&Text>, &Style)>();
+        let mut out = (String::new(), Display::None, 0);
+        for (part, text, style) in query.iter(&app.world) {
+            match part {
+                SliceChart::Text => out.0 = text.unwrap().sections[0].value.clone(),
+                SliceChart::Panel => out.1 = style.display,
+                SliceChart::Bar => out.2 += 1,
+                SliceChart::Bars => {}
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn slice_cuts_the_cubes() {
+        let mut app = test_app();
+        config(&mut app).n = 2;
+        app.update();
+        assert!(cut(&mut app, 2)
+            .iter()
+            .all(|&(_, a, _, s)| a == 1. && s == 1.));
+        assert_eq!(chart(&mut app).1, Display::None);
+
+        key(&mut app, KeyCode::S);
+        assert!(config(&mut app).show_slice);
+        let mut query = app.world.query_filtered::<&Visibility, With<SlicePlane>>();
+        assert_eq!(*query.single(&app.world), Visibility::Visible);
+
+        app.world.send_event(ControlEvent {
+            key: "slice_position",
+            input: ControlInput::Set(0.6),
+        });
+        app.update();
+        // x = 0.6 cuts the third column of level 2 and the second one of level 1
+        for (level, column) in [(1, 1), (2, 2)] {
+            let size = 0.5f32.powi(level.into());
+            for (i, alpha, x, scale) in cut(&mut app, level) {
+                match i.cmp(&column) {
+                    std::cmp::Ordering::Less => assert!(alpha < 1.),
+                    std::cmp::Ordering::Equal => {
+                        assert_eq!(alpha, 1.);
+                        assert_eq!(x, 0.6);
+                        let end = size * (i + 1) as f32;
+                        assert!((scale - (end - 0.6) / size).abs() < 1e-5);
+                    }
+                    std::cmp::Ordering::Greater => assert_eq!((alpha, scale), (1., 1.)),
+                }
+            }
+        }
+
+        // the function graph starts at the cut
+        let mut query = app.world.query_filtered::<&Handle<Mesh>, With<Plane>>();
+        let mesh = query.single(&app.world).clone();
+        let mesh = app.world.resource::<Assets<Mesh>>().get(&mesh).unwrap();
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("mesh without positions");
+        };
+        assert!(positions.iter().all(|p| p[0] >= 0.6));
+
+        key(&mut app, KeyCode::S);
+        assert!(cut(&mut app, 2)
+            .iter()
+            .all(|&(i, a, x, s)| a == 1. && s == 1. && x == i as f32 / 4.));
+    }
+
+    #[test]
+    fn slice_chart_shows_the_sum_of_the_cut() {
+        let mut app = test_app();
+        config(&mut app).n = 2;
+        config(&mut app).show_slice = true;
+        app.update();
+        let (text, display, bars) = chart(&mut app);
+        assert_eq!(display, Display::Flex);
+        assert_eq!(bars, 4);
+        // lower left samples of the column from 0.5 to 0.75: 0.5, 0.75, 1, 1.25
+        assert!(text.contains("Schnitt bei x = 0.500"), "{text}");
+        assert!(text.contains("Summe entlang y: 0.87500"), "{text}");
+        assert!(text.contains("Integral entlang y: 1.00000"), "{text}");
+
+        press(&mut app, "slice_axis", ControlInput::Press);
+        config(&mut app).n = 3;
+        app.update();
+        let (text, _, bars) = chart(&mut app);
+        assert_eq!(bars, 8);
+        assert!(text.contains("Schnitt bei y = 0.500"), "{text}");
+
+        config(&mut app).show_slice = false;
+        app.update();
+        assert_eq!(chart(&mut app).1, Display::None);
+    }
+}
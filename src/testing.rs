@@ -0,0 +1,145 @@
+//! The headless app the tests of the modules run in, and the helpers they share.
+//!
+//! The app is built from `MinimalPlugins` and the asset storages the systems write to,
+//! so everything runs without a window or a GPU.
+
+use bevy::{
+    gizmos::GizmoPlugin,
+    input::{keyboard::KeyboardInput, ButtonState, InputPlugin},
+    transform::{TransformPlugin, TransformSystem},
+    ui::{ui_layout_system, UiSurface},
+    window::{WindowResized, WindowScaleFactorChanged},
+};
+
+use crate::controls::{ControlButton, ControlLabel, ControlRegistry};
+use crate::input::InputMap;
+use crate::*;
+
+pub(crate) fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), InputPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Shader>()
+        .add_plugins((
+            GizmoPlugin,
+            IntegralPlugin::default(),
+            IntegralUiPlugin,
+            IntegralKeyboardPlugin,
+        ))
+        .init_resource::<UiScale>();
+    // the defaults, whatever `keybindings.cfg` there is
+    let map = InputMap::new(app.world.resource::<ControlRegistry>());
+    app.insert_resource(map);
+    app.update();
+    app
+}
+
+pub(crate) fn config(app: &mut App) -> Mut<'_, Config> {
+    app.world.resource_mut::<Config>()
+}
+
+/// Counts the cubes per level as `(visible, hidden)`.
+pub(crate) fn cubes(app: &mut App, level: u8) -> (usize, usize) {
+    let mut query = app.world.query::<(&Cube, &Visibility)>();
+    let mut count = (0, 0);
+    for (cube, vis) in query.iter(&app.world) {
+        if cube.size_n != level {
+            continue;
+        }
+        match vis {
+            Visibility::Hidden => count.1 += 1,
+            _ => count.0 += 1,
+        }
+    }
+    count
+}
+
+pub(crate) fn button(app: &mut App, key: &str, input: ControlInput) -> Entity {
+    let mut query = app.world.query::<(Entity, &ControlButton)>();
+    let mut buttons = query
+        .iter(&app.world)
+        .filter(|(_, button)| button.key == key && button.input == input);
+    let (button, _) = buttons.next().unwrap();
+    assert!(buttons.next().is_none());
+    button
+}
+
+pub(crate) fn press(app: &mut App, key: &str, input: ControlInput) -> Entity {
+    let button = button(app, key, input);
+    *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+    app.update();
+    *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::None;
+    app.update();
+    button
+}
+
+pub(crate) fn key(app: &mut App, key_code: KeyCode) {
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+    }
+}
+
+pub(crate) fn label(app: &mut App, button: Entity) -> String {
+    let child = app.world.get::<Children>(button).unwrap()[0];
+    app.world.get::<Text>(child).unwrap().sections[0]
+        .value
+        .clone()
+}
+
+pub(crate) fn control_label(app: &mut App, index: usize) -> String {
+    let mut query = app.world.query::<(&Text, &ControlLabel)>();
+    let (text, _) = query
+        .iter(&app.world)
+        .find(|(_, label)| label.index == index)
+        .unwrap();
+    text.sections[0].value.clone()
+}
+
+pub(crate) fn n_label(app: &mut App) -> String {
+    control_label(app, 0)
+}
+
+pub(crate) fn plane_visibility(app: &mut App) -> Vec<Visibility> {
+    let mut query = app.world.query_filtered::<&Visibility, With<Plane>>();
+    query.iter(&app.world).copied().collect()
+}
+
+pub(crate) fn heading(app: &App, camera: Entity) -> views::CameraView {
+    views::CameraView::of(app.world.get::<PanOrbitCamera>(camera).unwrap())
+}
+
+/// A window of the default size `1280 × 720`, with the control panel laid out in its left
+/// `281` pixels, and the primary camera moved by the fingers.
+pub(crate) fn touch_app() -> (App, Entity) {
+    let mut app = test_app();
+    app.add_plugins(TransformPlugin)
+        .init_resource::<ActiveCameraData>()
+        .init_resource::<UiScale>()
+        .init_resource::<UiSurface>()
+        .add_event::<WindowScaleFactorChanged>()
+        .add_event::<WindowResized>()
+        .add_systems(Update, (touch_camera, panel_blocks_mouse))
+        .add_systems(
+            PostUpdate,
+            ui_layout_system.before(TransformSystem::TransformPropagate),
+        );
+    app.world.spawn((Window::default(), PrimaryWindow));
+    let camera = app
+        .world
+        .spawn((
+            Camera::default(),
+            Transform::default(),
+            Projection::default(),
+            default_camera(),
+            View::Primary,
+        ))
+        .id();
+    (app, camera)
+}
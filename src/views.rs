@@ -0,0 +1,208 @@
+//! Preset views and bookmarks of the camera.
+//!
+//! A view only sets the targets of the [`PanOrbitCamera`]s, which then glide there with
+//! their usual smoothing. The bookmarks are saved in the [scene file](crate::scene).
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, TAU};
+
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{default_camera, View};
+
+/// Where a camera looks at and from where.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraView {
+    pub focus: Vec3,
+    pub alpha: f32,
+    pub beta: f32,
+    pub radius: f32,
+}
+
+impl CameraView {
+    /// Where `camera` is heading.
+    pub fn of(camera: &PanOrbitCamera) -> Self {
+        Self {
+            focus: camera.target_focus,
+            alpha: camera.target_alpha,
+            beta: camera.target_beta,
+            radius: camera.target_radius,
+        }
+    }
+
+    /// Sends `camera` here. The angle around the vertical axis is shifted by whole turns so
+    /// the camera takes the short way.
+    pub fn apply(&self, camera: &mut PanOrbitCamera) {
+        let current = camera.alpha.unwrap_or(camera.target_alpha);
+        camera.target_focus = self.focus;
+        camera.target_alpha = self.alpha + TAU * ((current - self.alpha) / TAU).round();
+        camera.target_beta = self.beta;
+        camera.target_radius = self.radius;
+    }
+}
+
+/// The views that are always there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preset {
+    /// Where the camera starts.
+    #[default]
+    Default,
+    /// Straight down onto the domain.
+    Top,
+    /// Along the `y` axis of the domain, onto `x` and the height.
+    Front,
+    /// Along the `x` axis of the domain, onto `y` and the height.
+    Side,
+    Isometric,
+}
+
+impl Preset {
+    pub const ALL: [Self; 5] = [
+        Self::Default,
+        Self::Top,
+        Self::Front,
+        Self::Side,
+        Self::Isometric,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Default => "Standard",
+            Self::Top => "oben",
+            Self::Front => "vorne",
+            Self::Side => "seitlich",
+            Self::Isometric => "isometrisch",
+        }
+    }
+
+    pub fn view(self) -> CameraView {
+        // the middle of the unit square, halfway up the graph of `f`
+        let focus = Vec3::new(0.5, 1.0, 0.5);
+        let at = |alpha, beta| CameraView {
+            focus,
+            alpha,
+            beta,
+            radius: 4.5,
+        };
+        match self {
+            Self::Default => CameraView::of(&default_camera()),
+            Self::Top => at(0., FRAC_PI_2),
+            Self::Front => at(0., 0.),
+            Self::Side => at(FRAC_PI_2, 0.),
+            // the diagonal of a cube
+            Self::Isometric => at(FRAC_PI_4, (1. / 2f32.sqrt()).atan()),
+        }
+    }
+}
+
+/// The chosen preset and the bookmarks.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct CameraViews {
+    pub preset: Preset,
+    pub bookmarks: Vec<CameraView>,
+    /// The bookmark that was shown last.
+    pub current: Option<usize>,
+}
+
+/// Sends all cameras to `view`, both sides of the split screen look the same way afterwards.
+pub fn show(world: &mut World, view: CameraView) {
+    let mut cameras = world.query::<&mut PanOrbitCamera>();
+    for mut camera in cameras.iter_mut(world) {
+        view.apply(&mut camera);
+    }
+}
+
+pub fn show_preset(world: &mut World, preset: Preset) {
+    world.resource_mut::<CameraViews>().preset = preset;
+    show(world, preset.view());
+}
+
+/// Bookmarks where the primary camera is heading.
+pub fn add_bookmark(world: &mut World) {
+    let mut cameras = world.query::<(&PanOrbitCamera, &View)>();
+    let Some(view) = cameras
+        .iter(world)
+        .find(|(_, view)| **view == View::Primary)
+        .map(|(camera, _)| CameraView::of(camera))
+    else {
+        warn!("there is no camera to bookmark");
+        return;
+    };
+    let mut views = world.resource_mut::<CameraViews>();
+    views.bookmarks.push(view);
+    views.current = Some(views.bookmarks.len() - 1);
+}
+
+/// Shows the bookmark after the one shown last, starting over after the last one.
+pub fn next_bookmark(world: &mut World) {
+    let mut views = world.resource_mut::<CameraViews>();
+    if views.bookmarks.is_empty() {
+        return;
+    }
+    let next = views
+        .current
+        .map_or(0, |current| (current + 1) % views.bookmarks.len());
+    views.current = Some(next);
+    let view = views.bookmarks[next];
+    show(world, view);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controls::ControlInput;
+    use crate::testing::*;
+
+    fn orbit_camera(app: &mut App) -> Entity {
+        app.world.spawn((default_camera(), View::Primary)).id()
+    }
+
+    #[test]
+    fn preset_views_move_the_camera_targets() {
+        use std::f32::consts::TAU;
+
+        let mut app = test_app();
+        let camera = orbit_camera(&mut app);
+        let view = press(&mut app, "view", ControlInput::Press);
+        assert_eq!(label(&mut app, view), "Ansicht: oben");
+        assert_eq!(heading(&app, camera), Preset::Top.view());
+
+        // a camera that turned around a few times takes the short way
+        app.world.get_mut::<PanOrbitCamera>(camera).unwrap().alpha = Some(3. * TAU + 0.1);
+        press(&mut app, "view", ControlInput::Press);
+        assert_eq!(heading(&app, camera).alpha, 3. * TAU);
+        assert_eq!(heading(&app, camera).beta, 0.);
+
+        key(&mut app, KeyCode::R);
+        assert_eq!(label(&mut app, view), "Ansicht: Standard");
+        let default = Preset::Default.view();
+        assert_eq!(heading(&app, camera).focus, default.focus);
+        assert_eq!(heading(&app, camera).radius, default.radius);
+    }
+
+    #[test]
+    fn bookmarks_cycle() {
+        let mut app = test_app();
+        let camera = orbit_camera(&mut app);
+        // nothing to show yet
+        press(&mut app, "next_bookmark", ControlInput::Press);
+        assert_eq!(heading(&app, camera), Preset::Default.view());
+
+        let mut marked = Vec::new();
+        for radius in [2., 3., 4.] {
+            app.world
+                .get_mut::<PanOrbitCamera>(camera)
+                .unwrap()
+                .target_radius = radius;
+            add_bookmark(&mut app.world);
+            marked.push(heading(&app, camera));
+        }
+        assert_eq!(app.world.resource::<CameraViews>().bookmarks, marked);
+
+        // the last one was just bookmarked, so the first one is next
+        for expected in [2., 3., 4., 2.] {
+            key(&mut app, KeyCode::V);
+            assert_eq!(heading(&app, camera).radius, expected);
+        }
+    }
+}
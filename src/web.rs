@@ -0,0 +1,46 @@
+//! Browser APIs for the wasm build, which has no file system.
+
+use js_sys::Array;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Storage, Url};
+
+/// Lets the browser download `contents` as a file called `name`.
+pub fn download(name: &str, contents: &str, mime: &str) -> Result<(), JsValue> {
+    let window = web_sys::window().ok_or("no window")?;
+    let document = window.document().ok_or("no document")?;
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    let parts = Array::of1(&JsValue::from_str(contents));
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let anchor = document
+        .create_element("a")?
+        .dyn_into::<HtmlAnchorElement>()?;
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+    // some browsers only start the download after the click returned, so the url has to
+    // outlive it
+    let revoke = Closure::once_into_js(move || {
+        let _ = Url::revoke_object_url(&url);
+    });
+    window.set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), 0)?;
+    Ok(())
+}
+
+fn local_storage() -> Result<Storage, JsValue> {
+    web_sys::window()
+        .ok_or("no window")?
+        .local_storage()?
+        .ok_or_else(|| "no local storage".into())
+}
+
+/// Keeps `value` in the local storage of the page, where it outlives reloads.
+pub fn store(key: &str, value: &str) -> Result<(), JsValue> {
+    local_storage()?.set_item(key, value)
+}
+
+/// What [`store`] kept under `key`, if anything.
+pub fn stored(key: &str) -> Result<Option<String>, JsValue> {
+    local_storage()?.get_item(key)
+}
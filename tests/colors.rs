@@ -0,0 +1,62 @@
+use bevy::prelude::Color;
+use integral::colors::{ColorMode, ColorScale, Palette};
+
+fn assert_color(a: Color, b: Color) {
+    let (a, b) = (a.as_rgba_f32(), b.as_rgba_f32());
+    for (x, y) in a.iter().zip(b) {
+        assert!((x - y).abs() <= 1. / 255., "{a:?} != {b:?}");
+    }
+}
+
+#[test]
+fn classic_palette_matches_the_old_level_colors() {
+    for n in 1..=6u8 {
+        let t = (n - 1) as f32 / 5.;
+        assert_color(
+            Palette::Classic.sample(t),
+            Color::rgb_u8(124, 40 * n, 255 / n),
+        );
+    }
+}
+
+#[test]
+fn palettes_hit_their_end_points() {
+    assert_color(Palette::Viridis.sample(0.), Color::rgb_u8(0x44, 0x01, 0x54));
+    assert_color(Palette::Viridis.sample(1.), Color::rgb_u8(0xfd, 0xe7, 0x25));
+    assert_color(Palette::Magma.sample(1.), Color::rgb_u8(0xfc, 0xfd, 0xbf));
+    assert_color(Palette::Cividis.sample(0.), Color::rgb_u8(0x00, 0x22, 0x4e));
+    for palette in Palette::ALL {
+        // out of range and undefined values are clamped
+        assert_color(palette.sample(-3.), palette.sample(0.));
+        assert_color(palette.sample(7.), palette.sample(1.));
+        assert_color(palette.sample(f32::NAN), palette.sample(0.));
+    }
+}
+
+#[test]
+fn perceptual_palettes_get_brighter() {
+    for palette in [Palette::Viridis, Palette::Magma, Palette::Cividis] {
+        let mut last = -1.;
+        for i in 0..=20 {
+            let [r, g, b, _] = palette.sample(i as f32 / 20.).as_rgba_f32();
+            let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+            assert!(luminance > last, "{palette:?} at {i}");
+            last = luminance;
+        }
+    }
+}
+
+#[test]
+fn scale_positions() {
+    let scale = ColorScale {
+        mode: ColorMode::Error,
+        palette: Palette::Viridis,
+        min: -2.,
+        max: 2.,
+    };
+    assert_eq!(scale.position(-2.), 0.);
+    assert_eq!(scale.position(0.), 0.5);
+    assert_eq!(scale.position(2.), 1.);
+    let flat = ColorScale { max: -2., ..scale };
+    assert_eq!(flat.position(5.), 0.5);
+}
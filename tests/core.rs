@@ -0,0 +1,308 @@
+use integral::{
+    core::{self, Axis, Domain, Partition, Rule},
+    export::Export,
+    f, Float, Side,
+};
+
+const EPS: Float = 1e-4;
+
+fn assert_close(a: Float, b: Float) {
+    assert!((a - b).abs() <= EPS * (1. + b.abs()), "{a} != {b}");
+}
+
+fn random_domain(rng: &mut fastrand::Rng) -> Domain {
+    let min_x = rng.f32() * 4. - 2.;
+    let min_y = rng.f32() * 4. - 2.;
+    Domain {
+        min_x,
+        max_x: min_x + 0.1 + rng.f32() * 3.,
+        min_y,
+        max_y: min_y + 0.1 + rng.f32() * 3.,
+    }
+}
+
+fn random_partition(rng: &mut fastrand::Rng) -> Partition {
+    Partition {
+        nx: rng.u32(1..20),
+        ny: rng.u32(1..20),
+    }
+}
+
+#[test]
+fn dyadic_partition() {
+    assert_eq!(Partition::dyadic(0), Partition { nx: 1, ny: 1 });
+    assert_eq!(Partition::dyadic(3), Partition { nx: 8, ny: 8 });
+    assert_eq!(Partition::dyadic(3).len(), 64);
+    assert!(Partition::dyadic(3).refines(&Partition::dyadic(1)));
+    assert!(!Partition::dyadic(1).refines(&Partition::dyadic(3)));
+    assert!(!Partition::uniform(3).refines(&Partition::uniform(2)));
+}
+
+#[test]
+fn cell_bounds() {
+    let domain = Domain {
+        min_x: -1.,
+        max_x: 1.,
+        min_y: 2.,
+        max_y: 6.,
+    };
+    let partition = Partition { nx: 4, ny: 2 };
+    assert_eq!(partition.bounds(&domain, 0, 0), (-1., -0.5, 2., 4.));
+    assert_eq!(partition.bounds(&domain, 3, 1), (0.5, 1., 4., 6.));
+}
+
+#[test]
+fn sample_points() {
+    let bounds = (0., 2., 4., 8.);
+    assert_eq!(Rule::LowerLeft.sample_point(bounds), (0., 4.));
+    assert_eq!(Rule::LowerRight.sample_point(bounds), (2., 4.));
+    assert_eq!(Rule::UpperLeft.sample_point(bounds), (0., 8.));
+    assert_eq!(Rule::UpperRight.sample_point(bounds), (2., 8.));
+    assert_eq!(Rule::Midpoint.sample_point(bounds), (1., 6.));
+}
+
+#[test]
+fn cells_are_ordered_and_cover_the_domain() {
+    let partition = Partition { nx: 3, ny: 5 };
+    let cells = core::cells(&f, &Domain::UNIT, &partition, Rule::Midpoint);
+    assert_eq!(cells.len(), 15);
+    assert_eq!((cells[0].i, cells[0].j), (0, 0));
+    assert_eq!((cells[1].i, cells[1].j), (0, 1));
+    assert_eq!((cells[5].i, cells[5].j), (1, 0));
+    let area: Float = cells.iter().map(|cell| cell.area()).sum();
+    assert_close(area, 1.);
+    for cell in &cells {
+        assert!(cell.min_x <= cell.sample_x && cell.sample_x <= cell.max_x);
+        assert!(cell.min_y <= cell.sample_y && cell.sample_y <= cell.max_y);
+        assert_eq!(cell.height, f(cell.sample_x, cell.sample_y));
+        assert_eq!(cell.base, 0.);
+    }
+}
+
+#[test]
+fn lower_and_upper_sums_of_f() {
+    // f(x, y) = x + y is increasing, so the corners give the lower and upper sums
+    // 1 - 2^-n and 1 + 2^-n around the exact value 1.
+    for n in 0..=6 {
+        let partition = Partition::dyadic(n);
+        let size = Float::powi(2., -(n as i32));
+        let lower = core::riemann(&f, &Domain::UNIT, &partition, Rule::LowerLeft).sum;
+        let upper = core::riemann(&f, &Domain::UNIT, &partition, Rule::UpperRight).sum;
+        let mid = core::riemann(&f, &Domain::UNIT, &partition, Rule::Midpoint).sum;
+        assert_close(lower, 1. - size);
+        assert_close(upper, 1. + size);
+        assert_close(mid, 1.);
+    }
+}
+
+#[test]
+fn refinement_adds_up_to_the_finer_sum() {
+    let g = |x: Float, y: Float| (x * 3.).sin() + y * y;
+    for rule in Rule::ALL {
+        let mut sum = core::riemann(&g, &Domain::UNIT, &Partition::dyadic(1), rule).sum;
+        for n in 2..=5 {
+            let cells = core::level_cells(&g, &Domain::UNIT, n, n - 1, rule);
+            assert_eq!(cells.len(), Partition::dyadic(n).len());
+            sum += cells.iter().map(|cell| cell.volume).sum::<Float>();
+            let fine = core::riemann(&g, &Domain::UNIT, &Partition::dyadic(n), rule).sum;
+            assert_close(sum, fine);
+        }
+    }
+}
+
+#[test]
+fn refinement_base_is_the_parent_height() {
+    let cells = core::level_cells(&f, &Domain::UNIT, 3, 1, Rule::LowerLeft);
+    for cell in cells {
+        let parent_x = if cell.i < 4 { 0. } else { 0.5 };
+        let parent_y = if cell.j < 4 { 0. } else { 0.5 };
+        assert_eq!(cell.base, f(parent_x, parent_y));
+    }
+}
+
+#[test]
+#[should_panic]
+fn refine_rejects_unrelated_partitions() {
+    core::refine(
+        &f,
+        &Domain::UNIT,
+        &Partition::uniform(2),
+        &Partition::uniform(3),
+        Rule::LowerLeft,
+    );
+}
+
+#[test]
+fn export_matches_core() {
+    let side = |n, show_incremental_cubes| Side {
+        n,
+        rule: Rule::LowerLeft,
+        show_incremental_cubes,
+    };
+    let export = Export::new(&side(4, false));
+    let sum = core::riemann(&f, &Domain::UNIT, &Partition::dyadic(4), Rule::LowerLeft).sum;
+    // without the cell in the corner, where f is 0
+    assert_eq!(export.cells.len(), 255);
+    assert_close(export.summary.volume, sum);
+
+    let incremental = Export::new(&side(4, true));
+    assert_eq!(incremental.summary.levels.len(), 4);
+    assert_eq!(incremental.cells.len(), 3 + 12 + 48 + 192);
+    assert_close(incremental.summary.volume, sum);
+    assert!(Export::new(&side(0, true)).cells.is_empty());
+}
+
+#[test]
+fn exact_integral() {
+    assert_close(core::integrate(&f, &Domain::UNIT), 1.);
+    // degree 5 in each variable is integrated exactly
+    let g = |x: Float, y: Float| x.powi(5) * y.powi(4) - 3. * x * y;
+    let domain = Domain {
+        min_x: -1.,
+        max_x: 2.,
+        min_y: 0.,
+        max_y: 1.,
+    };
+    let exact = (64. - 1.) / 6. / 5. - 3. * (4. - 1.) / 2. / 2.;
+    assert_close(core::integrate(&g, &domain), exact);
+}
+
+#[test]
+fn cell_errors_add_up_to_the_total_error() {
+    let g = |x: Float, y: Float| x * x * y + 1.;
+    let approx = core::riemann(&g, &Domain::UNIT, &Partition::dyadic(3), Rule::UpperLeft);
+    let error: Float = approx.cells.iter().map(|cell| cell.error(&g)).sum();
+    assert_close(error, approx.sum - core::integrate(&g, &Domain::UNIT));
+}
+
+#[test]
+fn property_constant_integrand() {
+    let mut rng = fastrand::Rng::with_seed(7);
+    for _ in 0..200 {
+        let c = rng.f32() * 10. - 5.;
+        let domain = random_domain(&mut rng);
+        let partition = random_partition(&mut rng);
+        let rule = Rule::ALL[rng.usize(..Rule::ALL.len())];
+        let approx = core::riemann(&|_, _| c, &domain, &partition, rule);
+        assert_eq!(approx.cells.len(), partition.len());
+        assert_close(approx.sum, c * domain.area());
+    }
+}
+
+#[test]
+fn property_affine_integrand() {
+    let mut rng = fastrand::Rng::with_seed(42);
+    for _ in 0..200 {
+        let (a, b, c) = (
+            rng.f32() * 4. - 2.,
+            rng.f32() * 4. - 2.,
+            rng.f32() * 4. - 2.,
+        );
+        let g = move |x: Float, y: Float| a + b * x + c * y;
+        let domain = random_domain(&mut rng);
+        let partition = random_partition(&mut rng);
+        let exact = domain.area()
+            * g(
+                (domain.min_x + domain.max_x) / 2.,
+                (domain.min_y + domain.max_y) / 2.,
+            );
+
+        // the midpoint rule and the average of opposite corners are exact for affine functions
+        let mid = core::riemann(&g, &domain, &partition, Rule::Midpoint).sum;
+        assert_close(mid, exact);
+        let lower = core::riemann(&g, &domain, &partition, Rule::LowerLeft).sum;
+        let upper = core::riemann(&g, &domain, &partition, Rule::UpperRight).sum;
+        assert_close((lower + upper) / 2., exact);
+        let lr = core::riemann(&g, &domain, &partition, Rule::LowerRight).sum;
+        let ul = core::riemann(&g, &domain, &partition, Rule::UpperLeft).sum;
+        assert_close((lr + ul) / 2., exact);
+    }
+}
+
+#[test]
+fn property_volumes_sum_to_the_total() {
+    let mut rng = fastrand::Rng::with_seed(3);
+    let g = |x: Float, y: Float| (x * y).cos() - x;
+    for _ in 0..100 {
+        let domain = random_domain(&mut rng);
+        let partition = random_partition(&mut rng);
+        let approx = core::riemann(&g, &domain, &partition, Rule::Midpoint);
+        let by_height: Float = approx
+            .cells
+            .iter()
+            .map(|cell| cell.area() * cell.height)
+            .sum();
+        assert_close(approx.sum, by_height);
+    }
+}
+
+#[test]
+fn slice_through_the_unit_square() {
+    let slice = core::slice(
+        &f,
+        &Domain::UNIT,
+        &Partition::uniform(4),
+        Rule::LowerLeft,
+        Axis::X,
+        0.6,
+    );
+    // x = 0.6 lies in the column from 0.5 to 0.75, sampled at x = 0.5
+    let heights: Vec<_> = slice.steps.iter().map(|step| step.height).collect();
+    assert_eq!(heights, [0.5, 0.75, 1., 1.25]);
+    assert_close(slice.sum, 0.875);
+    assert_close(
+        core::integrate_slice(&f, &Domain::UNIT, Axis::X, 0.6),
+        0.6 + 0.5,
+    );
+
+    // on a border the column after it is cut, at the end the last one
+    let column = |c| Axis::Y.column(&Domain::UNIT, &Partition::uniform(4), c);
+    assert_eq!(
+        (column(0.), column(0.5), column(1.), column(2.)),
+        (0, 2, 3, 3)
+    );
+}
+
+#[test]
+fn property_slices_sum_to_the_riemann_sum() {
+    // Fubini for step functions: integrating the slice sums over the other axis
+    let mut rng = fastrand::Rng::with_seed(4);
+    let g = |x: Float, y: Float| (x * y).sin() + x * x - y;
+    for _ in 0..50 {
+        let domain = random_domain(&mut rng);
+        let partition = random_partition(&mut rng);
+        let rule = Rule::ALL[rng.usize(..Rule::ALL.len())];
+        let riemann = core::riemann(&g, &domain, &partition, rule).sum;
+        for axis in Axis::ALL {
+            let (min, max) = axis.range(&domain);
+            let columns = match axis {
+                Axis::X => partition.nx,
+                Axis::Y => partition.ny,
+            };
+            let width = (max - min) / columns as Float;
+            let slabs = core::slabs(&g, &domain, &partition, rule, axis);
+            assert_eq!(slabs.len(), columns as usize);
+            let sum: Float = slabs.iter().map(|slab| width * slab.sum).sum();
+            assert_close(sum, riemann);
+        }
+    }
+}
+
+#[test]
+fn property_slices_converge_to_the_line_integral() {
+    let mut rng = fastrand::Rng::with_seed(5);
+    let g = |x: Float, y: Float| (x * y).cos() - x;
+    let partition = Partition::uniform(256);
+    for _ in 0..50 {
+        let domain = random_domain(&mut rng);
+        let axis = Axis::ALL[rng.usize(..2)];
+        let (min, max) = axis.range(&domain);
+        let c = min + rng.f32() * (max - min);
+        let slice = core::slice(&g, &domain, &partition, Rule::Midpoint, axis, c);
+        // the cells are sampled in the middle of the cut column, not at `c`
+        let width = (max - min) / 256.;
+        let mid = min + width * (axis.column(&domain, &partition, c) as Float + 0.5);
+        let exact = core::integrate_slice(&g, &domain, axis, mid);
+        assert!((slice.sum - exact).abs() < 1e-3, "{} != {exact}", slice.sum);
+    }
+}
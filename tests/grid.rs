@@ -0,0 +1,87 @@
+use bevy::{prelude::*, render::mesh::VertexAttributeValues};
+use integral::{
+    core::Domain,
+    f,
+    grid::{grid_mesh, tick_label, GridBounds, GridSpacing},
+    Float,
+};
+
+#[test]
+fn spacing_follows_the_camera_distance() {
+    // the default camera
+    let spacing = GridSpacing::new(5.5);
+    assert_eq!(spacing.fadeout_distance, 3.);
+    assert_eq!(spacing.minor_per_major, 3);
+    assert_eq!(spacing.ticks(0., 2.), [1., 2.]);
+    assert_eq!(spacing.ticks(-2., 1.), [-2., -1., 1.]);
+
+    // close up the minor lines are labeled too
+    let spacing = GridSpacing::new(1.);
+    assert_eq!(spacing.fadeout_distance, 1.);
+    assert_eq!(spacing.minor_per_major, 6);
+    let ticks = spacing.ticks(0., 1.);
+    assert_eq!(ticks.len(), 6);
+    assert_eq!(ticks[5], 1.);
+
+    // far away there is at least one line per unit
+    assert_eq!(GridSpacing::new(400.).minor_per_major, 1);
+}
+
+#[test]
+fn tick_labels_are_short() {
+    assert_eq!(tick_label(1.), "1");
+    assert_eq!(tick_label(0.5), "0.5");
+    assert_eq!(tick_label(-0.5), "-0.5");
+    assert_eq!(tick_label(1. / 3.), "0.33");
+    assert_eq!(tick_label(2. / 3.), "0.67");
+}
+
+#[test]
+fn bounds_cover_domain_and_function() {
+    let bounds = GridBounds::new(&Domain::UNIT, &f);
+    assert_eq!(bounds.min, Vec3::ZERO);
+    // the height is the world's y
+    assert_eq!(bounds.max, Vec3::new(1., 2., 1.));
+
+    let domain = Domain {
+        min_x: -1.5,
+        max_x: 0.5,
+        min_y: -1.,
+        max_y: 1.,
+    };
+    let g = |x: Float, y: Float| x - 3. * y;
+    let bounds = GridBounds::new(&domain, &g);
+    assert_eq!(bounds.min, Vec3::new(-2., -5., -1.));
+    assert_eq!(bounds.max, Vec3::new(1., 4., 1.));
+}
+
+fn positions(mesh: &Mesh) -> Vec<Vec3> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("mesh without positions");
+    };
+    positions.iter().copied().map(Vec3::from).collect()
+}
+
+#[test]
+fn grid_mesh_spans_the_bounds() {
+    let bounds = GridBounds {
+        min: Vec3::new(-1., -2., 0.),
+        max: Vec3::new(1., 2., 1.),
+    };
+    let spacing = GridSpacing::new(5.5);
+    for full in [false, true] {
+        let mesh = grid_mesh(&bounds, &spacing, full);
+        let positions = positions(&mesh);
+        assert_eq!(positions.len() % 2, 0);
+        let min = positions.iter().copied().fold(Vec3::MAX, Vec3::min);
+        let max = positions.iter().copied().fold(Vec3::MIN, Vec3::max);
+        // the axes reach a bit further than the lines
+        assert!(min.cmple(bounds.min).all() && max.cmpge(bounds.max).all());
+        assert!((max - bounds.max).max_element() <= 0.25);
+    }
+    let lines = |full| positions(&grid_mesh(&bounds, &spacing, full)).len() / 2;
+    // 7 × 13 × 4 lines per axis fill the box, the planes through the origin need far less
+    assert!(lines(true) > 3 * lines(false));
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::{Ray, Vec3};
+use integral::{
+    core::{self, Domain, Rule},
+    f,
+    picking::{cell_box, describe, nearest, ray_box},
+};
+
+fn down(x: f32, z: f32) -> Ray {
+    Ray {
+        origin: Vec3::new(x, 10., z),
+        direction: Vec3::NEG_Y,
+    }
+}
+
+#[test]
+fn ray_box_intersection() {
+    let (min, max) = (Vec3::ZERO, Vec3::ONE);
+    assert_eq!(ray_box(down(0.5, 0.5), min, max), Some(9.));
+    assert_eq!(ray_box(down(1.5, 0.5), min, max), None);
+    // pointing away
+    let up = Ray {
+        direction: Vec3::Y,
+        ..down(0.5, 0.5)
+    };
+    assert_eq!(ray_box(up, min, max), None);
+    // starting inside
+    let inside = Ray {
+        origin: Vec3::splat(0.5),
+        direction: Vec3::new(1., 1., 0.).normalize(),
+    };
+    assert_eq!(ray_box(inside, min, max), Some(0.));
+    // diagonal through a corner region
+    let diagonal = Ray {
+        origin: Vec3::new(-1., -1., 0.5),
+        direction: Vec3::new(1., 1., 0.).normalize(),
+    };
+    let distance = ray_box(diagonal, min, max).unwrap();
+    assert!((distance - 2f32.sqrt()).abs() < 1e-6);
+}
+
+#[test]
+fn cell_boxes_follow_the_stacking() {
+    let cells = core::level_cells(&f, &Domain::UNIT, 2, 1, Rule::UpperRight);
+    for cell in &cells {
+        let (min, max) = cell_box(cell);
+        assert!(min.y <= max.y);
+        assert_eq!(min.y, cell.base.min(cell.height));
+        assert_eq!(max.y, cell.base.max(cell.height));
+        assert_eq!((min.x, min.z), (cell.min_x, cell.min_y));
+    }
+}
+
+#[test]
+fn nearest_cell_from_above() {
+    let level_1 = core::level_cells(&f, &Domain::UNIT, 1, 0, Rule::LowerLeft);
+    let level_2 = core::level_cells(&f, &Domain::UNIT, 2, 1, Rule::LowerLeft);
+    let cells = level_1
+        .iter()
+        .map(|cell| ((1, cell.i, cell.j), cell))
+        .chain(level_2.iter().map(|cell| ((2, cell.i, cell.j), cell)));
+    // the finer cubes sit on top of the coarser ones
+    assert_eq!(nearest(down(0.6, 0.3), cells.clone()), Some((2, 2, 1)));
+    assert_eq!(nearest(down(1.6, 0.3), cells), None);
+    // the cell at the origin has no height and is not hit from the side
+    let side = Ray {
+        origin: Vec3::new(-5., 0.3, 0.6),
+        direction: Vec3::X,
+    };
+    assert_eq!(
+        nearest(side, level_1.iter().map(|cell| ((cell.i, cell.j), cell))),
+        Some((0, 1))
+    );
+}
+
+#[test]
+fn tooltip_text() {
+    let cells = core::level_cells(&f, &Domain::UNIT, 2, 1, Rule::Midpoint);
+    let cell = cells
+        .iter()
+        .find(|cell| (cell.i, cell.j) == (3, 0))
+        .unwrap();
+    let text = describe(cell, 2, 1);
+    assert_eq!(
+        text,
+        "Zelle (i, j) = (3, 0) auf Stufe 2\n\
+         x: 0.7500 bis 1.0000\n\
+         y: 0.0000 bis 0.2500\n\
+         Stützstelle: (0.8750, 0.1250)\n\
+         f = 1.00000\n\
+         Beitrag zur Summe: 0.000000\n\
+         (Änderung gegenüber Stufe 1, Höhe vorher 1.00000)"
+    );
+    assert!(!describe(cell, 2, 0).contains("Änderung"));
+}
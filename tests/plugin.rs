@@ -0,0 +1,173 @@
+//! Embeds the visualization into a host app through the public API only.
+
+use bevy::{
+    gizmos::GizmoPlugin,
+    input::{keyboard::KeyboardInput, ButtonState, InputPlugin},
+    prelude::*,
+};
+use integral::{
+    core::Rule, AddCubes, Config, DeleteCubes, IntegralPlugin, IntegralSet, Side, View,
+};
+
+#[derive(Resource, Default)]
+struct Seen {
+    add: Vec<AddCubes>,
+    delete: Vec<DeleteCubes>,
+    n_after_controls: Vec<u8>,
+}
+
+fn record(
+    mut seen: ResMut<Seen>,
+    mut add: EventReader<AddCubes>,
+    mut delete: EventReader<DeleteCubes>,
+) {
+    seen.add.extend(add.read().cloned());
+    seen.delete.extend(delete.read().cloned());
+}
+
+fn host_app(config: Config) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), InputPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Shader>()
+        .add_plugins((GizmoPlugin, IntegralPlugin { config }))
+        .init_resource::<Seen>()
+        .add_systems(Update, record.after(IntegralSet::Cubes))
+        .add_systems(
+            Update,
+            (|config: Res<Config>, mut seen: ResMut<Seen>| seen.n_after_controls.push(config.n))
+                .after(IntegralSet::Controls)
+                .before(IntegralSet::Cubes),
+        );
+    app.update();
+    app
+}
+
+#[test]
+fn plugin_uses_the_host_config() {
+    let app = host_app(Config {
+        n: 3,
+        show_incremental_cubes: false,
+        ..default()
+    });
+    let seen = app.world.resource::<Seen>();
+    assert_eq!(
+        seen.add,
+        [AddCubes {
+            view: View::Primary,
+            rule: Rule::LowerLeft,
+            prev_n: 0,
+            new_n: 3,
+            show_incremental_cubes: false
+        }]
+    );
+    assert_eq!(seen.n_after_controls, [3]);
+}
+
+#[test]
+fn runs_without_camera_and_ui() {
+    let mut app = host_app(Config::default());
+    app.world.resource_mut::<Config>().n = 2;
+    app.update();
+    let seen = app.world.resource::<Seen>();
+    assert_eq!(
+        seen.delete,
+        [DeleteCubes {
+            view: View::Primary,
+            new_n: 0
+        }]
+    );
+    assert_eq!(seen.add.last().unwrap().new_n, 2);
+    assert_eq!(seen.n_after_controls, [1, 2]);
+}
+
+#[test]
+fn keyboard_shortcuts_are_opt_in() {
+    let mut app = host_app(Config::default());
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(KeyCode::Right),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+    }
+    assert_eq!(app.world.resource::<Config>().n, 1);
+}
+
+#[test]
+fn host_can_send_cube_events() {
+    let mut app = host_app(Config::default());
+    app.world.send_event(AddCubes {
+        view: View::Primary,
+        rule: Rule::LowerLeft,
+        prev_n: 0,
+        new_n: 2,
+        show_incremental_cubes: true,
+    });
+    app.update();
+    let mut cubes = app
+        .world
+        .query_filtered::<&Visibility, With<Handle<Mesh>>>();
+    let visible = cubes
+        .iter(&app.world)
+        .filter(|vis| **vis != Visibility::Hidden)
+        .count();
+    // 3 cubes of level 1, 12 of level 2, the function graph and the grid
+    assert_eq!(visible, 3 + 12 + 1 + 1);
+}
+
+fn side(app: &mut App, view: View) -> Mut<'_, Side> {
+    let mut query = app.world.query::<(Entity, &View, &Side)>();
+    let (entity, _, _) = query
+        .iter(&app.world)
+        .find(|(_, v, _)| **v == view)
+        .unwrap();
+    app.world.get_mut::<Side>(entity).unwrap()
+}
+
+#[test]
+fn split_screen_sides_are_independent() {
+    let mut app = host_app(Config::default());
+    assert_eq!(app.world.resource::<Seen>().add.len(), 1);
+
+    app.world.resource_mut::<Config>().split_screen = true;
+    app.update();
+    let seen = app.world.resource::<Seen>();
+    assert_eq!(
+        seen.add.last().unwrap(),
+        &AddCubes {
+            view: View::Secondary,
+            rule: Rule::Midpoint,
+            prev_n: 0,
+            new_n: 1,
+            show_incremental_cubes: true
+        }
+    );
+
+    // the config only drives the primary side
+    app.world.resource_mut::<Config>().n = 3;
+    app.update();
+    assert_eq!(side(&mut app, View::Primary).n, 3);
+    assert_eq!(side(&mut app, View::Secondary).n, 1);
+
+    side(&mut app, View::Secondary).n = 2;
+    app.update();
+    let seen = app.world.resource::<Seen>();
+    assert_eq!(seen.add.last().unwrap().view, View::Secondary);
+    assert_eq!(seen.add.last().unwrap().new_n, 2);
+    assert_eq!(app.world.resource::<Config>().n, 3);
+
+    app.world.resource_mut::<Config>().split_screen = false;
+    app.update();
+    let seen = app.world.resource::<Seen>();
+    assert_eq!(
+        seen.delete.last().unwrap(),
+        &DeleteCubes {
+            view: View::Secondary,
+            new_n: 0
+        }
+    );
+}
@@ -0,0 +1,89 @@
+//! Saving and loading the scene file through the public API.
+
+use bevy::{gizmos::GizmoPlugin, input::InputPlugin, prelude::*};
+use integral::{
+    core::Rule,
+    scene,
+    views::{CameraView, CameraViews},
+    Config, IntegralPlugin,
+};
+
+fn host_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), InputPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<Shader>()
+        .add_plugins((GizmoPlugin, IntegralPlugin::default()));
+    app.update();
+    app
+}
+
+#[test]
+fn scene_round_trip() {
+    let mut app = host_app();
+    {
+        let mut config = app.world.resource_mut::<Config>();
+        config.n = 4;
+        config.rule = Rule::Midpoint;
+        config.show_function = false;
+        config.slice_position = 0.25;
+    }
+    let bookmark = CameraView {
+        focus: Vec3::new(0.5, 1., -0.5),
+        alpha: 0.75,
+        beta: -0.125,
+        radius: 3.5,
+    };
+    app.world
+        .resource_mut::<CameraViews>()
+        .bookmarks
+        .push(bookmark);
+    let saved = scene::to_string(&app.world);
+    assert!(saved.contains("\nn = 4\n"), "{saved}");
+    assert!(saved.contains("\nrule = Mittelpunkt\n"), "{saved}");
+    assert!(saved.contains("\nbookmark = 0.5, 1, -0.5, 0.75, -0.125, 3.5\n"));
+    // triggers have no value
+    assert!(!saved.contains("export"));
+
+    let mut other = host_app();
+    scene::apply(&mut other.world, &saved).unwrap();
+    let config = other.world.resource::<Config>();
+    assert_eq!(config.n, 4);
+    assert_eq!(config.rule, Rule::Midpoint);
+    assert!(!config.show_function);
+    assert_eq!(config.slice_position, 0.25);
+    assert_eq!(other.world.resource::<CameraViews>().bookmarks, [bookmark]);
+    assert_eq!(scene::to_string(&other.world), saved);
+}
+
+#[test]
+fn values_are_clamped_and_checked() {
+    let mut app = host_app();
+    scene::apply(&mut app.world, "# comment\n\nn = 99\nslice_position = -3").unwrap();
+    let config = app.world.resource::<Config>();
+    assert_eq!((config.n, config.slice_position), (6, 0.));
+
+    for (scene, error) in [
+        (
+            "n = 2\nrule = irgendwo",
+            "line 2: invalid value `irgendwo` for `rule`",
+        ),
+        (
+            "n = 2\nfunction = ja",
+            "line 2: invalid value `ja` for `function`",
+        ),
+        ("n = 2\nexport = true", "line 2: `export` has no value"),
+        ("n = 2\nnope = 1", "line 2: unknown key `nope`"),
+        ("n = 2\nbookmark = 1, 2", "line 2: invalid bookmark `1, 2`"),
+        (
+            "n = 2\nslice_position = NaN",
+            "line 2: invalid value `NaN` for `slice_position`",
+        ),
+        ("n 2", "line 1: expected `key = value`"),
+    ] {
+        assert_eq!(scene::apply(&mut app.world, scene), Err(error.to_string()));
+        // nothing is applied
+        assert_eq!(app.world.resource::<Config>().n, 6);
+    }
+}
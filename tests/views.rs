@@ -0,0 +1,58 @@
+//! The preset views and bookmarks on bare `PanOrbitCamera`s.
+
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+use bevy_panorbit_camera::PanOrbitCamera;
+use integral::views::{self, CameraView, CameraViews, Preset};
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-5, "{a} != {b}");
+}
+
+#[test]
+fn presets_move_the_camera_targets() {
+    let mut world = World::new();
+    world.init_resource::<CameraViews>();
+    let cameras = [
+        world.spawn(PanOrbitCamera::default()).id(),
+        world.spawn(PanOrbitCamera::default()).id(),
+    ];
+    for preset in Preset::ALL {
+        views::show_preset(&mut world, preset);
+        assert_eq!(world.resource::<CameraViews>().preset, preset);
+        for camera in cameras {
+            let camera = world.get::<PanOrbitCamera>(camera).unwrap();
+            // all presets are less than half a turn away from each other
+            assert_eq!(CameraView::of(camera), preset.view(), "{preset:?}");
+        }
+    }
+    assert_ne!(Preset::Top.view(), Preset::Front.view());
+}
+
+#[test]
+fn views_take_the_short_way_round() {
+    let view = |alpha| CameraView {
+        focus: Vec3::ZERO,
+        alpha,
+        beta: 0.,
+        radius: 1.,
+    };
+    let mut camera = PanOrbitCamera {
+        alpha: Some(2. * TAU + 0.1),
+        ..default()
+    };
+    view(0.2).apply(&mut camera);
+    assert_close(camera.target_alpha, 2. * TAU + 0.2);
+
+    // across the half turn
+    camera.alpha = Some(PI - 0.1);
+    view(-PI + 0.1).apply(&mut camera);
+    assert_close(camera.target_alpha, PI + 0.1);
+
+    // a camera that hasn't moved yet starts from its target
+    camera.alpha = None;
+    camera.target_alpha = -TAU;
+    view(0.5).apply(&mut camera);
+    assert_close(camera.target_alpha, -TAU + 0.5);
+}